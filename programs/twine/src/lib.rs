@@ -1,4 +1,4 @@
-use anchor_lang::{prelude::*, solana_program::clock::Clock};
+use anchor_lang::{prelude::*, solana_program::{clock::Clock, program_option::COption}, Discriminator};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token,
@@ -7,6 +7,7 @@ use anchor_spl::{
 
 declare_id!("GFyEm6g84oRmd156hPrJWsbWcsZaM4mhFf4654QgN5ae");
 
+/// the original payment mint. accepted mints are now registered in AcceptedMint accounts
 pub mod payment_token {
     use super::*;
     declare_id!("F6g9cmPtNAec9GYBF4s9vtX6hCE9eUxnFcv3bL8WsNuj");
@@ -14,13 +15,29 @@ pub mod payment_token {
 
 //const TIME_OFFSET: u32 = 1641024000; //twine timestamp epoch is seconds since 2022-01-01. This is the number of seconds since unix timestamp 1970-01-01.
 
-const PROGRAM_VERSION: u8 = 0;
-const STORE_VERSION : u8 = 0;
-const PRODUCT_VERSION: u8 = 0;
+const PROGRAM_VERSION: u8 = 2;
+const STORE_VERSION : u8 = 3;
+const PRODUCT_VERSION: u8 = 8;
 const PRODUCT_SNAPSHOT_METADATA_VERSION: u8 = 0;
-const PURCHASE_TICKET_VERSION: u8 = 0;
+const PURCHASE_TICKET_VERSION: u8 = 8;
 const TICKET_TAKER_VERSION: u8 = 0;
 const REDEMPTION_VERSION: u8 = 0;
+const ADMIN_MULTISIG_VERSION: u8 = 0;
+const ADMIN_PROPOSAL_VERSION: u8 = 0;
+const FEE_TIER_VERSION: u8 = 1;
+const REFERRAL_VERSION: u8 = 0;
+const ACCEPTED_MINT_VERSION: u8 = 0;
+const PRICE_FEED_VERSION: u8 = 0;
+const PAYOUT_SPLIT_VERSION: u8 = 0;
+const TICKET_LISTING_VERSION: u8 = 0;
+const SUBSCRIPTION_VERSION: u8 = 0;
+const COUPON_VERSION: u8 = 0;
+const COUPON_USAGE_VERSION: u8 = 0;
+const PRICE_TIERS_VERSION: u8 = 0;
+const AUCTION_VERSION: u8 = 0;
+const ORDER_VERSION: u8 = 0;
+const GIFT_VERSION: u8 = 0;
+const DISPUTE_VERSION: u8 = 0;
 
 const PROGRAM_METADATA_BYTES: &[u8] = b"program_metadata";
 const STORE_SEED_BYTES : &[u8] = b"store";
@@ -31,6 +48,29 @@ const PURCHASE_TICKET_BYTES : &[u8] = b"purchase_ticket";
 const REDEMPTION_BYTES: &[u8] = b"redemption";
 const PRODUCT_TAKER_BYTES: &[u8] = b"product_taker";
 const STORE_TAKER_BYTES: &[u8] = b"store_taker";
+const ADMIN_MULTISIG_BYTES: &[u8] = b"admin_multisig";
+const ADMIN_PROPOSAL_BYTES: &[u8] = b"admin_proposal";
+const FEE_TIER_BYTES: &[u8] = b"fee_tier";
+const REFERRAL_BYTES: &[u8] = b"referral";
+const ACCEPTED_MINT_BYTES: &[u8] = b"accepted_mint";
+const PRICE_FEED_BYTES: &[u8] = b"price_feed";
+const PAYOUT_SPLIT_BYTES: &[u8] = b"payout_split";
+const TICKET_LISTING_BYTES: &[u8] = b"ticket_listing";
+const SUBSCRIPTION_BYTES: &[u8] = b"subscription";
+const COUPON_BYTES: &[u8] = b"coupon";
+const COUPON_USAGE_BYTES: &[u8] = b"coupon_usage";
+const PRICE_TIERS_BYTES: &[u8] = b"price_tiers";
+const AUCTION_BYTES: &[u8] = b"auction";
+const ORDER_BYTES: &[u8] = b"order";
+const GIFT_BYTES: &[u8] = b"gift";
+const DISPUTE_BYTES: &[u8] = b"dispute";
+
+const BASIS_POINTS_DIVISOR: u64 = 10000;
+const NATIVE_PAYMENT_MINT: Pubkey = anchor_lang::system_program::ID; //payment_mint of lamport priced products and tickets
+const NATIVE_DECIMALS: u8 = 9;
+const PRICE_QUOTE_DECIMALS: u32 = 6; //products priced off a feed are quoted in millionths, ie micro USD
+const MAX_PRICE_FEED_EXPONENT: u32 = 18;
+const FEE_CHANGE_DELAY_SECONDS: i64 = 60 * 60 * 24; //fee changes are scheduled a day out, so buyers aren't surprised mid-purchase
 
 //const PURCHASE_TRANSACTION_FEE: u64 = 10000; //.01; USDC token has 6 decimals
 //const GENERAL_TRANSACTION_FEE: u64 = 5000000; //.005; SOL coin has 9 decimals
@@ -53,23 +93,498 @@ pub mod twine {
         program_metadata.authority = ctx.accounts.authority.key();
         program_metadata.secondary_authority = ctx.accounts.secondary_authority.key();
         program_metadata.fee_account = ctx.accounts.fee_account.key();
-        program_metadata.fee = fee;
+        program_metadata.fee = FeeSchedule { flat_fee: fee, ..FeeSchedule::default() };
+        program_metadata.pending_authority = Pubkey::default();
+        program_metadata.pending_secondary_authority = Pubkey::default();
+        program_metadata.pending_fee = FeeSchedule::default();
+        program_metadata.pending_fee_timestamp = 0;
+        program_metadata.paused = 0;
 
         Ok(())
     }
 
-    pub fn change_fee(ctx: Context<UpdateProgramMetadata>, fee: u64) -> Result<()> {
-        ctx.accounts.program_metadata.fee = fee;        
+    /// schedules the fee to take effect FEE_CHANGE_DELAY_SECONDS from now
+    pub fn change_fee(ctx: Context<UpdateProgramMetadata>, fee: FeeSchedule) -> Result<()> {
+        let clock = Clock::get()?;
+        fee.validate()?;
+        ctx.accounts.program_metadata.schedule_fee(fee, clock.unix_timestamp);
+        Ok(())
+    }
+
+    /// bitmask of PauseFlag. refund paths that aren't flagged stay open while purchases are paused
+    pub fn set_paused(ctx: Context<UpdateProgramMetadata>, paused: u64) -> Result<()> {
+        ctx.accounts.program_metadata.paused = paused;
         Ok(())
     }
 
+    /// fee_recipients are kept, so fees already collected in program_metadata's vaults can still be distributed
     pub fn change_fee_account(ctx: Context<ChangeFeeAccount>) -> Result<()> {
+        ctx.accounts.program_metadata.fee_account = ctx.accounts.fee_account.key();
+
+        Ok(())
+    }
+
+    /// fees are collected in token accounts owned by program_metadata and split between the recipients by distribute_fees
+    pub fn set_fee_recipients(ctx: Context<UpdateProgramMetadata>, fee_recipients: Vec<FeeRecipient>) -> Result<()> {
+        validate_fee_recipients(&fee_recipients)?;
+
         let program_metadata = &mut ctx.accounts.program_metadata;
-        program_metadata.fee_account = ctx.accounts.fee_account.key();
+        program_metadata.fee_account = program_metadata.key();
+        program_metadata.fee_recipients = fee_recipients;
+
+        Ok(())
+    }
+
+    /// permissionless. remaining accounts are the recipients' token accounts, in fee_recipients order.
+    /// still open after fee_account changes, for whatever was collected before
+    pub fn distribute_fees<'info>(ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>) -> Result<()> {
+        let program_metadata = &ctx.accounts.program_metadata;
+        let fee_vault = &ctx.accounts.fee_vault;
+        let fee_recipients = &program_metadata.fee_recipients;
+
+        if fee_recipients.is_empty() {
+            return Err(ErrorCode::NoFeeRecipients.into());
+        }
+
+        if ctx.remaining_accounts.len() != fee_recipients.len() {
+            return Err(ErrorCode::FeeRecipientMismatch.into());
+        }
+
+        let program_metadata_seeds = &[PROGRAM_METADATA_BYTES, &[program_metadata.bump]];
+        let fee_transfer_signer = &[&program_metadata_seeds[..]];
+        let total = fee_vault.amount;
+        let mut distributed: u64 = 0;
+
+        for (i, (fee_recipient, recipient_token_account_info)) in fee_recipients.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+            let recipient_token_account = Account::<TokenAccount>::try_from(recipient_token_account_info)?;
+            if recipient_token_account.owner != fee_recipient.wallet || recipient_token_account.mint != fee_vault.mint {
+                return Err(ErrorCode::FeeRecipientMismatch.into());
+            }
+
+            //the last recipient picks up rounding dust
+            let amount = if i == fee_recipients.len() - 1 {
+                total - distributed
+            } else {
+                apply_basis_points(total, fee_recipient.basis_points)?
+            };
+
+            if amount == 0 {
+                continue;
+            }
+
+            let fee_transfer_accounts = anchor_spl::token::Transfer {
+                from: fee_vault.to_account_info(),
+                to: recipient_token_account_info.clone(),
+                authority: program_metadata.to_account_info(),
+            };
+
+            let fee_transfer_cpicontext = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                fee_transfer_accounts,
+                fee_transfer_signer,
+            );
+
+            token::transfer(fee_transfer_cpicontext, amount)?;
+            distributed += amount;
+        }
+
+        Ok(())
+    }
+
+    /// permissionless. native fees collect as lamports in program_metadata. remaining accounts are the recipients' wallets, in fee_recipients order
+    pub fn distribute_native_fees<'info>(ctx: Context<'_, '_, '_, 'info, DistributeNativeFees<'info>>) -> Result<()> {
+        let program_metadata_info = ctx.accounts.program_metadata.to_account_info();
+        let fee_recipients = &ctx.accounts.program_metadata.fee_recipients;
+
+        if fee_recipients.is_empty() {
+            return Err(ErrorCode::NoFeeRecipients.into());
+        }
+
+        if ctx.remaining_accounts.len() != fee_recipients.len() {
+            return Err(ErrorCode::FeeRecipientMismatch.into());
+        }
+
+        let total = escrowed_lamports(&program_metadata_info)?;
+        let mut distributed: u64 = 0;
+
+        for (i, (fee_recipient, recipient_info)) in fee_recipients.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+            if *recipient_info.key != fee_recipient.wallet {
+                return Err(ErrorCode::FeeRecipientMismatch.into());
+            }
+
+            //the last recipient picks up rounding dust
+            let amount = if i == fee_recipients.len() - 1 {
+                total - distributed
+            } else {
+                apply_basis_points(total, fee_recipient.basis_points)?
+            };
+
+            release_escrowed_lamports(&program_metadata_info, recipient_info, amount)?;
+            distributed += amount;
+        }
+
+        Ok(())
+    }
+
+    pub fn propose_authority_change(ctx: Context<ProposeAuthorityChange>) -> Result<()> {
+        let program_metadata = &mut ctx.accounts.program_metadata;
+        program_metadata.pending_authority = ctx.accounts.new_authority.key();
+        program_metadata.pending_secondary_authority = ctx.accounts.new_secondary_authority.key();
+
+        Ok(())
+    }
+
+    pub fn cancel_authority_change(ctx: Context<UpdateProgramMetadata>) -> Result<()> {
+        let program_metadata = &mut ctx.accounts.program_metadata;
+        program_metadata.pending_authority = Pubkey::default();
+        program_metadata.pending_secondary_authority = Pubkey::default();
+
+        Ok(())
+    }
+
+    pub fn accept_authority_change(ctx: Context<AcceptAuthorityChange>) -> Result<()> {
+        let program_metadata = &mut ctx.accounts.program_metadata;
+
+        if program_metadata.pending_authority == Pubkey::default() {
+            return Err(ErrorCode::NoPendingAuthorityChange.into());
+        }
+
+        program_metadata.authority = program_metadata.pending_authority;
+        program_metadata.secondary_authority = program_metadata.pending_secondary_authority;
+        program_metadata.pending_authority = Pubkey::default();
+        program_metadata.pending_secondary_authority = Pubkey::default();
+
+        Ok(())
+    }
+
+    /// Hands program administration over to an M-of-N signer set.
+    /// Once set, program settings, fee tiers, accepted mints and price feeds only change through admin proposals.
+    /// The authorities are cleared until a CHANGE_AUTHORITY proposal installs the keys that enable and disable accepted mints.
+    pub fn create_admin_multisig(ctx: Context<CreateAdminMultisig>, threshold: u8, signers: Vec<Pubkey>) -> Result<()> {
+        validate_admin_signers(threshold, &signers)?;
+
+        let admin_multisig = &mut ctx.accounts.admin_multisig;
+        admin_multisig.bump = *ctx.bumps.get("admin_multisig").unwrap();
+        admin_multisig.version = ADMIN_MULTISIG_VERSION;
+        admin_multisig.threshold = threshold;
+        admin_multisig.signers_version = 0;
+        admin_multisig.proposal_count = 0;
+        admin_multisig.signers = signers;
+
+        let program_metadata = &mut ctx.accounts.program_metadata;
+        program_metadata.multisig = admin_multisig.key();
+        program_metadata.authority = Pubkey::default();
+        program_metadata.secondary_authority = Pubkey::default();
+        program_metadata.pending_authority = Pubkey::default();
+        program_metadata.pending_secondary_authority = Pubkey::default();
+
+        Ok(())
+    }
+
+    pub fn create_admin_proposal(ctx: Context<CreateAdminProposal>, action: u8, fee: FeeSchedule, fee_account: Pubkey,
+        authority: Pubkey, secondary_authority: Pubkey, threshold: u8, signers: Vec<Pubkey>, paused: u64,
+        fee_recipients: Vec<FeeRecipient>) -> Result<()> {
+        let clock = Clock::get()?;
+        let admin_multisig = &mut ctx.accounts.admin_multisig;
+        let proposal = &mut ctx.accounts.proposal;
+
+        let signer_index = match admin_multisig.signer_index(&ctx.accounts.proposer.key) {
+            Some(i) => i,
+            None => return Err(ErrorCode::NotAMultisigSigner.into()),
+        };
+
+        match action {
+            AdminAction::CHANGE_FEE => fee.validate()?,
+            AdminAction::CHANGE_FEE_ACCOUNT => {
+                if fee_account == Pubkey::default() {
+                    return Err(ErrorCode::InvalidAdminAction.into());
+                }
+            },
+            AdminAction::CHANGE_AUTHORITY => {
+                if authority == Pubkey::default() {
+                    return Err(ErrorCode::InvalidAdminAction.into());
+                }
+            },
+            AdminAction::CHANGE_SIGNERS => validate_admin_signers(threshold, &signers)?,
+            AdminAction::SET_PAUSED => {},
+            AdminAction::SET_FEE_RECIPIENTS => validate_fee_recipients(&fee_recipients)?,
+            _ => return Err(ErrorCode::InvalidAdminAction.into()),
+        }
+
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+        proposal.version = ADMIN_PROPOSAL_VERSION;
+        proposal.id = admin_multisig.proposal_count;
+        proposal.action = action;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.signers_version = admin_multisig.signers_version;
+        proposal.approvals = 1 << signer_index;
+        proposal.executed = false;
+        proposal.created_slot = clock.slot;
+        proposal.created_timestamp = clock.unix_timestamp;
+        proposal.executed_slot = 0;
+        proposal.executed_timestamp = 0;
+        proposal.fee = fee;
+        proposal.fee_account = fee_account;
+        proposal.authority = authority;
+        proposal.secondary_authority = secondary_authority;
+        proposal.threshold = threshold;
+        proposal.signers = signers;
+        proposal.paused = paused;
+        proposal.fee_recipients = fee_recipients;
+
+        admin_multisig.proposal_count += 1;
+
+        Ok(())
+    }
+
+    pub fn approve_admin_proposal(ctx: Context<ApproveAdminProposal>) -> Result<()> {
+        let admin_multisig = &ctx.accounts.admin_multisig;
+        let proposal = &mut ctx.accounts.proposal;
+
+        if proposal.executed {
+            return Err(ErrorCode::AlreadyProcessed.into());
+        }
+
+        if proposal.signers_version != admin_multisig.signers_version {
+            return Err(ErrorCode::StaleAdminProposal.into());
+        }
+
+        let signer_index = match admin_multisig.signer_index(&ctx.accounts.signer.key) {
+            Some(i) => i,
+            None => return Err(ErrorCode::NotAMultisigSigner.into()),
+        };
+
+        if proposal.approvals & (1 << signer_index) != 0 {
+            return Err(ErrorCode::AlreadyApproved.into());
+        }
+
+        proposal.approvals |= 1 << signer_index;
+
+        Ok(())
+    }
+
+    pub fn execute_admin_proposal(ctx: Context<ExecuteAdminProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let admin_multisig = &mut ctx.accounts.admin_multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let program_metadata = &mut ctx.accounts.program_metadata;
+
+        if proposal.executed {
+            return Err(ErrorCode::AlreadyProcessed.into());
+        }
+
+        if proposal.signers_version != admin_multisig.signers_version {
+            return Err(ErrorCode::StaleAdminProposal.into());
+        }
+
+        if proposal.approvals.count_ones() < u32::from(admin_multisig.threshold) {
+            return Err(ErrorCode::NotEnoughApprovals.into());
+        }
+
+        match proposal.action {
+            AdminAction::CHANGE_FEE => {
+                program_metadata.schedule_fee(proposal.fee, clock.unix_timestamp);
+            },
+            AdminAction::CHANGE_FEE_ACCOUNT => {
+                program_metadata.fee_account = proposal.fee_account;
+            },
+            AdminAction::CHANGE_AUTHORITY => {
+                program_metadata.authority = proposal.authority;
+                program_metadata.secondary_authority = proposal.secondary_authority;
+            },
+            AdminAction::CHANGE_SIGNERS => {
+                admin_multisig.threshold = proposal.threshold;
+                admin_multisig.signers = proposal.signers.clone();
+                admin_multisig.signers_version += 1;
+            },
+            AdminAction::SET_PAUSED => {
+                program_metadata.paused = proposal.paused;
+            },
+            AdminAction::SET_FEE_RECIPIENTS => {
+                program_metadata.fee_account = program_metadata.key();
+                program_metadata.fee_recipients = proposal.fee_recipients.clone();
+            },
+            _ => return Err(ErrorCode::InvalidAdminAction.into()),
+        }
+
+        proposal.executed = true;
+        proposal.executed_slot = clock.slot;
+        proposal.executed_timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn create_store_fee_tier(ctx: Context<CreateStoreFeeTier>, fee: FeeSchedule, expiration_timestamp: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let fee_tier = &mut ctx.accounts.fee_tier;
+        fee.validate()?;
+
+        fee_tier.bump = *ctx.bumps.get("fee_tier").unwrap();
+        fee_tier.version = FEE_TIER_VERSION;
+        fee_tier.entity_type = EntityType::STORE;
+        fee_tier.entity = ctx.accounts.store.key();
+        fee_tier.authorized_by = ctx.accounts.authority.key();
+        fee_tier.fee = fee;
+        fee_tier.expiration_timestamp = expiration_timestamp;
+        fee_tier.slot = clock.slot;
+        fee_tier.timestamp = clock.unix_timestamp;
+        fee_tier.pending_fee = FeeSchedule::default();
+        fee_tier.pending_expiration_timestamp = 0;
+        fee_tier.pending_fee_timestamp = 0;
+
+        ctx.accounts.store.fee_tier = fee_tier.key();
+
+        Ok(())
+    }
+
+    pub fn create_creator_fee_tier(ctx: Context<CreateCreatorFeeTier>, fee: FeeSchedule, expiration_timestamp: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let fee_tier = &mut ctx.accounts.fee_tier;
+        fee.validate()?;
+
+        fee_tier.bump = *ctx.bumps.get("fee_tier").unwrap();
+        fee_tier.version = FEE_TIER_VERSION;
+        fee_tier.entity_type = EntityType::CREATOR;
+        fee_tier.entity = ctx.accounts.creator.key();
+        fee_tier.authorized_by = ctx.accounts.authority.key();
+        fee_tier.fee = fee;
+        fee_tier.expiration_timestamp = expiration_timestamp;
+        fee_tier.slot = clock.slot;
+        fee_tier.timestamp = clock.unix_timestamp;
+        fee_tier.pending_fee = FeeSchedule::default();
+        fee_tier.pending_expiration_timestamp = 0;
+        fee_tier.pending_fee_timestamp = 0;
+
+        Ok(())
+    }
+
+    /// schedules the tier's fee and expiration to take effect FEE_CHANGE_DELAY_SECONDS from now
+    pub fn update_fee_tier(ctx: Context<UpdateFeeTier>, fee: FeeSchedule, expiration_timestamp: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let fee_tier = &mut ctx.accounts.fee_tier;
+        fee.validate()?;
+
+        fee_tier.authorized_by = ctx.accounts.authority.key();
+        fee_tier.schedule_fee(fee, expiration_timestamp, clock.unix_timestamp);
+        fee_tier.slot = clock.slot;
+        fee_tier.timestamp = clock.unix_timestamp;
 
         Ok(())
     }
     
+    pub fn add_accepted_mint(ctx: Context<AddAcceptedMint>) -> Result<()> {
+        let clock = Clock::get()?;
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+
+        accepted_mint.bump = *ctx.bumps.get("accepted_mint").unwrap();
+        accepted_mint.version = ACCEPTED_MINT_VERSION;
+        accepted_mint.mint = ctx.accounts.mint.key();
+        accepted_mint.enabled = true;
+        accepted_mint.fee_override = false;
+        accepted_mint.fee = FeeSchedule::default();
+        accepted_mint.pending_fee_override = false;
+        accepted_mint.pending_fee = FeeSchedule::default();
+        accepted_mint.pending_fee_timestamp = 0;
+        accepted_mint.slot = clock.slot;
+        accepted_mint.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// registers lamports as a payment option. native products use NATIVE_PAYMENT_MINT as their payment_mint
+    pub fn add_native_accepted_mint(ctx: Context<AddNativeAcceptedMint>) -> Result<()> {
+        let clock = Clock::get()?;
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+
+        accepted_mint.bump = *ctx.bumps.get("accepted_mint").unwrap();
+        accepted_mint.version = ACCEPTED_MINT_VERSION;
+        accepted_mint.mint = NATIVE_PAYMENT_MINT;
+        accepted_mint.enabled = true;
+        accepted_mint.fee_override = false;
+        accepted_mint.fee = FeeSchedule::default();
+        accepted_mint.pending_fee_override = false;
+        accepted_mint.pending_fee = FeeSchedule::default();
+        accepted_mint.pending_fee_timestamp = 0;
+        accepted_mint.slot = clock.slot;
+        accepted_mint.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// disabled mints can't be used for new purchases. tickets already paid with them can still be redeemed, transferred and cancelled
+    pub fn set_accepted_mint_enabled(ctx: Context<UpdateAcceptedMint>, enabled: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+
+        accepted_mint.enabled = enabled;
+        accepted_mint.slot = clock.slot;
+        accepted_mint.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// schedules the mint's fee to take effect FEE_CHANGE_DELAY_SECONDS from now. without an override the program fee applies
+    pub fn change_accepted_mint_fee(ctx: Context<ChangeAcceptedMintFee>, fee_override: bool, fee: FeeSchedule) -> Result<()> {
+        let clock = Clock::get()?;
+        fee.validate()?;
+
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+        accepted_mint.schedule_fee(fee_override, fee, clock.unix_timestamp);
+        accepted_mint.slot = clock.slot;
+        accepted_mint.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// feeds are priced in PRICE_QUOTE_DECIMALS per whole token of the mint. the updater keeps them current
+    pub fn create_price_feed(ctx: Context<CreatePriceFeed>, max_age_seconds: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let price_feed = &mut ctx.accounts.price_feed;
+
+        price_feed.bump = *ctx.bumps.get("price_feed").unwrap();
+        price_feed.version = PRICE_FEED_VERSION;
+        price_feed.mint = ctx.accounts.accepted_mint.mint;
+        price_feed.updater = ctx.accounts.updater.key();
+        price_feed.price = 0;
+        price_feed.exponent = 0;
+        price_feed.publish_time = 0;
+        price_feed.max_age_seconds = max_age_seconds;
+        price_feed.slot = clock.slot;
+        price_feed.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn configure_price_feed(ctx: Context<ConfigurePriceFeed>, max_age_seconds: u32) -> Result<()> {
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.updater = ctx.accounts.updater.key();
+        price_feed.max_age_seconds = max_age_seconds;
+
+        Ok(())
+    }
+
+    /// price of one whole token is price * 10^exponent in the quote currency
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: i64, exponent: i32, publish_time: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let price_feed = &mut ctx.accounts.price_feed;
+
+        if price <= 0 || exponent.unsigned_abs() > MAX_PRICE_FEED_EXPONENT {
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+
+        if publish_time < price_feed.publish_time || publish_time > clock.unix_timestamp {
+            return Err(ErrorCode::InvalidPriceFeedPublishTime.into());
+        }
+
+        price_feed.price = price;
+        price_feed.exponent = exponent;
+        price_feed.publish_time = publish_time;
+        price_feed.slot = clock.slot;
+        price_feed.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
     pub fn create_store(ctx: Context<CreateStore>, id: u16, status: u8, name: String, description: String, data: Vec<u8>) -> Result<()> {
         let store = &mut ctx.accounts.store;
 
@@ -93,6 +608,9 @@ pub mod twine {
         store.name = name;
         store.description = description;
         store.data = data;
+        store.fee_tier = Pubkey::default();
+        store.payout_split = Pubkey::default();
+        store.arbiter = Pubkey::default();
 
         Ok(())
     }
@@ -153,6 +671,14 @@ pub mod twine {
         product.name = name;
         product.description = description;
         product.data = data;
+        product.payment_mint = ctx.accounts.accepted_mint.mint;
+        product.price_feed = Pubkey::default();
+        product.payout_split = Pubkey::default();
+        product.royalty_basis_points = 0;
+        product.resale_policy = ResalePolicy::default();
+        product.layaway_policy = LayawayPolicy::default();
+        product.subscription_period_minutes = 0;
+        product.dutch_auction = DutchAuction::default();
 
         Ok(())
     }
@@ -193,6 +719,14 @@ pub mod twine {
         product.name = name;
         product.description = description;
         product.data = data;
+        product.payment_mint = ctx.accounts.accepted_mint.mint;
+        product.price_feed = Pubkey::default();
+        product.payout_split = store.payout_split; //store's split is the default for its products
+        product.royalty_basis_points = 0;
+        product.resale_policy = ResalePolicy::default();
+        product.layaway_policy = LayawayPolicy::default();
+        product.subscription_period_minutes = 0;
+        product.dutch_auction = DutchAuction::default();
 
         store.product_count += 1;
 
@@ -231,406 +765,711 @@ pub mod twine {
     }    
 
 
-    pub fn buy_product(ctx: Context<BuyProduct>, nonce: u16, quantity: u64,
-        agreed_price: u64, agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32) -> Result<()>{
-        
-        let product = &mut ctx.accounts.product;
-        let buyer = &mut ctx.accounts.buyer;
-        let product_snapshot_metadata = &mut ctx.accounts.product_snapshot_metadata;
-        let product_snapshot = &mut ctx.accounts.product_snapshot;
-        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
-        let purchase_ticket_payment = &mut ctx.accounts.purchase_ticket_payment;
-        let pay_to = &ctx.accounts.pay_to;
-        let pay_to_token_account = &ctx.accounts.pay_to_token_account;
-        let token_program = &ctx.accounts.token_program;
-        let fee_token_account = &mut ctx.accounts.fee_token_account;
-        let fee = ctx.accounts.program_metadata.fee;
-        let clock = Clock::get()?;
-        let total_purchase_price = product.price * quantity;
- 
-        if product.status != ProductStatus::ACTIVE {
-            return Err(ErrorCode::ProductIsNotActive.into());
+    /// splits are immutable, so tickets that recorded one always pay out the same way
+    pub fn create_payout_split(ctx: Context<CreatePayoutSplit>, id: u32, recipients: Vec<PayoutRecipient>) -> Result<()> {
+        validate_payout_recipients(&recipients)?;
+
+        let payout_split = &mut ctx.accounts.payout_split;
+        payout_split.bump = *ctx.bumps.get("payout_split").unwrap();
+        payout_split.version = PAYOUT_SPLIT_VERSION;
+        payout_split.creator = ctx.accounts.creator.key();
+        payout_split.id = id;
+        payout_split.recipients = recipients;
+
+        Ok(())
+    }
+
+    /// passing the system program as the payout_split pays the store's products' pay_to again
+    pub fn set_store_payout_split(ctx: Context<SetStorePayoutSplit>) -> Result<()> {
+        let payout_split_info = &ctx.accounts.payout_split;
+
+        if *payout_split_info.key != Pubkey::default() {
+            load_payout_split(payout_split_info)?;
         }
 
-        if product.inventory < quantity {
-            return Err(ErrorCode::NotEnoughInventory.into());
+        ctx.accounts.store.payout_split = payout_split_info.key();
+
+        Ok(())
+    }
+
+    /// the arbiter is recorded on tickets bought after it's set and rules on their disputes. passing the system program
+    /// clears it. the store can't arbitrate its own tickets
+    pub fn set_store_arbiter(ctx: Context<SetStoreArbiter>) -> Result<()> {
+        let arbiter = ctx.accounts.arbiter.key();
+        if arbiter != Pubkey::default() && ctx.accounts.store.is_authorized(&arbiter) {
+            return Err(ErrorCode::InvalidArbiter.into());
         }
 
-        if product.price > agreed_price {
-            return Err(ErrorCode::PriceIsGreaterThanPayment.into());
+        ctx.accounts.store.arbiter = arbiter;
+
+        Ok(())
+    }
+
+    /// passing the system program as the payout_split pays pay_to again
+    pub fn set_product_payout_split(ctx: Context<SetProductPayoutSplit>) -> Result<()> {
+        let product = &mut ctx.accounts.product;
+        let payout_split_info = &ctx.accounts.payout_split;
+
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
         }
 
-        //msg!("expiration: {}/{}, afterPurchase: {}/{}, afterRedemption: {}/{}", 
-        //    product.expiration_timestamp, agreed_expiration_timestamp,
-        //    product.expiration_minutes_after_purchase, agreed_expiration_minutes_after_purchase,
-        //    product.expiration_minutes_after_redemption, agreed_expiration_minutes_after_redemption
-        //);
-        if product.expiration_timestamp != agreed_expiration_timestamp {
-            return Err(ErrorCode::AgreedExpirationDoesntMatch.into())
+        if *payout_split_info.key != Pubkey::default() {
+            load_payout_split(payout_split_info)?;
         }
 
-        if product.expiration_minutes_after_purchase != agreed_expiration_minutes_after_purchase {
-            return Err(ErrorCode::AgreedExpirationAfterPurchaseDoesntMatch.into())
+        product.payout_split = payout_split_info.key();
+
+        Ok(())
+    }
+
+    /// passing the system program as the price_feed clears it
+    pub fn set_product_price_feed(ctx: Context<SetProductPriceFeed>) -> Result<()> {
+        let product = &mut ctx.accounts.product;
+        let price_feed_info = &ctx.accounts.price_feed;
+
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
         }
-        if product.expiration_minutes_after_redemption != agreed_expiration_minutes_after_redemption {
-            return Err(ErrorCode::AgreedExpirationAfterRedemptionDoesntMatch.into())
+
+        if *price_feed_info.key == Pubkey::default() {
+            product.price_feed = Pubkey::default();
+            return Ok(());
         }
 
-        if product.is_snapshot {
-            return Err(ErrorCode::UnableToPurchaseSnapshot.into());
+        if *price_feed_info.owner != crate::ID {
+            return Err(ErrorCode::PublicKeyMismatch.into());
         }
 
-        if product.expiration_timestamp > 0 && product.expiration_timestamp < clock.unix_timestamp {
-            return Err(ErrorCode::ProductIsExpired.into());
+        let price_feed = PriceFeed::try_deserialize(&mut &price_feed_info.data.borrow()[..])?;
+        if price_feed.version != PRICE_FEED_VERSION {
+            return Err(ErrorCode::UnsupportedAccountVersion.into());
         }
-        
-        if purchase_ticket_payment.amount < (total_purchase_price + fee) {
-            return Err(ErrorCode::InsufficientFunds.into());
+
+        if price_feed.mint != product.payment_mint {
+            return Err(ErrorCode::PaymentMintMismatch.into());
         }
-   
-        require_keys_eq!(pay_to.key(), product.pay_to.key());
 
-        let purchase_ticket_seed_bump = *ctx.bumps.get("purchase_ticket").unwrap();
-        let product_snapshot_metadata_key = product_snapshot_metadata.key();
-        let buyer_key = buyer.key();
-        let purchase_ticket_seeds = &[
-            PURCHASE_TICKET_BYTES,
-            product_snapshot_metadata_key.as_ref(),
-            buyer_key.as_ref(),
-            &nonce.to_be_bytes(),
-            &[purchase_ticket_seed_bump]
-        ];
-        let payment_transfer_signer = &[&purchase_ticket_seeds[..]];       
+        product.price_feed = price_feed_info.key();
 
-        //fee transfer        
-        let fee_transfer_accounts = anchor_spl::token::Transfer {
-            from: purchase_ticket_payment.to_account_info(),
-            to: fee_token_account.to_account_info(),
-            authority: purchase_ticket.to_account_info(), //ata owned by twine program
-        };
+        Ok(())
+    }
 
-        let fee_transfer_cpicontext = CpiContext::new_with_signer(
-    token_program.to_account_info(),
-            fee_transfer_accounts,
-            payment_transfer_signer,
-        );
+    /// royalties are snapshotted with the product, so a change only applies to tickets bought afterwards
+    pub fn set_product_royalty(ctx: Context<SetProductTerms>, royalty_basis_points: u16) -> Result<()> {
+        let product = &mut ctx.accounts.product;
 
-        let _fee_transfer_result = token::transfer(fee_transfer_cpicontext, fee)?;
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
+        }
 
-        if product.redemption_type == RedemptionType::IMMEDIATE {  //release payment if redemption type is immediate
+        if u64::from(royalty_basis_points) > BASIS_POINTS_DIVISOR {
+            return Err(ErrorCode::InvalidRoyaltyRate.into());
+        }
 
-            let payment_transfer_accounts = anchor_spl::token::Transfer {
-                from: purchase_ticket_payment.to_account_info(),
-                to: pay_to_token_account .to_account_info(),
-                authority: purchase_ticket.to_account_info(), //ata owned by twine program
-            };
+        product.royalty_basis_points = royalty_basis_points;
 
-            let payment_transfer_cpicontext = CpiContext::new_with_signer(
-                token_program.to_account_info(),
-                payment_transfer_accounts,
-                payment_transfer_signer,
-            );
+        Ok(())
+    }
 
-            let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, product.price)?;
-            
-            purchase_ticket.redeemed = quantity;
-            purchase_ticket.remaining_quantity = 0;
-        }
-        else {
-            purchase_ticket.remaining_quantity = quantity;
-            purchase_ticket.redeemed = 0;
+    /// like royalties, the policy is snapshotted with the product and applies to tickets bought afterwards
+    pub fn set_product_resale_policy(ctx: Context<SetProductTerms>, resale_policy: ResalePolicy) -> Result<()> {
+        let product = &mut ctx.accounts.product;
+
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
         }
 
-        product_snapshot_metadata.bump = *ctx.bumps.get("product_snapshot_metadata").unwrap();
-        product_snapshot_metadata.version = PRODUCT_SNAPSHOT_METADATA_VERSION;
-        product_snapshot_metadata.slot = clock.slot;
-        product_snapshot_metadata.timestamp = clock.unix_timestamp;
-        product_snapshot_metadata.product = product.key();
-        product_snapshot_metadata.product_snapshot =  product_snapshot.key();
-        product_snapshot_metadata.nonce = nonce;
-
-        purchase_ticket.bump = *ctx.bumps.get("purchase_ticket").unwrap();
-        purchase_ticket.version = PURCHASE_TICKET_VERSION;
-        purchase_ticket.slot = clock.slot;
-        purchase_ticket.timestamp = clock.unix_timestamp;
-        purchase_ticket.product = product.key();
-        purchase_ticket.product_snapshot_metadata = product_snapshot_metadata.key();
-        purchase_ticket.product_snapshot = product_snapshot.key();
-        purchase_ticket.buyer = buyer.key();
-        purchase_ticket.pay_to = pay_to.key();
-        purchase_ticket.authority = ctx.accounts.buy_for.key();
-        purchase_ticket.nonce = nonce;
-        purchase_ticket.price = product.price;
-        purchase_ticket.store = product.store;
-        purchase_ticket.payment = purchase_ticket_payment.key();
-        purchase_ticket.expiration_minutes_after_redemption = product.expiration_minutes_after_redemption;
+        product.resale_policy = resale_policy;
 
-        if product.expiration_minutes_after_purchase > 0 {
-            purchase_ticket.expiration = clock.unix_timestamp + (i64::from(product.expiration_minutes_after_purchase) * 60);
-        }
+        Ok(())
+    }
+
+    pub fn set_product_layaway_policy(ctx: Context<SetProductTerms>, layaway_policy: LayawayPolicy) -> Result<()> {
+        let product = &mut ctx.accounts.product;
 
-        if product.expiration_timestamp > 0 && (purchase_ticket.expiration == 0 || product.expiration_timestamp < purchase_ticket.expiration) {
-            purchase_ticket.expiration = product.expiration_timestamp;
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
         }
 
-        let product_clone = product.clone().into_inner();
-        ctx.accounts.product_snapshot.set_inner(product_clone);
-        ctx.accounts.product_snapshot.is_snapshot = true;
+        if u64::from(layaway_policy.min_deposit_basis_points) > BASIS_POINTS_DIVISOR || u64::from(layaway_policy.forfeit_basis_points) > BASIS_POINTS_DIVISOR {
+            return Err(ErrorCode::InvalidLayawayPolicy.into());
+        }
 
-        product.inventory -= quantity;
+        product.layaway_policy = layaway_policy;
 
         Ok(())
     }
 
-    pub fn create_store_ticket_taker(ctx: Context<CreateStoreTicketTaker>) -> Result<()> {
-        let clock = Clock::get()?;
-        let ticket_taker = &mut ctx.accounts.ticket_taker;
-    
-        ticket_taker.bump = *ctx.bumps.get("ticket_taker").unwrap();
-        ticket_taker.version = TICKET_TAKER_VERSION;
-        ticket_taker.taker = ctx.accounts.taker.key();
-        ticket_taker.entity_type = EntityType::STORE;
-        ticket_taker.entity = ctx.accounts.store.key();
-        ticket_taker.authorized_by = ctx.accounts.store_authority.key();
-        ticket_taker.enabled_slot = clock.slot;
-        ticket_taker.enabled_timestamp = clock.unix_timestamp;
-        ticket_taker.disabled_slot = 0;
-        ticket_taker.disabled_timestamp = 0;
-    
-        Ok(())
-    }
-    
-    pub fn create_product_ticket_taker(ctx: Context<CreateProductTicketTaker>) -> Result<()> {
-        let clock = Clock::get()?;
-        let ticket_taker = &mut ctx.accounts.ticket_taker;
-    
-        ticket_taker.bump = *ctx.bumps.get("ticket_taker").unwrap();
-        ticket_taker.version = TICKET_TAKER_VERSION;
-        ticket_taker.taker = ctx.accounts.taker.key();
-        ticket_taker.entity_type = EntityType::PRODUCT;
-        ticket_taker.entity = ctx.accounts.product.key();
-        ticket_taker.authorized_by = ctx.accounts.product_authority.key();
-        ticket_taker.enabled_slot = clock.slot;
-        ticket_taker.enabled_timestamp = clock.unix_timestamp;
-        ticket_taker.disabled_slot = 0;
-        ticket_taker.disabled_timestamp = 0;
-    
+    /// existing subscriptions keep the period they signed up with
+    pub fn set_product_subscription_period(ctx: Context<SetProductTerms>, subscription_period_minutes: u32) -> Result<()> {
+        let product = &mut ctx.accounts.product;
+
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
+        }
+
+        product.subscription_period_minutes = subscription_period_minutes;
+
         Ok(())
     }
 
-    pub fn initiate_redemption(ctx: Context<InitiateRedemption>, nonce: u32, quantity: u64, take_expiration_minutes: u32) -> Result<()> {
-        let clock = Clock::get()?;
-        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
-        let purchase_ticket_payment = &ctx.accounts.purchase_ticket_payment;
-        let total_purchase_price = purchase_ticket.price * quantity;
-        
-        if quantity <= 0 {
-            return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
-        }
+    /// the declining price replaces price on every purchase path. a start_price of 0 goes back to price
+    pub fn set_product_dutch_auction(ctx: Context<SetProductTerms>, dutch_auction: DutchAuction) -> Result<()> {
+        let product = &mut ctx.accounts.product;
 
-        if quantity > purchase_ticket.remaining_quantity {
-            return Err(ErrorCode::InsufficientRemainingRedemptions.into());
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
         }
 
-        if purchase_ticket_payment.amount < total_purchase_price {
-            return Err(ErrorCode::InsufficientFunds.into());
+        if dutch_auction.start_price > 0 && dutch_auction.floor_price > dutch_auction.start_price {
+            return Err(ErrorCode::InvalidDutchAuction.into());
         }
 
-        if purchase_ticket.expiration > 0 && purchase_ticket.expiration < clock.unix_timestamp {
-            return Err(ErrorCode::TicketIsExpired.into());
-        }
+        product.dutch_auction = dutch_auction;
 
-        let redemption = &mut ctx.accounts.redemption;
-        redemption.bump = *ctx.bumps.get("redemption").unwrap();
-        redemption.version = REDEMPTION_VERSION;        
-        redemption.init_slot = clock.slot;
-        redemption.init_timestamp = clock.unix_timestamp;
-        redemption.close_slot=0;
-        redemption.close_timestamp = 0;
-        redemption.store = purchase_ticket.store;
-        redemption.product = purchase_ticket.product;
-        redemption.product_snapshot_metadata = purchase_ticket.product_snapshot_metadata;
-        redemption.product_snapshot = purchase_ticket.product_snapshot;
-        redemption.purchase_ticket = purchase_ticket.key();
-        redemption.buyer = purchase_ticket.buyer;
-        redemption.pay_to = purchase_ticket.pay_to;
-        redemption.purchase_ticket_signer = ctx.accounts.purchase_ticket_authority.key();
-        //redemption.purchase_ticket_remaining_quantity = purchase_ticket.remaining_quantity;
-        redemption.redeem_quantity = quantity;
-        redemption.price = purchase_ticket.price;
-        redemption.status = RedemptionStatus::WAITING;
-        redemption.nonce = nonce;
+        Ok(())
+    }
 
-        if take_expiration_minutes > 0 {
-            redemption.take_expiration = clock.unix_timestamp + (i64::from(take_expiration_minutes) * 60);
+    /// volume discounts for buy_product. an empty list removes them
+    pub fn set_product_price_tiers(ctx: Context<SetProductPriceTiers>, tiers: Vec<PriceTier>) -> Result<()> {
+        if ctx.accounts.product.is_snapshot {
+            return Err(ErrorCode::UnableToModifySnapshot.into());
         }
 
-        purchase_ticket.remaining_quantity -= quantity;
-        purchase_ticket.pending_redemption += quantity;
+        validate_price_tiers(&tiers)?;
+
+        let price_tiers = &mut ctx.accounts.price_tiers;
+        price_tiers.bump = *ctx.bumps.get("price_tiers").unwrap();
+        price_tiers.version = PRICE_TIERS_VERSION;
+        price_tiers.product = ctx.accounts.product.key();
+        price_tiers.tiers = tiers;
 
         Ok(())
     }
-    
-    pub fn take_redemption(ctx: Context<TakeRedemption>) -> Result<()>{ 
-        let clock = Clock::get()?;
+
+    /// remaining accounts are the payout split recipients' token accounts, in order, when the product has a payout split
+    pub fn buy_product<'info>(ctx: Context<'_, '_, '_, 'info, BuyProduct<'info>>, nonce: u16, quantity: u64,
+        agreed_price: u64, agreed_fee: u64, agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32) -> Result<()>{
+        
+        let product = &mut ctx.accounts.product;
+        let buyer = &mut ctx.accounts.buyer;
+        let product_snapshot_metadata = &mut ctx.accounts.product_snapshot_metadata;
+        let product_snapshot = &mut ctx.accounts.product_snapshot;
         let purchase_ticket = &mut ctx.accounts.purchase_ticket;
-        let purchase_ticket_payment = &ctx.accounts.purchase_ticket_payment;
+        let purchase_ticket_payment = &mut ctx.accounts.purchase_ticket_payment;
+        let pay_to = &ctx.accounts.pay_to;
         let pay_to_token_account = &ctx.accounts.pay_to_token_account;
         let token_program = &ctx.accounts.token_program;
-        let ticket_taker = &ctx.accounts.ticket_taker;
-        let redemption = &mut ctx.accounts.redemption;
-
-        if redemption.ticket_taker != Pubkey::default() {
-            return Err(ErrorCode::AlreadyRedeemed.into());
-        }
+        let fee_token_account = &mut ctx.accounts.fee_token_account;
+        let clock = Clock::get()?;
+        let base_price = load_unit_price(product, &ctx.accounts.price_feed, ctx.accounts.purchase_ticket_payment_mint.decimals, clock.unix_timestamp)?;
+        let price_tiers = load_price_tiers(&ctx.accounts.price_tiers)?;
+        let list_price = match &price_tiers {
+            Some(price_tiers) => price_tiers.unit_price(base_price, quantity)?, //volume discount
+            None => base_price,
+        };
+        let discount = redeem_coupon(&ctx.accounts.coupon, &ctx.accounts.coupon_usage, &buyer.to_account_info(), &ctx.accounts.system_program,
+            product.key(), product.store, list_price, clock.unix_timestamp)?;
+        let unit_price = list_price - discount;
+        let total_purchase_price = unit_price.checked_mul(quantity).ok_or(ErrorCode::NumericalOverflow)?;
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let fee = fee_schedule.calculate(total_purchase_price)?;
+        let referral = load_referral(&ctx.accounts.referral, product.key(), product.store)?;
+        let referral_commission = match &referral {
+            Some(referral) => apply_basis_points(total_purchase_price, referral.basis_points)?,
+            None => 0,
+        };
+ 
+        validate_purchase(product, quantity, agreed_expiration_timestamp,
+            agreed_expiration_minutes_after_purchase, agreed_expiration_minutes_after_redemption, clock.unix_timestamp)?;
 
-        if redemption.close_slot > 0  {
-            return Err(ErrorCode::AlreadyProcessed.into());
+        //agreed_price is the most the buyer will pay per unit after any coupon, in payment mint units. doubles as slippage protection on feed priced and auctioned products
+        if unit_price > agreed_price {
+            return Err(ErrorCode::PriceIsGreaterThanPayment.into());
         }
 
-        if purchase_ticket.expiration > 0 && purchase_ticket.expiration < clock.unix_timestamp {
-            return Err(ErrorCode::TicketIsExpired.into());
+        if fee > agreed_fee {
+            return Err(ErrorCode::FeeIsGreaterThanAgreedFee.into());
         }
-
-        if redemption.take_expiration > 0 && redemption.take_expiration < clock.unix_timestamp {
-            return Err(ErrorCode::TakeIsExpired.into());
+        
+        if purchase_ticket_payment.amount < total_purchase_price.checked_add(fee).ok_or(ErrorCode::NumericalOverflow)? {
+            return Err(ErrorCode::InsufficientFunds.into());
         }
+   
+        require_keys_eq!(pay_to.key(), product.pay_to.key());
 
-        redemption.ticket_taker = ticket_taker.key();
-        redemption.ticket_taker_signer = ctx.accounts.ticket_taker_signer.key(); 
-        redemption.close_slot = clock.slot;
-        redemption.close_timestamp = clock.unix_timestamp;
-        redemption.status = RedemptionStatus::REDEEMED;
-
-
-        if purchase_ticket.expiration_minutes_after_redemption > 0 {
-            redemption.usage_expiration = clock.unix_timestamp + (i64::from(purchase_ticket.expiration_minutes_after_redemption) * 60);
+        if let Some(referral) = &referral {
+            check_referrer_token_account(&ctx.accounts.referrer_token_account, referral.referrer, ctx.accounts.purchase_ticket_payment_mint.key())?;
         }
 
-        let purchase_ticket_seed_bump = purchase_ticket.bump;
-        let product_snapshot_metadata_key = purchase_ticket.product_snapshot_metadata;
-        let buyer_key = purchase_ticket.buyer;
+        let purchase_ticket_seed_bump = *ctx.bumps.get("purchase_ticket").unwrap();
+        let product_snapshot_metadata_key = product_snapshot_metadata.key();
+        let buyer_key = buyer.key();
         let purchase_ticket_seeds = &[
             PURCHASE_TICKET_BYTES,
             product_snapshot_metadata_key.as_ref(),
             buyer_key.as_ref(),
-            &purchase_ticket.nonce.to_be_bytes(), 
+            &nonce.to_be_bytes(),
             &[purchase_ticket_seed_bump]
         ];
-        let payment_transfer_signer = &[&purchase_ticket_seeds[..]];
-  
-        //payment transfer
-        let payment_transfer_accounts = anchor_spl::token::Transfer {
+        let payment_transfer_signer = &[&purchase_ticket_seeds[..]];       
+
+        //fee transfer        
+        let fee_transfer_accounts = anchor_spl::token::Transfer {
             from: purchase_ticket_payment.to_account_info(),
-            to: pay_to_token_account .to_account_info(),
+            to: fee_token_account.to_account_info(),
             authority: purchase_ticket.to_account_info(), //ata owned by twine program
         };
 
-        let payment_transfer_cpicontext = CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            payment_transfer_accounts,
+        let fee_transfer_cpicontext = CpiContext::new_with_signer(
+    token_program.to_account_info(),
+            fee_transfer_accounts,
             payment_transfer_signer,
         );
 
-        let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, redemption.price * redemption.redeem_quantity)?;
+        let _fee_transfer_result = token::transfer(fee_transfer_cpicontext, fee)?;
 
-        purchase_ticket.redeemed += redemption.redeem_quantity;
-        purchase_ticket.pending_redemption -= redemption.redeem_quantity;
+        if product.redemption_type == RedemptionType::IMMEDIATE {  //release payment if redemption type is immediate
+            let merchant_payment = total_purchase_price - referral_commission;
+
+            if product.payout_split == Pubkey::default() {
+                let payment_transfer_accounts = anchor_spl::token::Transfer {
+                    from: purchase_ticket_payment.to_account_info(),
+                    to: pay_to_token_account .to_account_info(),
+                    authority: purchase_ticket.to_account_info(), //ata owned by twine program
+                };
+
+                let payment_transfer_cpicontext = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    payment_transfer_accounts,
+                    payment_transfer_signer,
+                );
+
+                let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, merchant_payment)?;
+            }
+            else {
+                pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, merchant_payment, false, |recipient_token_account, share| {
+                    let split_transfer_accounts = anchor_spl::token::Transfer {
+                        from: purchase_ticket_payment.to_account_info(),
+                        to: recipient_token_account.clone(),
+                        authority: purchase_ticket.to_account_info(),
+                    };
+
+                    let split_transfer_cpicontext = CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        split_transfer_accounts,
+                        payment_transfer_signer,
+                    );
+
+                    token::transfer(split_transfer_cpicontext, share)
+                })?;
+            }
+
+            if referral_commission > 0 {
+                let commission_transfer_accounts = anchor_spl::token::Transfer {
+                    from: purchase_ticket_payment.to_account_info(),
+                    to: ctx.accounts.referrer_token_account.to_account_info(),
+                    authority: purchase_ticket.to_account_info(),
+                };
+
+                let commission_transfer_cpicontext = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    commission_transfer_accounts,
+                    payment_transfer_signer,
+                );
+
+                token::transfer(commission_transfer_cpicontext, referral_commission)?;
+                purchase_ticket.referral_commission = referral_commission;
+            }
+            
+            purchase_ticket.redeemed = quantity;
+            purchase_ticket.remaining_quantity = 0;
+        }
+        else {
+            purchase_ticket.remaining_quantity = quantity;
+            purchase_ticket.redeemed = 0;
+        }
+
+        snapshot_product(product, product_snapshot_metadata, *ctx.bumps.get("product_snapshot_metadata").unwrap(),
+            product_snapshot, nonce, &clock);
+
+        purchase_ticket.init_purchase(*ctx.bumps.get("purchase_ticket").unwrap(), product, product_snapshot_metadata.key(), product_snapshot_metadata,
+            buyer.key(), ctx.accounts.buy_for.key(), &clock);
+        purchase_ticket.arbiter = load_store_arbiter(&ctx.accounts.store)?;
+        purchase_ticket.price = unit_price;
+        purchase_ticket.payment = purchase_ticket_payment.key();
+        purchase_ticket.fee = fee;
+        purchase_ticket.coupon = ctx.accounts.coupon.key();
+        purchase_ticket.discount = discount;
+        purchase_ticket.set_referral(ctx.accounts.referral.key(), &referral);
+
+        if let Some(price_tiers) = price_tiers {
+            snapshot_price_tiers(price_tiers, &ctx.accounts.price_tiers_snapshot, *ctx.bumps.get("price_tiers_snapshot").unwrap(),
+                ctx.accounts.product_snapshot.key(), &ctx.accounts.buyer.to_account_info(), &ctx.accounts.system_program)?;
+        }
+
+        product.inventory -= quantity;
 
         Ok(())
     }
 
-    pub fn cancel_redemption(ctx: Context<CancelRedemption>) -> Result<()> {        
+    /// buys several of a store's products in one order, charging a single fee on the order total. each item takes
+    /// CHECKOUT_ITEM_ACCOUNTS remaining accounts, in item order: [product, product_snapshot, purchase_ticket,
+    /// purchase_ticket_payment, pay_to_token_account, price_tiers, price_tiers_snapshot, price_feed, payout_split]. the ticket,
+    /// snapshot and any tiers snapshot are created here, seeded off the order and the item's index, and the order stands in for
+    /// the ticket's product snapshot metadata. purchase_ticket_payment is the ticket's token account, created beforehand like
+    /// buy_product's. pay_to_token_account is only checked for immediate redemption products without a payout split.
+    /// price_tiers is the product's PriceTiers PDA, which may not exist. price_feed and payout_split are the product's, or the
+    /// system program. after every item's accounts come the payout split recipients' token accounts, in item order, for the
+    /// immediate redemption items with a payout split. coupons and referrals only apply through buy_product
+    pub fn checkout<'info>(ctx: Context<'_, '_, '_, 'info, Checkout<'info>>, nonce: u16, items: Vec<CheckoutItem>, agreed_fee: u64) -> Result<()> {
         let clock = Clock::get()?;
-        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
-        let redemption = &mut ctx.accounts.redemption;
-        
-        if redemption.status != RedemptionStatus::WAITING || redemption.close_timestamp > 0 {
-            return Err(ErrorCode::AlreadyProcessed.into());
+
+        if items.is_empty() || items.len() > MAX_ORDER_ITEMS || ctx.remaining_accounts.len() < items.len() * CHECKOUT_ITEM_ACCOUNTS {
+            return Err(ErrorCode::InvalidCheckout.into());
         }
 
-        purchase_ticket.remaining_quantity += redemption.redeem_quantity;
-        purchase_ticket.pending_redemption -= redemption.redeem_quantity;
+        let (item_accounts, split_recipient_accounts) = ctx.remaining_accounts.split_at(items.len() * CHECKOUT_ITEM_ACCOUNTS);
+        let store_key = ctx.accounts.store.key();
+        let payment_mint_key = ctx.accounts.payment_mint.key();
+        let order_key = ctx.accounts.order.key();
+        let buyer = &ctx.accounts.buyer;
+        let buyer_key = buyer.key();
+        let token_program = &ctx.accounts.token_program;
+        let mut tickets = Vec::with_capacity(items.len());
+        let mut total: u64 = 0;
+        let mut split_recipient_index = 0;
 
-        redemption.status = RedemptionStatus::CANCELLED;
-        redemption.close_slot = clock.slot;
-        redemption.close_timestamp = clock.unix_timestamp;
+        let pay_from_buyer = |destination: &AccountInfo<'info>, amount: u64| {
+            let payment_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: destination.clone(),
+                authority: buyer.to_account_info(),
+            };
+
+            let payment_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), payment_transfer_accounts);
+            token::transfer(payment_transfer_cpicontext, amount)
+        };
+
+        for (index, item) in items.iter().enumerate() {
+            let accounts = &item_accounts[index * CHECKOUT_ITEM_ACCOUNTS..(index + 1) * CHECKOUT_ITEM_ACCOUNTS];
+            let product_info = &accounts[0];
+            let product_snapshot_info = &accounts[1];
+            let purchase_ticket_info = &accounts[2];
+            let purchase_ticket_payment_info = &accounts[3];
+            let pay_to_token_account_info = &accounts[4];
+            let price_tiers_info = &accounts[5];
+            let price_tiers_snapshot_info = &accounts[6];
+            let price_feed_info = &accounts[7];
+            let payout_split_info = &accounts[8];
+
+            //each product's inventory is written back once, so a product can only be in the order once
+            if item_accounts[..index * CHECKOUT_ITEM_ACCOUNTS].iter().step_by(CHECKOUT_ITEM_ACCOUNTS).any(|info| info.key == product_info.key) {
+                return Err(ErrorCode::InvalidCheckout.into());
+            }
+
+            let mut product = Account::<Product>::try_from(product_info)?;
+            if product.version != PRODUCT_VERSION {
+                return Err(ErrorCode::UnsupportedAccountVersion.into());
+            }
+
+            if product.store != store_key {
+                return Err(ErrorCode::InvalidCheckout.into());
+            }
+
+            if product.payment_mint != payment_mint_key {
+                return Err(ErrorCode::PaymentMintMismatch.into());
+            }
+
+            let (price_tiers_key, _) = Pubkey::find_program_address(&[PRICE_TIERS_BYTES, product_info.key.as_ref()], &crate::ID);
+            if price_tiers_key != price_tiers_info.key() || product.price_feed != price_feed_info.key() || product.payout_split != payout_split_info.key() {
+                return Err(ErrorCode::PublicKeyMismatch.into());
+            }
+
+            validate_purchase(&product, item.quantity, item.agreed_expiration_timestamp,
+                item.agreed_expiration_minutes_after_purchase, item.agreed_expiration_minutes_after_redemption, clock.unix_timestamp)?;
+
+            let base_price = load_unit_price(&product, price_feed_info, ctx.accounts.payment_mint.decimals, clock.unix_timestamp)?;
+            let price_tiers = load_price_tiers(price_tiers_info)?;
+            let unit_price = match &price_tiers {
+                Some(price_tiers) => price_tiers.unit_price(base_price, item.quantity)?, //volume discount
+                None => base_price,
+            };
+
+            if unit_price > item.agreed_price {
+                return Err(ErrorCode::PriceIsGreaterThanPayment.into());
+            }
+
+            let item_total = unit_price.checked_mul(item.quantity).ok_or(ErrorCode::NumericalOverflow)?;
+            total = total.checked_add(item_total).ok_or(ErrorCode::NumericalOverflow)?;
+
+            let item_nonce = index as u16;
+            let (purchase_ticket_key, purchase_ticket_bump) = Pubkey::find_program_address(
+                &[PURCHASE_TICKET_BYTES, order_key.as_ref(), buyer_key.as_ref(), &item_nonce.to_be_bytes()], &crate::ID);
+            let (product_snapshot_key, product_snapshot_bump) = Pubkey::find_program_address(
+                &[PRODUCT_SNAPSHOT_BYTES, order_key.as_ref(), &item_nonce.to_be_bytes()], &crate::ID);
+            let (price_tiers_snapshot_key, price_tiers_snapshot_bump) = Pubkey::find_program_address(
+                &[PRICE_TIERS_BYTES, product_snapshot_key.as_ref()], &crate::ID);
+
+            if purchase_ticket_key != purchase_ticket_info.key() || product_snapshot_key != product_snapshot_info.key()
+                || price_tiers_snapshot_key != price_tiers_snapshot_info.key() {
+                return Err(ErrorCode::PublicKeyMismatch.into());
+            }
+
+            let purchase_ticket_payment = Account::<TokenAccount>::try_from(purchase_ticket_payment_info)?;
+            if purchase_ticket_payment.owner != purchase_ticket_key || purchase_ticket_payment.mint != payment_mint_key {
+                return Err(ErrorCode::InvalidTokenAccount.into());
+            }
+
+            let item_snapshot_metadata = ProductSnapshotMetadata {
+                bump: 0,
+                version: PRODUCT_SNAPSHOT_METADATA_VERSION,
+                slot: clock.slot,
+                timestamp: clock.unix_timestamp,
+                product: product.key(),
+                product_snapshot: product_snapshot_key,
+                nonce: item_nonce,
+            };
+
+            let mut purchase_ticket = PurchaseTicket::default();
+            purchase_ticket.init_purchase(purchase_ticket_bump, &product, order_key, &item_snapshot_metadata,
+                buyer_key, ctx.accounts.buy_for.key(), &clock);
+            purchase_ticket.arbiter = ctx.accounts.store.arbiter;
+            purchase_ticket.price = unit_price;
+            purchase_ticket.payment = purchase_ticket_payment_info.key();
+            purchase_ticket.fee = 0; //paid once on the order
+
+            //payment transfer. released to the merchant for immediate redemption, escrowed in the ticket otherwise
+            if product.redemption_type == RedemptionType::IMMEDIATE {
+                purchase_ticket.redeemed = item.quantity;
+                purchase_ticket.remaining_quantity = 0;
+
+                if product.payout_split == Pubkey::default() {
+                    let pay_to_token_account = Account::<TokenAccount>::try_from(pay_to_token_account_info)?;
+                    if pay_to_token_account.owner != product.pay_to || pay_to_token_account.mint != payment_mint_key {
+                        return Err(ErrorCode::InvalidTokenAccount.into());
+                    }
+
+                    pay_from_buyer(pay_to_token_account_info, item_total)?;
+                }
+                else {
+                    let recipient_count = load_payout_split(payout_split_info)?.recipients.len();
+                    let recipient_infos = split_recipient_accounts.get(split_recipient_index..split_recipient_index + recipient_count)
+                        .ok_or(ErrorCode::PayoutRecipientMismatch)?;
+                    split_recipient_index += recipient_count;
+
+                    pay_out_split(payout_split_info, recipient_infos, item_total, false, &pay_from_buyer)?;
+                }
+            }
+            else {
+                purchase_ticket.redeemed = 0;
+                purchase_ticket.remaining_quantity = item.quantity;
+                pay_from_buyer(purchase_ticket_payment_info, item_total)?;
+            }
+
+            create_pda_account(purchase_ticket_info, &buyer.to_account_info(), &ctx.accounts.system_program, 8 + PURCHASE_TICKET_SIZE,
+                &[PURCHASE_TICKET_BYTES, order_key.as_ref(), buyer_key.as_ref(), &item_nonce.to_be_bytes(), &[purchase_ticket_bump]])?;
+            purchase_ticket.try_serialize(&mut &mut purchase_ticket_info.data.borrow_mut()[..])?;
+
+            let mut product_snapshot = product.clone().into_inner();
+            product_snapshot.is_snapshot = true;
+            create_pda_account(product_snapshot_info, &buyer.to_account_info(), &ctx.accounts.system_program, 8 + PRODUCT_SIZE + product.data.len(),
+                &[PRODUCT_SNAPSHOT_BYTES, order_key.as_ref(), &item_nonce.to_be_bytes(), &[product_snapshot_bump]])?;
+            product_snapshot.try_serialize(&mut &mut product_snapshot_info.data.borrow_mut()[..])?;
+
+            if let Some(price_tiers) = price_tiers {
+                snapshot_price_tiers(price_tiers, price_tiers_snapshot_info, price_tiers_snapshot_bump, product_snapshot_key,
+                    &buyer.to_account_info(), &ctx.accounts.system_program)?;
+            }
+
+            product.inventory -= item.quantity;
+            product.exit(&crate::ID)?;
+
+            tickets.push(purchase_ticket_key);
+        }
+
+        //every recipient account passed has to belong to an item's split
+        if split_recipient_index != split_recipient_accounts.len() {
+            return Err(ErrorCode::PayoutRecipientMismatch.into());
+        }
+
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let fee = fee_schedule.calculate(total)?;
+
+        if fee > agreed_fee {
+            return Err(ErrorCode::FeeIsGreaterThanAgreedFee.into());
+        }
+
+        //fee transfer
+        let fee_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.fee_token_account.to_account_info(),
+            authority: buyer.to_account_info(),
+        };
+
+        let fee_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), fee_transfer_accounts);
+        token::transfer(fee_transfer_cpicontext, fee)?;
+
+        let order = &mut ctx.accounts.order;
+        order.bump = *ctx.bumps.get("order").unwrap();
+        order.version = ORDER_VERSION;
+        order.buyer = buyer_key;
+        order.store = store_key;
+        order.payment_mint = payment_mint_key;
+        order.nonce = nonce;
+        order.total = total;
+        order.fee = fee;
+        order.tickets = tickets;
+        order.slot = clock.slot;
+        order.timestamp = clock.unix_timestamp;
 
         Ok(())
     }
 
-    pub fn transfer_ticket(ctx: Context<TransferTicket>, nonce: u16, quantity: u64) -> Result<()> {
-        if quantity <= 0 {
-            return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
+    /// the ticket's escrow, less the fee, is the deposit. the balance is paid in with pay_installment before the payment deadline.
+    /// layaway tickets can't be redeemed, transferred, listed or cancelled until they're paid in full
+    pub fn buy_product_layaway(ctx: Context<BuyProductLayaway>, nonce: u16, quantity: u64,
+        agreed_price: u64, agreed_fee: u64, agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32) -> Result<()>{
+
+        let product = &mut ctx.accounts.product;
+        let product_snapshot_metadata = &mut ctx.accounts.product_snapshot_metadata;
+        let product_snapshot = &mut ctx.accounts.product_snapshot;
+        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
+        let purchase_ticket_payment = &ctx.accounts.purchase_ticket_payment;
+        let layaway_policy = product.layaway_policy;
+        let clock = Clock::get()?;
+        let unit_price = load_unit_price(product, &ctx.accounts.price_feed, ctx.accounts.purchase_ticket_payment_mint.decimals, clock.unix_timestamp)?;
+        let total_purchase_price = unit_price.checked_mul(quantity).ok_or(ErrorCode::NumericalOverflow)?;
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let fee = fee_schedule.calculate(total_purchase_price)?;
+
+        validate_purchase(product, quantity, agreed_expiration_timestamp,
+            agreed_expiration_minutes_after_purchase, agreed_expiration_minutes_after_redemption, clock.unix_timestamp)?;
+
+        //paying in full later only makes sense when redemption is delayed
+        if layaway_policy.payment_window_minutes == 0 || product.redemption_type == RedemptionType::IMMEDIATE {
+            return Err(ErrorCode::LayawayNotOffered.into());
         }
 
-        if quantity > ctx.accounts.source_ticket.remaining_quantity {
-            return Err(ErrorCode::InsufficientQuantity.into());
+        if unit_price > agreed_price {
+            return Err(ErrorCode::PriceIsGreaterThanPayment.into());
         }
 
-        let clock = Clock::get()?;
-        let source_ticket = &ctx.accounts.source_ticket;
+        if fee > agreed_fee {
+            return Err(ErrorCode::FeeIsGreaterThanAgreedFee.into());
+        }
 
-        if source_ticket.expiration > 0 && source_ticket.expiration < clock.unix_timestamp {
-            return Err(ErrorCode::TicketIsExpired.into());
+        let deposit = purchase_ticket_payment.amount.checked_sub(fee).ok_or(ErrorCode::InsufficientFunds)?;
+        if deposit < apply_basis_points(total_purchase_price, layaway_policy.min_deposit_basis_points)? {
+            return Err(ErrorCode::InsufficientDeposit.into());
         }
-        
-        let source_ticket_seed_bump = source_ticket.bump;
-        let product_snapshot_metadata_key = source_ticket.product_snapshot_metadata;
-        let buyer_key = source_ticket.buyer;
-        let source_ticket_seeds = &[
+
+        //anything over the price would be stuck in escrow, since the ticket only ever pays out price * quantity
+        if deposit > total_purchase_price {
+            return Err(ErrorCode::DepositIsMoreThanPrice.into());
+        }
+
+        let purchase_ticket_seed_bump = *ctx.bumps.get("purchase_ticket").unwrap();
+        let product_snapshot_metadata_key = product_snapshot_metadata.key();
+        let buyer_key = ctx.accounts.buyer.key();
+        let purchase_ticket_seeds = &[
             PURCHASE_TICKET_BYTES,
             product_snapshot_metadata_key.as_ref(),
             buyer_key.as_ref(),
-            &source_ticket.nonce.to_be_bytes(),
-            &[source_ticket_seed_bump]
+            &nonce.to_be_bytes(),
+            &[purchase_ticket_seed_bump]
         ];
-        let payment_transfer_signer = &[&source_ticket_seeds[..]];
-  
-        let source_ticket_payment = &ctx.accounts.source_ticket_payment;
-        let destination_ticket_payment = &ctx.accounts.destination_ticket_payment;
+        let payment_transfer_signer = &[&purchase_ticket_seeds[..]];
 
-        //payment transfer
-        let payment_transfer_accounts = anchor_spl::token::Transfer {
-            from: source_ticket_payment.to_account_info(),
-            to: destination_ticket_payment .to_account_info(),
-            authority: source_ticket.to_account_info(),
+        //fee transfer
+        let fee_transfer_accounts = anchor_spl::token::Transfer {
+            from: purchase_ticket_payment.to_account_info(),
+            to: ctx.accounts.fee_token_account.to_account_info(),
+            authority: purchase_ticket.to_account_info(),
         };
 
-        let payment_transfer_cpicontext = CpiContext::new_with_signer(
+        let fee_transfer_cpicontext = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            payment_transfer_accounts,
+            fee_transfer_accounts,
             payment_transfer_signer,
         );
 
-        let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, source_ticket.price * quantity)?;
+        token::transfer(fee_transfer_cpicontext, fee)?;
 
-        ctx.accounts.destination_ticket.set_inner(ctx.accounts.source_ticket.clone().into_inner());
-        let clock = Clock::get()?;
-        let destination_ticket = &mut ctx.accounts.destination_ticket;
-        destination_ticket.bump = *ctx.bumps.get("destination_ticket").unwrap();
-        destination_ticket.nonce = nonce;        
-        destination_ticket.slot = clock.slot;
-        destination_ticket.timestamp = clock.unix_timestamp;
-        destination_ticket.buyer = ctx.accounts.source_ticket_authority.key();
-        destination_ticket.authority = ctx.accounts.destination_ticket_authority.key();
-        destination_ticket.remaining_quantity = quantity;
-        destination_ticket.redeemed = 0;
-        destination_ticket.pending_redemption = 0;
-        destination_ticket.payment = ctx.accounts.destination_ticket_payment.key();
+        snapshot_product(product, product_snapshot_metadata, *ctx.bumps.get("product_snapshot_metadata").unwrap(),
+            product_snapshot, nonce, &clock);
+
+        purchase_ticket.init_purchase(purchase_ticket_seed_bump, product, product_snapshot_metadata.key(), product_snapshot_metadata,
+            buyer_key, ctx.accounts.buy_for.key(), &clock);
+        purchase_ticket.arbiter = load_store_arbiter(&ctx.accounts.store)?;
+        purchase_ticket.remaining_quantity = quantity;
+        purchase_ticket.redeemed = 0;
+        purchase_ticket.price = unit_price;
+        purchase_ticket.payment = purchase_ticket_payment.key();
+        purchase_ticket.fee = fee;
+        purchase_ticket.amount_due = total_purchase_price - deposit;
+        purchase_ticket.payment_deadline = clock.unix_timestamp + i64::from(layaway_policy.payment_window_minutes) * 60;
+        purchase_ticket.forfeit_basis_points = layaway_policy.forfeit_basis_points;
 
-        ctx.accounts.source_ticket.remaining_quantity -= quantity;
+        product.inventory -= quantity; //held for the buyer until they pay up or forfeit
 
         Ok(())
     }
 
-    pub fn cancel_ticket(ctx: Context<CancelTicket>, quantity: u64) -> Result<()> {
+    /// anyone can pay toward a layaway ticket, up to the amount due
+    pub fn pay_installment(ctx: Context<PayInstallment>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
         let ticket = &mut ctx.accounts.ticket;
-        
-        if quantity > ticket.remaining_quantity {
-            return Err(ErrorCode::InsufficientQuantity.into());
+
+        if amount <= 0 {
+            return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
+        }
+
+        if ticket.amount_due == 0 {
+            return Err(ErrorCode::TicketIsPaidInFull.into());
+        }
+
+        if amount > ticket.amount_due {
+            return Err(ErrorCode::InstallmentIsMoreThanAmountDue.into());
+        }
+
+        if clock.unix_timestamp > ticket.payment_deadline {
+            return Err(ErrorCode::PaymentDeadlinePassed.into());
+        }
+
+        let installment_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.payer_token_account.to_account_info(),
+            to: ctx.accounts.ticket_payment.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        };
+
+        let installment_transfer_cpicontext = CpiContext::new(ctx.accounts.token_program.to_account_info(), installment_transfer_accounts);
+        token::transfer(installment_transfer_cpicontext, amount)?;
+
+        ticket.amount_due -= amount;
+
+        Ok(())
+    }
+
+    /// permissionless once the payment deadline passes on an unpaid layaway ticket. the merchant keeps forfeit_basis_points
+    /// of what was paid in, the rest is refunded to the ticket authority, and the held inventory goes back on the product.
+    /// remaining accounts are the payout split recipients' token accounts, in order, when the ticket has a payout split
+    pub fn forfeit_layaway<'info>(ctx: Context<'_, '_, '_, 'info, ForfeitLayaway<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket = &ctx.accounts.ticket;
+
+        if ticket.amount_due == 0 {
+            return Err(ErrorCode::TicketIsPaidInFull.into());
         }
 
+        if clock.unix_timestamp <= ticket.payment_deadline {
+            return Err(ErrorCode::PaymentDeadlineNotPassed.into());
+        }
+
+        let paid = ctx.accounts.ticket_payment.amount;
+        let forfeited = apply_basis_points(paid, ticket.forfeit_basis_points)?;
+
         let ticket_seed_bump = ticket.bump;
         let product_snapshot_metadata_key = ticket.product_snapshot_metadata;
         let buyer_key = ticket.buyer;
@@ -642,909 +1481,6518 @@ pub mod twine {
             &[ticket_seed_bump]
         ];
         let payment_transfer_signer = &[&ticket_seeds[..]];
-  
-        let ticket_payment = &ctx.accounts.ticket_payment;
 
-        //payment transfer
-        let payment_transfer_accounts = anchor_spl::token::Transfer {
-            from: ticket_payment.to_account_info(),
-            to: ctx.accounts.payment_return.to_account_info(),
+        //forfeit transfer
+        if ticket.payout_split == Pubkey::default() {
+            let forfeit_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.ticket_payment.to_account_info(),
+                to: ctx.accounts.pay_to_token_account.to_account_info(),
+                authority: ticket.to_account_info(),
+            };
+
+            let forfeit_transfer_cpicontext = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                forfeit_transfer_accounts,
+                payment_transfer_signer,
+            );
+
+            token::transfer(forfeit_transfer_cpicontext, forfeited)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, forfeited, false, |recipient_token_account, share| {
+                let split_transfer_accounts = anchor_spl::token::Transfer {
+                    from: ctx.accounts.ticket_payment.to_account_info(),
+                    to: recipient_token_account.clone(),
+                    authority: ticket.to_account_info(),
+                };
+
+                let split_transfer_cpicontext = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    split_transfer_accounts,
+                    payment_transfer_signer,
+                );
+
+                token::transfer(split_transfer_cpicontext, share)
+            })?;
+        }
+
+        //refund transfer
+        let refund_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.ticket_payment.to_account_info(),
+            to: ctx.accounts.refund_token_account.to_account_info(),
             authority: ticket.to_account_info(),
         };
 
-        let payment_transfer_cpicontext = CpiContext::new_with_signer(
+        let refund_transfer_cpicontext = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            payment_transfer_accounts,
+            refund_transfer_accounts,
             payment_transfer_signer,
         );
 
-        let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, ticket.price * quantity)?;
+        token::transfer(refund_transfer_cpicontext, paid - forfeited)?;
 
-        ticket.remaining_quantity -= quantity;
-        ctx.accounts.product.inventory += quantity;
+        let ticket = &mut ctx.accounts.ticket;
+        ctx.accounts.product.inventory += ticket.remaining_quantity;
+        ticket.remaining_quantity = 0;
+        ticket.amount_due = 0;
 
         Ok(())
     }
 
+    /// pays for the first period and approves the subscription as a delegate on the subscriber's token account for allowance,
+    /// which renew_subscription draws the following periods and their fees from. price and the fee bound are locked in for the
+    /// life of the subscription. a cancelled or lapsed subscription can be subscribed to again, picking up after any time left on it.
+    /// remaining accounts are the payout split recipients' token accounts, in order, when the product has a payout split
+    pub fn subscribe<'info>(ctx: Context<'_, '_, '_, 'info, Subscribe<'info>>, agreed_price: u64, agreed_fee: u64, allowance: u64) -> Result<()> {
+        let product = &ctx.accounts.product;
+        let clock = Clock::get()?;
 
-}
+        if ctx.accounts.subscription.status == SubscriptionStatus::ACTIVE && clock.unix_timestamp < ctx.accounts.subscription.expiration {
+            return Err(ErrorCode::SubscriptionIsActive.into());
+        }
 
+        let price = load_unit_price(product, &ctx.accounts.price_feed, ctx.accounts.payment_mint.decimals, clock.unix_timestamp)?;
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let fee = fee_schedule.calculate(price)?;
 
+        if product.subscription_period_minutes == 0 {
+            return Err(ErrorCode::NotASubscriptionProduct.into());
+        }
 
+        if product.status != ProductStatus::ACTIVE {
+            return Err(ErrorCode::ProductIsNotActive.into());
+        }
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init,
-        payer = creator,
-        space = 8 + PROGRAM_METADATA_SIZE,
-        seeds = [PROGRAM_METADATA_BYTES],
-        bump)]
-    pub program_metadata: Account<'info, ProgramMetadata>,
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToPurchaseSnapshot.into());
+        }
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        if product.expiration_timestamp > 0 && product.expiration_timestamp < clock.unix_timestamp {
+            return Err(ErrorCode::ProductIsExpired.into());
+        }
 
-    /// CHECK:
-    pub authority: AccountInfo<'info>,
-    /// CHECK:
-    pub secondary_authority: AccountInfo<'info>,
-    /// CHECK:
-    #[account(owner=anchor_lang::system_program::ID)]
-    pub fee_account: AccountInfo<'info>,
-    pub system_program: Program<'info, System>,
-}
+        if price > agreed_price {
+            return Err(ErrorCode::PriceIsGreaterThanPayment.into());
+        }
 
-#[derive(Accounts)]
-pub struct UpdateProgramMetadata<'info> {
-    #[account(
-        mut,
-        constraint= program_metadata.is_authorized(&authority.key),
-        seeds = [PROGRAM_METADATA_BYTES],
-        bump= program_metadata.bump)]
-    pub program_metadata: Account<'info, ProgramMetadata>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
+        if fee > agreed_fee {
+            return Err(ErrorCode::FeeIsGreaterThanAgreedFee.into());
+        }
 
+        let token_program = &ctx.accounts.token_program;
+        let subscriber = &ctx.accounts.subscriber;
+        let subscriber_token_account = &ctx.accounts.subscriber_token_account;
 
-#[derive(Accounts)]
-pub struct ChangeFeeAccount<'info> {
+        //payment transfer
+        if product.payout_split == Pubkey::default() {
+            let payment_transfer_accounts = anchor_spl::token::Transfer {
+                from: subscriber_token_account.to_account_info(),
+                to: ctx.accounts.pay_to_token_account.to_account_info(),
+                authority: subscriber.to_account_info(),
+            };
 
-    #[account(
-        mut,
-        //has_one=authority,
-        seeds = [PROGRAM_METADATA_BYTES],
-        bump=program_metadata.bump
-    )]
-    pub program_metadata: Account<'info, ProgramMetadata>,
+            let payment_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), payment_transfer_accounts);
+            token::transfer(payment_transfer_cpicontext, price)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, price, false, |recipient_token_account, share| {
+                let split_transfer_accounts = anchor_spl::token::Transfer {
+                    from: subscriber_token_account.to_account_info(),
+                    to: recipient_token_account.clone(),
+                    authority: subscriber.to_account_info(),
+                };
+
+                let split_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), split_transfer_accounts);
+                token::transfer(split_transfer_cpicontext, share)
+            })?;
+        }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        //fee transfer
+        let fee_transfer_accounts = anchor_spl::token::Transfer {
+            from: subscriber_token_account.to_account_info(),
+            to: ctx.accounts.fee_token_account.to_account_info(),
+            authority: subscriber.to_account_info(),
+        };
 
-    /// CHECK:
-    #[account(owner = anchor_lang::system_program::ID)]
-    pub fee_account: AccountInfo<'info>,
-}
+        let fee_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), fee_transfer_accounts);
+        token::transfer(fee_transfer_cpicontext, fee)?;
 
+        let approve_accounts = anchor_spl::token::Approve {
+            to: subscriber_token_account.to_account_info(),
+            delegate: ctx.accounts.subscription.to_account_info(),
+            authority: subscriber.to_account_info(),
+        };
 
-#[derive(Accounts)]
-#[instruction(id: u16, status: u8, name: String, description: String, data: Vec<u8>)]
-pub struct CreateStore<'info> {
-    #[account(init,
-        payer=creator,
-        space=8 + STORE_SIZE + data.len(),
-        seeds=[STORE_SEED_BYTES, creator.key().as_ref(), &id.to_be_bytes()],
-        bump)]
-    pub store: Box<Account<'info, Store>>,
+        let approve_cpicontext = CpiContext::new(token_program.to_account_info(), approve_accounts);
+        token::approve(approve_cpicontext, allowance)?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.bump = *ctx.bumps.get("subscription").unwrap();
+        subscription.version = SUBSCRIPTION_VERSION;
+        subscription.product = product.key();
+        subscription.subscriber = subscriber.key();
+        subscription.payment_token_account = subscriber_token_account.key();
+        subscription.payment_mint = product.payment_mint;
+        subscription.pay_to = product.pay_to;
+        subscription.payout_split = product.payout_split;
+        subscription.price = price;
+        subscription.agreed_fee = agreed_fee;
+        subscription.period_minutes = product.subscription_period_minutes;
+        subscription.expiration = subscription.expiration.max(clock.unix_timestamp) + i64::from(product.subscription_period_minutes) * 60;
+        subscription.status = SubscriptionStatus::ACTIVE;
+        subscription.renewals = 0;
+        subscription.slot = clock.slot;
+        subscription.timestamp = clock.unix_timestamp;
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        Ok(())
+    }
 
-    /// CHECK: doesn't much need validation
-    #[account()]
-    pub authority: AccountInfo<'info>,
-     
-    /// CHECK: doesn't much need validation
-     #[account()]
-    pub secondary_authority: AccountInfo<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// permissionless crank. once the current period has elapsed, pulls the next period's price plus the fee from the
+    /// subscriber's token account. a subscription that lapsed for more than a full period restarts from now.
+    /// remaining accounts are the payout split recipients' token accounts, in order, when the subscription has a payout split
+    pub fn renew_subscription<'info>(ctx: Context<'_, '_, '_, 'info, RenewSubscription<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        let subscription = &ctx.accounts.subscription;
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let fee = fee_schedule.calculate(subscription.price)?;
 
+        if subscription.status != SubscriptionStatus::ACTIVE {
+            return Err(ErrorCode::SubscriptionIsCancelled.into());
+        }
 
-#[derive(Accounts)]
-#[instruction(status: u8, name: String, description: String, data: Vec<u8>)]
-pub struct UpdateStore<'info> {
-    #[account(mut,
-        constraint = store.is_authorized(&authority.key),
-        realloc = 8 + STORE_SIZE + data.len(),
-        realloc::payer = authority,
-        realloc::zero = true,
-        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
-        bump = store.bump)]   
-    pub store: Box<Account<'info, Store>>,
+        if ctx.accounts.product.status != ProductStatus::ACTIVE {
+            return Err(ErrorCode::ProductIsNotActive.into());
+        }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        //the subscriber only agreed to the fee at subscribe, a fee raised since then needs them to subscribe again
+        if fee > subscription.agreed_fee {
+            return Err(ErrorCode::FeeIsGreaterThanAgreedFee.into());
+        }
 
-//consider using metaplex for this.
-#[derive(Accounts)]
-#[instruction(id: u32, status: u8, //_mint_decimals: u8,
-    price: u64, inventory: u64, redemption_type: u8,
-    expiration_timestamp: i64, expiration_minutes_after_purchase: u32, expiration_minutes_after_redemption: u32,
-    name: String, description: String, data: Vec<u8>)]
-pub struct CreateStoreProduct<'info> {
-/*
-    #[account(
-        init,
-        payer=creator,
-        seeds=[PRODUCT_MINT_BYTES, product.key().as_ref()],
-        bump,
-        mint::decimals=_mint_decimals,
-        mint::authority=mint, //dont give the owner this, or they could mint tokens without going through this program for validation
-        mint::freeze_authority=mint,
-    )]
-    pub mint: Account<'info, Mint>, //mint account for this product. The owner mints tokens to the product_mint account for this program to use
-  */
-    #[account(init,
-        payer=creator,
-        space=8 + PRODUCT_SIZE + data.len(), 
-        seeds=[PRODUCT_SEED_BYTES, creator.key().as_ref(), &id.to_be_bytes()], 
-        bump)]
-    pub product: Box<Account<'info, Product>>,
+        if clock.unix_timestamp < subscription.expiration {
+            return Err(ErrorCode::SubscriptionPeriodHasntElapsed.into());
+        }
 
-    #[account(mut,
-        constraint = store.is_authorized(&creator.key),
-        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
-        bump=store.bump)]
-    pub store: Box<Account<'info, Store>>,
-  
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        let product_key = subscription.product;
+        let subscriber_key = subscription.subscriber;
+        let subscription_seeds = &[
+            SUBSCRIPTION_BYTES,
+            product_key.as_ref(),
+            subscriber_key.as_ref(),
+            &[subscription.bump]
+        ];
+        let delegate_signer = &[&subscription_seeds[..]];
+        let token_program = &ctx.accounts.token_program;
 
-    /// CHECK: doesn't much need validation
-    #[account()]
-    pub authority: AccountInfo<'info>,
+        //payment transfer
+        if subscription.payout_split == Pubkey::default() {
+            let payment_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.subscriber_token_account.to_account_info(),
+                to: ctx.accounts.pay_to_token_account.to_account_info(),
+                authority: subscription.to_account_info(), //delegate approved in subscribe
+            };
 
-    /// CHECK: doesn't much need validation
-    #[account()]
-    pub secondary_authority: AccountInfo<'info>,
+            let payment_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                payment_transfer_accounts,
+                delegate_signer,
+            );
 
-    /// CHECK: doesn't much need validation
-    #[account(owner=anchor_lang::system_program::ID)]
-    pub pay_to: AccountInfo<'info>,
+            token::transfer(payment_transfer_cpicontext, subscription.price)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, subscription.price, false, |recipient_token_account, share| {
+                let split_transfer_accounts = anchor_spl::token::Transfer {
+                    from: ctx.accounts.subscriber_token_account.to_account_info(),
+                    to: recipient_token_account.clone(),
+                    authority: subscription.to_account_info(),
+                };
+
+                let split_transfer_cpicontext = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    split_transfer_accounts,
+                    delegate_signer,
+                );
+
+                token::transfer(split_transfer_cpicontext, share)
+            })?;
+        }
 
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+        //fee transfer
+        let fee_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.subscriber_token_account.to_account_info(),
+            to: ctx.accounts.fee_token_account.to_account_info(),
+            authority: subscription.to_account_info(),
+        };
 
-#[derive(Accounts)]
-#[instruction(id: u32, status: u8,
-    price: u64, inventory: u64, redemption_type: u8,
-    expiration_timestamp: i64, expiration_minutes_after_purchase: u32, expiration_minutes_after_redemption: u32,
-    name: String, description: String, data: Vec<u8>)]
-pub struct CreateProduct<'info> {
-/*
-    #[account(
-        init,
-        payer=creator,
-        seeds=[PRODUCT_MINT_BYTES, product.key().as_ref()],
-        bump,
-        mint::decimals=_mint_decimals,
-        mint::authority=mint, //dont give the owner this, or they could mint tokens without going through this program for validation
-        mint::freeze_authority=mint,
-    )]
-    pub mint: Account<'info, Mint>, //mint account for this product. The owner mints tokens to the product_mint account for this program to use
- */ 
-    #[account(init,
-        payer=creator,
-        space=8 + PRODUCT_SIZE + data.len(), 
-        seeds=[PRODUCT_SEED_BYTES, creator.key().as_ref(), &id.to_be_bytes()], 
-        bump)]
-    pub product: Box<Account<'info, Product>>,
-  
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        let fee_transfer_cpicontext = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            fee_transfer_accounts,
+            delegate_signer,
+        );
 
-    /// CHECK: doesn't much need validation
-    #[account()]
-    pub authority: AccountInfo<'info>,
+        token::transfer(fee_transfer_cpicontext, fee)?;
 
-    /// CHECK: doesn't much need validation
-    #[account()]
-    pub secondary_authority: AccountInfo<'info>,
+        let subscription = &mut ctx.accounts.subscription;
+        let period_seconds = i64::from(subscription.period_minutes) * 60;
 
-    /// CHECK: doesn't much need validation
-    #[account(owner=anchor_lang::system_program::ID)]
-    pub pay_to: AccountInfo<'info>,
+        if clock.unix_timestamp > subscription.expiration + period_seconds {
+            subscription.expiration = clock.unix_timestamp + period_seconds;
+        }
+        else {
+            subscription.expiration += period_seconds;
+        }
 
+        subscription.renewals += 1;
+        subscription.slot = clock.slot;
+        subscription.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// the subscriber or the product authority can cancel. the subscription stays good until its expiration but won't renew.
+    /// when the subscriber cancels, the subscription's delegation on their token account is revoked too
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        let clock = Clock::get()?;
+        let subscriber_token_account = &ctx.accounts.subscriber_token_account;
+
+        if ctx.accounts.subscription.status != SubscriptionStatus::ACTIVE {
+            return Err(ErrorCode::SubscriptionIsCancelled.into());
+        }
+
+        //only the owner can revoke, and the subscriber may have delegated to someone else since
+        if ctx.accounts.authority.key() == ctx.accounts.subscription.subscriber
+            && subscriber_token_account.delegate == COption::Some(ctx.accounts.subscription.key()) {
+
+            let revoke_accounts = anchor_spl::token::Revoke {
+                source: subscriber_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+
+            let revoke_cpicontext = CpiContext::new(ctx.accounts.token_program.to_account_info(), revoke_accounts);
+            token::revoke(revoke_cpicontext)?;
+        }
+
+        let subscription = &mut ctx.accounts.subscription;
+
+        subscription.status = SubscriptionStatus::CANCELLED;
+        subscription.slot = clock.slot;
+        subscription.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// puts one unit of a product up for auction. the unit is held out of inventory until the auction is settled or cancelled.
+    /// bids are escrowed in the auction's token account, and the winner gets a normal ticket at settlement. the product and
+    /// fee schedule are snapshotted here, so bidders know the terms and fee they're bidding on won't change under their bids
+    pub fn create_auction(ctx: Context<CreateAuction>, nonce: u16, min_bid: u64, min_increment: u64, start_timestamp: i64, end_timestamp: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let product = &mut ctx.accounts.product;
+
+        //the winning bid sits in the ticket's escrow until redemption, same as any delayed redemption purchase
+        if min_bid == 0 || end_timestamp <= start_timestamp || end_timestamp <= clock.unix_timestamp
+            || product.redemption_type == RedemptionType::IMMEDIATE {
+            return Err(ErrorCode::InvalidAuction.into());
+        }
+
+        if product.status != ProductStatus::ACTIVE {
+            return Err(ErrorCode::ProductIsNotActive.into());
+        }
+
+        if product.is_snapshot {
+            return Err(ErrorCode::UnableToPurchaseSnapshot.into());
+        }
+
+        if product.subscription_period_minutes > 0 {
+            return Err(ErrorCode::SubscriptionProductMustBeSubscribedTo.into());
+        }
+
+        if product.inventory < 1 {
+            return Err(ErrorCode::NotEnoughInventory.into());
+        }
+
+        product.inventory -= 1;
+
+        snapshot_product(product, &mut ctx.accounts.product_snapshot_metadata, *ctx.bumps.get("product_snapshot_metadata").unwrap(),
+            &mut ctx.accounts.product_snapshot, nonce, &clock);
+
+        let auction = &mut ctx.accounts.auction;
+        auction.bump = *ctx.bumps.get("auction").unwrap();
+        auction.version = AUCTION_VERSION;
+        auction.product = product.key();
+        auction.authority = ctx.accounts.authority.key();
+        auction.payment_mint = product.payment_mint;
+        auction.escrow = ctx.accounts.escrow.key();
+        auction.product_snapshot_metadata = ctx.accounts.product_snapshot_metadata.key();
+        auction.product_snapshot = ctx.accounts.product_snapshot.key();
+        auction.nonce = nonce;
+        auction.fee_schedule = fee_schedule;
+        auction.min_bid = min_bid;
+        auction.min_increment = min_increment;
+        auction.start_timestamp = start_timestamp;
+        auction.end_timestamp = end_timestamp;
+        auction.highest_bid = 0;
+        auction.highest_bidder = Pubkey::default();
+        auction.bids = 0;
+        auction.slot = clock.slot;
+        auction.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// escrows the bid and refunds the bid it beats. previous_bidder_token_account can be any token account of the previous
+    /// bidder's in the payment mint, so closing theirs can't stall the auction. it's ignored on the first bid
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction = &ctx.accounts.auction;
+
+        if clock.unix_timestamp < auction.start_timestamp {
+            return Err(ErrorCode::AuctionHasntStarted.into());
+        }
+
+        if clock.unix_timestamp >= auction.end_timestamp {
+            return Err(ErrorCode::AuctionHasEnded.into());
+        }
+
+        let min_bid = if auction.highest_bidder == Pubkey::default() {
+            auction.min_bid
+        }
+        else {
+            auction.highest_bid.checked_add(auction.min_increment.max(1)).ok_or(ErrorCode::NumericalOverflow)?
+        };
+
+        if amount < min_bid {
+            return Err(ErrorCode::BidTooLow.into());
+        }
+
+        let token_program = &ctx.accounts.token_program;
+
+        //bid transfer
+        let bid_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.bidder_token_account.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+            authority: ctx.accounts.bidder.to_account_info(),
+        };
+
+        let bid_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), bid_transfer_accounts);
+        token::transfer(bid_transfer_cpicontext, amount)?;
+
+        //refund the outbid bidder
+        if auction.highest_bidder != Pubkey::default() {
+            let previous_bidder_token_account = Account::<TokenAccount>::try_from(&ctx.accounts.previous_bidder_token_account)?;
+            if previous_bidder_token_account.owner != auction.highest_bidder || previous_bidder_token_account.mint != auction.payment_mint {
+                return Err(ErrorCode::InvalidTokenAccount.into());
+            }
+
+            let product_key = auction.product;
+            let auction_seeds = &[
+                AUCTION_BYTES,
+                product_key.as_ref(),
+                &[auction.bump]
+            ];
+            let escrow_signer = &[&auction_seeds[..]];
+
+            let refund_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.previous_bidder_token_account.to_account_info(),
+                authority: auction.to_account_info(),
+            };
+
+            let refund_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                refund_transfer_accounts,
+                escrow_signer,
+            );
+
+            token::transfer(refund_transfer_cpicontext, auction.highest_bid)?;
+        }
+
+        let auction = &mut ctx.accounts.auction;
+        auction.highest_bid = amount;
+        auction.highest_bidder = ctx.accounts.bidder.key();
+        auction.bids += 1;
+        auction.slot = clock.slot;
+        auction.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// permissionless once the auction ends. the fee comes out of the winning bid and the rest moves into a new ticket's
+    /// escrow, owned by the winner and redeemed like any other. the ticket takes its terms from the snapshot made at
+    /// create_auction, and the fee from the schedule recorded then. tokens sent to the escrow outside of bidding go to the
+    /// product's pay_to, and the escrow and auction are closed to the auction's creator
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction = &ctx.accounts.auction;
+
+        if clock.unix_timestamp < auction.end_timestamp {
+            return Err(ErrorCode::AuctionHasntEnded.into());
+        }
+
+        let fee = auction.fee_schedule.calculate(auction.highest_bid)?;
+        let price = auction.highest_bid.checked_sub(fee).ok_or(ErrorCode::InsufficientFunds)?;
+        let surplus = ctx.accounts.escrow.amount.checked_sub(auction.highest_bid).ok_or(ErrorCode::InsufficientFunds)?;
+
+        let product_key = auction.product;
+        let auction_seeds = &[
+            AUCTION_BYTES,
+            product_key.as_ref(),
+            &[auction.bump]
+        ];
+        let escrow_signer = &[&auction_seeds[..]];
+        let token_program = &ctx.accounts.token_program;
+
+        //fee transfer
+        let fee_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.fee_token_account.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+
+        let fee_transfer_cpicontext = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            fee_transfer_accounts,
+            escrow_signer,
+        );
+
+        token::transfer(fee_transfer_cpicontext, fee)?;
+
+        //winning bid into the ticket's escrow
+        let payment_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.purchase_ticket_payment.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+
+        let payment_transfer_cpicontext = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            payment_transfer_accounts,
+            escrow_signer,
+        );
+
+        token::transfer(payment_transfer_cpicontext, price)?;
+
+        //the escrow has to be empty to close
+        if surplus > 0 {
+            let surplus_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.pay_to_token_account.to_account_info(),
+                authority: auction.to_account_info(),
+            };
+
+            let surplus_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                surplus_transfer_accounts,
+                escrow_signer,
+            );
+
+            token::transfer(surplus_transfer_cpicontext, surplus)?;
+        }
+
+        let close_escrow_accounts = anchor_spl::token::CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+
+        let close_escrow_cpicontext = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            close_escrow_accounts,
+            escrow_signer,
+        );
+
+        token::close_account(close_escrow_cpicontext)?;
+
+        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
+        purchase_ticket.init_purchase(*ctx.bumps.get("purchase_ticket").unwrap(), &ctx.accounts.product_snapshot,
+            ctx.accounts.product_snapshot_metadata.key(), &ctx.accounts.product_snapshot_metadata, auction.highest_bidder, auction.highest_bidder, &clock);
+        purchase_ticket.arbiter = load_store_arbiter(&ctx.accounts.store)?;
+        purchase_ticket.remaining_quantity = 1;
+        purchase_ticket.redeemed = 0;
+        purchase_ticket.price = price;
+        purchase_ticket.payment = ctx.accounts.purchase_ticket_payment.key();
+        purchase_ticket.fee = fee;
+
+        Ok(())
+    }
+
+    /// only before anyone bids. the unit goes back into inventory, and the auction's product snapshot is closed with it.
+    /// tokens sent to the escrow outside of bidding go to the authority before it's closed
+    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+
+        ctx.accounts.product.inventory += 1;
+
+        let product_key = auction.product;
+        let auction_seeds = &[
+            AUCTION_BYTES,
+            product_key.as_ref(),
+            &[auction.bump]
+        ];
+        let escrow_signer = &[&auction_seeds[..]];
+
+        if ctx.accounts.escrow.amount > 0 {
+            let sweep_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: auction.to_account_info(),
+            };
+
+            let sweep_transfer_cpicontext = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                sweep_transfer_accounts,
+                escrow_signer,
+            );
+
+            token::transfer(sweep_transfer_cpicontext, ctx.accounts.escrow.amount)?;
+        }
+
+        let close_escrow_accounts = anchor_spl::token::CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: auction.to_account_info(),
+        };
+
+        let close_escrow_cpicontext = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_escrow_accounts,
+            escrow_signer,
+        );
+
+        token::close_account(close_escrow_cpicontext)?;
+
+        Ok(())
+    }
+
+    pub fn create_store_ticket_taker(ctx: Context<CreateStoreTicketTaker>) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket_taker = &mut ctx.accounts.ticket_taker;
+    
+        ticket_taker.bump = *ctx.bumps.get("ticket_taker").unwrap();
+        ticket_taker.version = TICKET_TAKER_VERSION;
+        ticket_taker.taker = ctx.accounts.taker.key();
+        ticket_taker.entity_type = EntityType::STORE;
+        ticket_taker.entity = ctx.accounts.store.key();
+        ticket_taker.authorized_by = ctx.accounts.store_authority.key();
+        ticket_taker.enabled_slot = clock.slot;
+        ticket_taker.enabled_timestamp = clock.unix_timestamp;
+        ticket_taker.disabled_slot = 0;
+        ticket_taker.disabled_timestamp = 0;
+    
+        Ok(())
+    }
+    
+    pub fn create_product_ticket_taker(ctx: Context<CreateProductTicketTaker>) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket_taker = &mut ctx.accounts.ticket_taker;
+    
+        ticket_taker.bump = *ctx.bumps.get("ticket_taker").unwrap();
+        ticket_taker.version = TICKET_TAKER_VERSION;
+        ticket_taker.taker = ctx.accounts.taker.key();
+        ticket_taker.entity_type = EntityType::PRODUCT;
+        ticket_taker.entity = ctx.accounts.product.key();
+        ticket_taker.authorized_by = ctx.accounts.product_authority.key();
+        ticket_taker.enabled_slot = clock.slot;
+        ticket_taker.enabled_timestamp = clock.unix_timestamp;
+        ticket_taker.disabled_slot = 0;
+        ticket_taker.disabled_timestamp = 0;
+    
+        Ok(())
+    }
+
+    pub fn create_store_referral(ctx: Context<CreateStoreReferral>, basis_points: u16) -> Result<()> {
+        if basis_points == 0 || u64::from(basis_points) > BASIS_POINTS_DIVISOR {
+            return Err(ErrorCode::InvalidReferralRate.into());
+        }
+
+        let clock = Clock::get()?;
+        let referral = &mut ctx.accounts.referral;
+
+        referral.bump = *ctx.bumps.get("referral").unwrap();
+        referral.version = REFERRAL_VERSION;
+        referral.entity_type = EntityType::STORE;
+        referral.entity = ctx.accounts.store.key();
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.authorized_by = ctx.accounts.store_authority.key();
+        referral.basis_points = basis_points;
+        referral.slot = clock.slot;
+        referral.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn create_product_referral(ctx: Context<CreateProductReferral>, basis_points: u16) -> Result<()> {
+        if basis_points == 0 || u64::from(basis_points) > BASIS_POINTS_DIVISOR {
+            return Err(ErrorCode::InvalidReferralRate.into());
+        }
+
+        let clock = Clock::get()?;
+        let referral = &mut ctx.accounts.referral;
+
+        referral.bump = *ctx.bumps.get("referral").unwrap();
+        referral.version = REFERRAL_VERSION;
+        referral.entity_type = EntityType::PRODUCT;
+        referral.entity = ctx.accounts.product.key();
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.authorized_by = ctx.accounts.product_authority.key();
+        referral.basis_points = basis_points;
+        referral.slot = clock.slot;
+        referral.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Stops a referrer from earning on new store purchases. Tickets that already
+    /// recorded this referral keep their commission terms.
+    pub fn close_store_referral(_ctx: Context<CloseStoreReferral>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Stops a referrer from earning on new product purchases. Tickets that already
+    /// recorded this referral keep their commission terms.
+    pub fn close_product_referral(_ctx: Context<CloseProductReferral>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn create_store_coupon(ctx: Context<CreateStoreCoupon>, code: String, discount_type: u8, discount: u64,
+        expiration_timestamp: i64, max_uses: u32, max_uses_per_buyer: u32) -> Result<()> {
+
+        validate_coupon(&code, discount_type, discount)?;
+
+        let clock = Clock::get()?;
+        let coupon = &mut ctx.accounts.coupon;
+
+        coupon.bump = *ctx.bumps.get("coupon").unwrap();
+        coupon.version = COUPON_VERSION;
+        coupon.entity_type = EntityType::STORE;
+        coupon.entity = ctx.accounts.store.key();
+        coupon.authorized_by = ctx.accounts.store_authority.key();
+        coupon.code = code;
+        coupon.discount_type = discount_type;
+        coupon.discount = discount;
+        coupon.expiration_timestamp = expiration_timestamp;
+        coupon.max_uses = max_uses;
+        coupon.uses = 0;
+        coupon.max_uses_per_buyer = max_uses_per_buyer;
+        coupon.enabled = true;
+        coupon.slot = clock.slot;
+        coupon.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn create_product_coupon(ctx: Context<CreateProductCoupon>, code: String, discount_type: u8, discount: u64,
+        expiration_timestamp: i64, max_uses: u32, max_uses_per_buyer: u32) -> Result<()> {
+
+        validate_coupon(&code, discount_type, discount)?;
+
+        let clock = Clock::get()?;
+        let coupon = &mut ctx.accounts.coupon;
+
+        coupon.bump = *ctx.bumps.get("coupon").unwrap();
+        coupon.version = COUPON_VERSION;
+        coupon.entity_type = EntityType::PRODUCT;
+        coupon.entity = ctx.accounts.product.key();
+        coupon.authorized_by = ctx.accounts.product_authority.key();
+        coupon.code = code;
+        coupon.discount_type = discount_type;
+        coupon.discount = discount;
+        coupon.expiration_timestamp = expiration_timestamp;
+        coupon.max_uses = max_uses;
+        coupon.uses = 0;
+        coupon.max_uses_per_buyer = max_uses_per_buyer;
+        coupon.enabled = true;
+        coupon.slot = clock.slot;
+        coupon.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn set_coupon_enabled(ctx: Context<UpdateCoupon>, enabled: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        let coupon = &mut ctx.accounts.coupon;
+
+        coupon.enabled = enabled;
+        coupon.slot = clock.slot;
+        coupon.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn initiate_redemption(ctx: Context<InitiateRedemption>, nonce: u32, quantity: u64, take_expiration_minutes: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let redemption = &mut ctx.accounts.redemption;
+
+        redemption.bump = *ctx.bumps.get("redemption").unwrap();
+        redemption.nonce = nonce;
+        redemption.open(&mut ctx.accounts.purchase_ticket, ctx.accounts.purchase_ticket_authority.key(), quantity,
+            take_expiration_minutes, ctx.accounts.purchase_ticket_payment.amount, &clock)
+    }
+    
+    /// remaining accounts are the payout split recipients' token accounts, in order, when the ticket has a payout split
+    pub fn take_redemption<'info>(ctx: Context<'_, '_, '_, 'info, TakeRedemption<'info>>) -> Result<()>{ 
+        let clock = Clock::get()?;
+        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
+        let purchase_ticket_payment = &ctx.accounts.purchase_ticket_payment;
+        let pay_to_token_account = &ctx.accounts.pay_to_token_account;
+        let token_program = &ctx.accounts.token_program;
+        let (merchant_payment, referral_commission) = ctx.accounts.redemption.take(purchase_ticket, ctx.accounts.ticket_taker.key(),
+            ctx.accounts.ticket_taker_signer.key(), &clock)?;
+
+        let purchase_ticket_seed_bump = purchase_ticket.bump;
+        let product_snapshot_metadata_key = purchase_ticket.product_snapshot_metadata;
+        let buyer_key = purchase_ticket.buyer;
+        let purchase_ticket_seeds = &[
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata_key.as_ref(),
+            buyer_key.as_ref(),
+            &purchase_ticket.nonce.to_be_bytes(), 
+            &[purchase_ticket_seed_bump]
+        ];
+        let payment_transfer_signer = &[&purchase_ticket_seeds[..]];
+
+        //payment transfer
+        if purchase_ticket.payout_split == Pubkey::default() {
+            let payment_transfer_accounts = anchor_spl::token::Transfer {
+                from: purchase_ticket_payment.to_account_info(),
+                to: pay_to_token_account .to_account_info(),
+                authority: purchase_ticket.to_account_info(), //ata owned by twine program
+            };
+
+            let payment_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                payment_transfer_accounts,
+                payment_transfer_signer,
+            );
+
+            let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, merchant_payment)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, merchant_payment, false, |recipient_token_account, share| {
+                let split_transfer_accounts = anchor_spl::token::Transfer {
+                    from: purchase_ticket_payment.to_account_info(),
+                    to: recipient_token_account.clone(),
+                    authority: purchase_ticket.to_account_info(),
+                };
+
+                let split_transfer_cpicontext = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    split_transfer_accounts,
+                    payment_transfer_signer,
+                );
+
+                token::transfer(split_transfer_cpicontext, share)
+            })?;
+        }
+
+        if referral_commission > 0 {
+            check_referrer_token_account(&ctx.accounts.referrer_token_account, purchase_ticket.referrer, ctx.accounts.purchase_ticket_payment_mint.key())?;
+
+            let commission_transfer_accounts = anchor_spl::token::Transfer {
+                from: purchase_ticket_payment.to_account_info(),
+                to: ctx.accounts.referrer_token_account.to_account_info(),
+                authority: purchase_ticket.to_account_info(),
+            };
+
+            let commission_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                commission_transfer_accounts,
+                payment_transfer_signer,
+            );
+
+            token::transfer(commission_transfer_cpicontext, referral_commission)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn cancel_redemption(ctx: Context<CancelRedemption>) -> Result<()> {        
+        let clock = Clock::get()?;
+        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
+        let redemption = &mut ctx.accounts.redemption;
+        
+        if redemption.status != RedemptionStatus::WAITING || redemption.close_timestamp > 0 {
+            return Err(ErrorCode::AlreadyProcessed.into());
+        }
+
+        purchase_ticket.remaining_quantity += redemption.redeem_quantity;
+        purchase_ticket.pending_redemption -= redemption.redeem_quantity;
+
+        redemption.status = RedemptionStatus::CANCELLED;
+        redemption.close_slot = clock.slot;
+        redemption.close_timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn transfer_ticket(ctx: Context<TransferTicket>, nonce: u16, quantity: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let source_ticket = &ctx.accounts.source_ticket;
+
+        validate_ticket_transfer(source_ticket, &ctx.accounts.product_snapshot, quantity, clock.unix_timestamp)?;
+
+        let source_ticket_seed_bump = source_ticket.bump;
+        let product_snapshot_metadata_key = source_ticket.product_snapshot_metadata;
+        let buyer_key = source_ticket.buyer;
+        let source_ticket_seeds = &[
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata_key.as_ref(),
+            buyer_key.as_ref(),
+            &source_ticket.nonce.to_be_bytes(),
+            &[source_ticket_seed_bump]
+        ];
+        let payment_transfer_signer = &[&source_ticket_seeds[..]];
+  
+        let source_ticket_payment = &ctx.accounts.source_ticket_payment;
+        let destination_ticket_payment = &ctx.accounts.destination_ticket_payment;
+
+        //payment transfer
+        let payment_transfer_accounts = anchor_spl::token::Transfer {
+            from: source_ticket_payment.to_account_info(),
+            to: destination_ticket_payment .to_account_info(),
+            authority: source_ticket.to_account_info(),
+        };
+
+        let payment_transfer_cpicontext = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            payment_transfer_accounts,
+            payment_transfer_signer,
+        );
+
+        let _payment_transfer_result = token::transfer(payment_transfer_cpicontext, source_ticket.price * quantity)?;
+
+        split_ticket(&mut ctx.accounts.source_ticket, &mut ctx.accounts.destination_ticket, *ctx.bumps.get("destination_ticket").unwrap(),
+            nonce, ctx.accounts.destination_ticket_authority.key(), quantity, &clock);
+        ctx.accounts.destination_ticket.payment = ctx.accounts.destination_ticket_payment.key();
+
+        Ok(())
+    }
+
+    /// refunds the escrowed price of the quantity. the fee went to the fee account at purchase and isn't refunded;
+    /// ticket.fee is the record of what was charged
+    pub fn cancel_ticket(ctx: Context<CancelTicket>, quantity: u64) -> Result<()> {
+        refund_ticket(&mut ctx.accounts.ticket, &ctx.accounts.ticket_payment.to_account_info(), &ctx.accounts.payment_return.to_account_info(),
+            &mut ctx.accounts.product, &ctx.accounts.token_program.to_account_info(), quantity)
+    }
+
+    /// lamport priced products. the payment is escrowed in the purchase ticket itself instead of a token account
+    /// remaining accounts are the payout split recipients' wallets, in order, when the product has a payout split
+    pub fn buy_product_native<'info>(ctx: Context<'_, '_, '_, 'info, BuyProductNative<'info>>, nonce: u16, quantity: u64,
+        agreed_price: u64, agreed_fee: u64, agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32) -> Result<()>{
+
+        let product = &mut ctx.accounts.product;
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let product_snapshot_metadata = &mut ctx.accounts.product_snapshot_metadata;
+        let product_snapshot = &mut ctx.accounts.product_snapshot;
+        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
+        let pay_to = &ctx.accounts.pay_to;
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let clock = Clock::get()?;
+        let unit_price = load_unit_price(product, &ctx.accounts.price_feed, NATIVE_DECIMALS, clock.unix_timestamp)?;
+        let total_purchase_price = unit_price.checked_mul(quantity).ok_or(ErrorCode::NumericalOverflow)?;
+        let fee_schedule = purchase_fee_schedule(&ctx.accounts.fee_tier, &ctx.accounts.accepted_mint, &ctx.accounts.program_metadata, clock.unix_timestamp)?;
+        let fee = fee_schedule.calculate(total_purchase_price)?;
+        let referral = load_referral(&ctx.accounts.referral, product.key(), product.store)?;
+        let referral_commission = match &referral {
+            Some(referral) => apply_basis_points(total_purchase_price, referral.basis_points)?,
+            None => 0,
+        };
+
+        validate_purchase(product, quantity, agreed_expiration_timestamp,
+            agreed_expiration_minutes_after_purchase, agreed_expiration_minutes_after_redemption, clock.unix_timestamp)?;
+
+        if unit_price > agreed_price {
+            return Err(ErrorCode::PriceIsGreaterThanPayment.into());
+        }
+
+        if fee > agreed_fee {
+            return Err(ErrorCode::FeeIsGreaterThanAgreedFee.into());
+        }
+
+        if let Some(referral) = &referral {
+            if ctx.accounts.referrer.key() != referral.referrer {
+                return Err(ErrorCode::InvalidReferrer.into());
+            }
+        }
+
+        transfer_lamports(&buyer, &ctx.accounts.fee_account, &system_program, fee)?;
+
+        if product.redemption_type == RedemptionType::IMMEDIATE {  //pay out directly if redemption type is immediate
+            let merchant_payment = total_purchase_price - referral_commission;
+            if product.payout_split == Pubkey::default() {
+                transfer_lamports(&buyer, pay_to, &system_program, merchant_payment)?;
+            }
+            else {
+                pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, merchant_payment, true,
+                    |recipient, share| transfer_lamports(&buyer, recipient, &system_program, share))?;
+            }
+            transfer_lamports(&buyer, &ctx.accounts.referrer, &system_program, referral_commission)?;
+
+            purchase_ticket.referral_commission = referral_commission;
+            purchase_ticket.redeemed = quantity;
+            purchase_ticket.remaining_quantity = 0;
+        }
+        else {
+            transfer_lamports(&buyer, &purchase_ticket.to_account_info(), &system_program, total_purchase_price)?;
+
+            purchase_ticket.remaining_quantity = quantity;
+            purchase_ticket.redeemed = 0;
+        }
+
+        snapshot_product(product, product_snapshot_metadata, *ctx.bumps.get("product_snapshot_metadata").unwrap(),
+            product_snapshot, nonce, &clock);
+
+        purchase_ticket.init_purchase(*ctx.bumps.get("purchase_ticket").unwrap(), product, product_snapshot_metadata.key(), product_snapshot_metadata,
+            buyer.key(), ctx.accounts.buy_for.key(), &clock);
+        purchase_ticket.arbiter = load_store_arbiter(&ctx.accounts.store)?;
+        purchase_ticket.price = unit_price;
+        purchase_ticket.payment = purchase_ticket.key(); //lamports are escrowed in the ticket
+        purchase_ticket.fee = fee;
+        purchase_ticket.set_referral(ctx.accounts.referral.key(), &referral);
+
+        product.inventory -= quantity;
+
+        Ok(())
+    }
+
+    pub fn initiate_redemption_native(ctx: Context<InitiateRedemptionNative>, nonce: u32, quantity: u64, take_expiration_minutes: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let escrowed = escrowed_lamports(&ctx.accounts.purchase_ticket.to_account_info())?;
+        let redemption = &mut ctx.accounts.redemption;
+
+        redemption.bump = *ctx.bumps.get("redemption").unwrap();
+        redemption.nonce = nonce;
+        redemption.open(&mut ctx.accounts.purchase_ticket, ctx.accounts.purchase_ticket_authority.key(), quantity,
+            take_expiration_minutes, escrowed, &clock)
+    }
+
+    pub fn take_redemption_native<'info>(ctx: Context<'_, '_, '_, 'info, TakeRedemptionNative<'info>>) -> Result<()>{
+        let clock = Clock::get()?;
+        let purchase_ticket = &mut ctx.accounts.purchase_ticket;
+        let (merchant_payment, referral_commission) = ctx.accounts.redemption.take(purchase_ticket, ctx.accounts.ticket_taker.key(),
+            ctx.accounts.ticket_taker_signer.key(), &clock)?;
+
+        let purchase_ticket_info = purchase_ticket.to_account_info();
+        if purchase_ticket.payout_split == Pubkey::default() {
+            release_escrowed_lamports(&purchase_ticket_info, &ctx.accounts.pay_to, merchant_payment)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, merchant_payment, true,
+                |recipient, share| release_escrowed_lamports(&purchase_ticket_info, recipient, share))?;
+        }
+
+        if referral_commission > 0 {
+            if ctx.accounts.referrer.key() != purchase_ticket.referrer {
+                return Err(ErrorCode::InvalidReferrer.into());
+            }
+
+            release_escrowed_lamports(&purchase_ticket_info, &ctx.accounts.referrer, referral_commission)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn transfer_ticket_native(ctx: Context<TransferTicketNative>, nonce: u16, quantity: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let source_ticket = &ctx.accounts.source_ticket;
+
+        validate_ticket_transfer(source_ticket, &ctx.accounts.product_snapshot, quantity, clock.unix_timestamp)?;
+
+        release_escrowed_lamports(&source_ticket.to_account_info(), &ctx.accounts.destination_ticket.to_account_info(), source_ticket.price * quantity)?;
+
+        split_ticket(&mut ctx.accounts.source_ticket, &mut ctx.accounts.destination_ticket, *ctx.bumps.get("destination_ticket").unwrap(),
+            nonce, ctx.accounts.destination_ticket_authority.key(), quantity, &clock);
+        ctx.accounts.destination_ticket.payment = ctx.accounts.destination_ticket.key(); //lamports are escrowed in the ticket
+
+        Ok(())
+    }
+
+    /// same refund policy as cancel_ticket: the price comes back, the fee doesn't
+    pub fn cancel_ticket_native(ctx: Context<CancelTicketNative>, quantity: u64) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        let refund = ticket.cancel(&mut ctx.accounts.product, quantity)?;
+
+        release_escrowed_lamports(&ticket.to_account_info(), &ctx.accounts.payment_return, refund)
+    }
+
+    /// listed quantity is moved out of remaining_quantity so it can't be redeemed, transferred or cancelled until delisted
+    pub fn list_ticket(ctx: Context<ListTicket>, quantity: u64, price: u64) -> Result<()> {
+        if quantity <= 0 {
+            return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
+        }
+
+        let ticket = &mut ctx.accounts.ticket;
+
+        if quantity > ticket.remaining_quantity {
+            return Err(ErrorCode::InsufficientQuantity.into());
+        }
+
+        let clock = Clock::get()?;
+
+        if ticket.expiration > 0 && ticket.expiration < clock.unix_timestamp {
+            return Err(ErrorCode::TicketIsExpired.into());
+        }
+
+        let resale_policy = &ctx.accounts.product_snapshot.resale_policy;
+        resale_policy.check_transfer(ticket, clock.unix_timestamp)?;
+        resale_policy.check_resale_price(ticket, price)?;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.bump = *ctx.bumps.get("listing").unwrap();
+        listing.version = TICKET_LISTING_VERSION;
+        listing.ticket = ticket.key();
+        listing.seller = ctx.accounts.ticket_authority.key();
+        listing.payment_mint = ticket.payment_mint;
+        listing.price = price;
+        listing.quantity = quantity;
+        listing.slot = clock.slot;
+        listing.timestamp = clock.unix_timestamp;
+
+        ticket.remaining_quantity -= quantity;
+        ticket.listed_quantity += quantity;
+
+        Ok(())
+    }
+
+    /// the buyer pays the seller the asking price less the product snapshot's royalty, which goes to the merchant.
+    /// remaining accounts are the payout split recipients' token accounts, in order, when the ticket has a payout split
+    pub fn buy_listed_ticket<'info>(ctx: Context<'_, '_, '_, 'info, BuyListedTicket<'info>>, nonce: u16, quantity: u64, agreed_price: u64) -> Result<()> {
+        if quantity <= 0 {
+            return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
+        }
+
+        if quantity > ctx.accounts.listing.quantity {
+            return Err(ErrorCode::InsufficientQuantity.into());
+        }
+
+        //agreed_price is the most the buyer will pay per unit, so the seller can't raise the price out from under them
+        if ctx.accounts.listing.price > agreed_price {
+            return Err(ErrorCode::PriceIsGreaterThanPayment.into());
+        }
+
+        let clock = Clock::get()?;
+        let source_ticket = &ctx.accounts.source_ticket;
+
+        if source_ticket.expiration > 0 && source_ticket.expiration < clock.unix_timestamp {
+            return Err(ErrorCode::TicketIsExpired.into());
+        }
+
+        let resale_policy = &ctx.accounts.product_snapshot.resale_policy;
+        resale_policy.check_transfer(source_ticket, clock.unix_timestamp)?;
+        resale_policy.check_resale_price(source_ticket, ctx.accounts.listing.price)?;
+
+        let sale_price = ctx.accounts.listing.price.checked_mul(quantity).ok_or(ErrorCode::NumericalOverflow)?;
+        let royalty = apply_basis_points(sale_price, ctx.accounts.product_snapshot.royalty_basis_points)?;
+        let token_program = &ctx.accounts.token_program;
+        let buyer = &ctx.accounts.buyer;
+        let buyer_token_account = &ctx.accounts.buyer_token_account;
+
+        if royalty > 0 {
+            if source_ticket.payout_split == Pubkey::default() {
+                let royalty_transfer_accounts = anchor_spl::token::Transfer {
+                    from: buyer_token_account.to_account_info(),
+                    to: ctx.accounts.pay_to_token_account.to_account_info(),
+                    authority: buyer.to_account_info(),
+                };
+
+                let royalty_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), royalty_transfer_accounts);
+                token::transfer(royalty_transfer_cpicontext, royalty)?;
+            }
+            else {
+                pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, royalty, false, |recipient_token_account, share| {
+                    let split_transfer_accounts = anchor_spl::token::Transfer {
+                        from: buyer_token_account.to_account_info(),
+                        to: recipient_token_account.clone(),
+                        authority: buyer.to_account_info(),
+                    };
+
+                    let split_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), split_transfer_accounts);
+                    token::transfer(split_transfer_cpicontext, share)
+                })?;
+            }
+        }
+
+        //sale transfer
+        let sale_transfer_accounts = anchor_spl::token::Transfer {
+            from: buyer_token_account.to_account_info(),
+            to: ctx.accounts.seller_token_account.to_account_info(),
+            authority: buyer.to_account_info(),
+        };
+
+        let sale_transfer_cpicontext = CpiContext::new(token_program.to_account_info(), sale_transfer_accounts);
+        token::transfer(sale_transfer_cpicontext, sale_price - royalty)?;
+
+        let source_ticket_seed_bump = source_ticket.bump;
+        let product_snapshot_metadata_key = source_ticket.product_snapshot_metadata;
+        let buyer_key = source_ticket.buyer;
+        let source_ticket_seeds = &[
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata_key.as_ref(),
+            buyer_key.as_ref(),
+            &source_ticket.nonce.to_be_bytes(),
+            &[source_ticket_seed_bump]
+        ];
+        let payment_transfer_signer = &[&source_ticket_seeds[..]];
+
+        //escrowed payment moves with the ticket, same as transfer_ticket
+        let payment_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.source_ticket_payment.to_account_info(),
+            to: ctx.accounts.destination_ticket_payment.to_account_info(),
+            authority: source_ticket.to_account_info(),
+        };
+
+        let payment_transfer_cpicontext = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            payment_transfer_accounts,
+            payment_transfer_signer,
+        );
+
+        token::transfer(payment_transfer_cpicontext, source_ticket.price * quantity)?;
+
+        //the sold quantity comes off the listing and is split off the ticket like any other transfer
+        ctx.accounts.source_ticket.listed_quantity -= quantity;
+        ctx.accounts.source_ticket.remaining_quantity += quantity;
+
+        split_ticket(&mut ctx.accounts.source_ticket, &mut ctx.accounts.destination_ticket, *ctx.bumps.get("destination_ticket").unwrap(),
+            nonce, ctx.accounts.buyer.key(), quantity, &clock);
+        ctx.accounts.destination_ticket.buyer = ctx.accounts.buyer.key(); //the destination ticket's address is derived from its buyer
+        ctx.accounts.destination_ticket.payment = ctx.accounts.destination_ticket_payment.key();
+
+        ctx.accounts.listing.quantity -= quantity;
+
+        if ctx.accounts.listing.quantity == 0 {
+            ctx.accounts.listing.close(ctx.accounts.seller.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// returns the unsold listed quantity to the ticket
+    pub fn delist_ticket(ctx: Context<DelistTicket>) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        let listing = &ctx.accounts.listing;
+
+        ticket.remaining_quantity += listing.quantity;
+        ticket.listed_quantity -= listing.quantity;
+
+        Ok(())
+    }
+
+    /// hands the ticket to a hash-lock. the ticket's authority becomes the gift, so it's frozen until claimed with the
+    /// secret or reclaimed by the giver after the claim deadline. composes with buy_product in the same transaction.
+    /// gifting moves the whole ticket, so it has to be transferable under the snapshot's resale policy
+    pub fn create_gift(ctx: Context<CreateGift>, secret_hash: [u8; 32], claim_minutes: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket = &mut ctx.accounts.ticket;
+
+        if claim_minutes == 0 || ticket.remaining_quantity == 0 {
+            return Err(ErrorCode::InvalidGift.into());
+        }
+
+        validate_ticket_transfer(ticket, &ctx.accounts.product_snapshot, ticket.remaining_quantity, clock.unix_timestamp)?;
+
+        ticket.authority = ctx.accounts.gift.key();
+
+        let gift = &mut ctx.accounts.gift;
+        gift.bump = *ctx.bumps.get("gift").unwrap();
+        gift.version = GIFT_VERSION;
+        gift.ticket = ticket.key();
+        gift.giver = ctx.accounts.giver.key();
+        gift.secret_hash = secret_hash;
+        gift.claim_deadline = clock.unix_timestamp + i64::from(claim_minutes) * 60;
+        gift.slot = clock.slot;
+        gift.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// the gift's secret_hash is the sha256 of the secret followed by the recipient's key, so a secret seen in a pending
+    /// claim can't be used to claim for anyone else. the recipient becomes the ticket authority.
+    /// unclaimed gifts can still be claimed after the deadline until the giver reclaims them
+    pub fn claim_gift(ctx: Context<ClaimGift>, secret: Vec<u8>) -> Result<()> {
+        let recipient_key = ctx.accounts.recipient.key();
+        if anchor_lang::solana_program::hash::hashv(&[&secret, recipient_key.as_ref()]).to_bytes() != ctx.accounts.gift.secret_hash {
+            return Err(ErrorCode::InvalidGiftSecret.into());
+        }
+
+        ctx.accounts.ticket.authority = ctx.accounts.recipient.key();
+
+        Ok(())
+    }
+
+    /// after the claim deadline, the giver gets the ticket back and its remaining quantity is refunded like cancel_ticket
+    pub fn reclaim_gift(ctx: Context<ReclaimGift>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp < ctx.accounts.gift.claim_deadline {
+            return Err(ErrorCode::GiftClaimDeadlineNotPassed.into());
+        }
+
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.authority = ctx.accounts.giver.key();
+
+        let remaining_quantity = ticket.remaining_quantity;
+        refund_ticket(ticket, &ctx.accounts.ticket_payment.to_account_info(), &ctx.accounts.payment_return.to_account_info(),
+            &mut ctx.accounts.product, &ctx.accounts.token_program.to_account_info(), remaining_quantity)
+    }
+
+    /// reclaim_gift for lamport priced tickets. refunded like cancel_ticket_native
+    pub fn reclaim_gift_native(ctx: Context<ReclaimGiftNative>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp < ctx.accounts.gift.claim_deadline {
+            return Err(ErrorCode::GiftClaimDeadlineNotPassed.into());
+        }
+
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.authority = ctx.accounts.giver.key();
+
+        let remaining_quantity = ticket.remaining_quantity;
+        let refund = ticket.cancel(&mut ctx.accounts.product, remaining_quantity)?;
+
+        release_escrowed_lamports(&ticket.to_account_info(), &ctx.accounts.payment_return, refund)
+    }
+
+    /// the ticket authority or the store can dispute a store ticket's escrow, either its remaining quantity or one of its
+    /// waiting redemptions. pass the system program as the redemption to dispute the remaining quantity. the ticket is
+    /// frozen until the arbiter recorded on the ticket rules
+    pub fn open_dispute(ctx: Context<OpenDispute>) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket = &mut ctx.accounts.ticket;
+        let redemption_info = &ctx.accounts.redemption;
+
+        let quantity = if *redemption_info.key == Pubkey::default() {
+            ticket.remaining_quantity
+        }
+        else {
+            let redemption = Account::<Redemption>::try_from(redemption_info)?;
+            if redemption.purchase_ticket != ticket.key() {
+                return Err(ErrorCode::PublicKeyMismatch.into());
+            }
+
+            if redemption.status != RedemptionStatus::WAITING || redemption.close_timestamp > 0 {
+                return Err(ErrorCode::AlreadyProcessed.into());
+            }
+
+            redemption.redeem_quantity
+        };
+
+        if quantity == 0 || ticket.arbiter == Pubkey::default() {
+            return Err(ErrorCode::InvalidDispute.into());
+        }
+
+        //the store's authorities may have changed since the ticket was bought
+        if ctx.accounts.store.is_authorized(&ticket.arbiter) || ticket.arbiter == ticket.pay_to {
+            return Err(ErrorCode::InvalidArbiter.into());
+        }
+
+        //the buyer's share would go to the gift, which can't spend it. the ticket can be disputed once it's claimed or reclaimed
+        let (gift_key, _) = Pubkey::find_program_address(&[GIFT_BYTES, ticket.key().as_ref()], &crate::ID);
+        if ticket.authority == gift_key {
+            return Err(ErrorCode::TicketIsGifted.into());
+        }
+
+        ticket.dispute = ctx.accounts.dispute.key();
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.bump = *ctx.bumps.get("dispute").unwrap();
+        dispute.version = DISPUTE_VERSION;
+        dispute.ticket = ticket.key();
+        dispute.redemption = redemption_info.key();
+        dispute.store = ctx.accounts.store.key();
+        dispute.arbiter = ticket.arbiter;
+        dispute.opened_by = ctx.accounts.opened_by.key();
+        dispute.quantity = quantity;
+        dispute.price = ticket.price;
+        dispute.slot = clock.slot;
+        dispute.timestamp = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// the ticket's arbiter splits the disputed escrow, buyer_basis_points to the ticket authority and the rest paid out
+    /// like a redemption, to pay_to or the payout split less the referral commission.
+    /// the disputed quantity is used up, and a disputed redemption is closed as arbitrated
+    pub fn resolve_dispute<'info>(ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>, buyer_basis_points: u16) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket = &mut ctx.accounts.ticket;
+        let (buyer_share, merchant_payment, referral_commission) = ticket.resolve_dispute(&ctx.accounts.dispute, buyer_basis_points,
+            &ctx.accounts.redemption, &clock)?;
+
+        let ticket_seed_bump = ticket.bump;
+        let product_snapshot_metadata_key = ticket.product_snapshot_metadata;
+        let buyer_key = ticket.buyer;
+        let ticket_seeds = &[
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata_key.as_ref(),
+            buyer_key.as_ref(),
+            &ticket.nonce.to_be_bytes(),
+            &[ticket_seed_bump]
+        ];
+        let payment_transfer_signer = &[&ticket_seeds[..]];
+        let token_program = &ctx.accounts.token_program;
+
+        //buyer's share
+        let buyer_transfer_accounts = anchor_spl::token::Transfer {
+            from: ctx.accounts.ticket_payment.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ticket.to_account_info(),
+        };
+
+        let buyer_transfer_cpicontext = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            buyer_transfer_accounts,
+            payment_transfer_signer,
+        );
+
+        token::transfer(buyer_transfer_cpicontext, buyer_share)?;
+
+        //merchant's share
+        if ticket.payout_split == Pubkey::default() {
+            let merchant_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.ticket_payment.to_account_info(),
+                to: ctx.accounts.pay_to_token_account.to_account_info(),
+                authority: ticket.to_account_info(),
+            };
+
+            let merchant_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                merchant_transfer_accounts,
+                payment_transfer_signer,
+            );
+
+            token::transfer(merchant_transfer_cpicontext, merchant_payment)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, merchant_payment, false, |recipient_token_account, share| {
+                let split_transfer_accounts = anchor_spl::token::Transfer {
+                    from: ctx.accounts.ticket_payment.to_account_info(),
+                    to: recipient_token_account.clone(),
+                    authority: ticket.to_account_info(),
+                };
+
+                let split_transfer_cpicontext = CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    split_transfer_accounts,
+                    payment_transfer_signer,
+                );
+
+                token::transfer(split_transfer_cpicontext, share)
+            })?;
+        }
+
+        if referral_commission > 0 {
+            check_referrer_token_account(&ctx.accounts.referrer_token_account, ticket.referrer, ctx.accounts.payment_mint.key())?;
+
+            let commission_transfer_accounts = anchor_spl::token::Transfer {
+                from: ctx.accounts.ticket_payment.to_account_info(),
+                to: ctx.accounts.referrer_token_account.to_account_info(),
+                authority: ticket.to_account_info(),
+            };
+
+            let commission_transfer_cpicontext = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                commission_transfer_accounts,
+                payment_transfer_signer,
+            );
+
+            token::transfer(commission_transfer_cpicontext, referral_commission)?;
+        }
+
+        Ok(())
+    }
+
+    /// resolve_dispute for lamport priced tickets. the shares are released from the lamports escrowed in the ticket.
+    /// remaining accounts are the payout split recipients' wallets, in order, when the ticket has a payout split
+    pub fn resolve_dispute_native<'info>(ctx: Context<'_, '_, '_, 'info, ResolveDisputeNative<'info>>, buyer_basis_points: u16) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket = &mut ctx.accounts.ticket;
+        let (buyer_share, merchant_payment, referral_commission) = ticket.resolve_dispute(&ctx.accounts.dispute, buyer_basis_points,
+            &ctx.accounts.redemption, &clock)?;
+
+        let ticket_info = ticket.to_account_info();
+        release_escrowed_lamports(&ticket_info, &ctx.accounts.ticket_authority, buyer_share)?;
+
+        if ticket.payout_split == Pubkey::default() {
+            release_escrowed_lamports(&ticket_info, &ctx.accounts.pay_to, merchant_payment)?;
+        }
+        else {
+            pay_out_split(&ctx.accounts.payout_split, ctx.remaining_accounts, merchant_payment, true,
+                |recipient, share| release_escrowed_lamports(&ticket_info, recipient, share))?;
+        }
+
+        if referral_commission > 0 {
+            if ctx.accounts.referrer.key() != ticket.referrer {
+                return Err(ErrorCode::InvalidReferrer.into());
+            }
+
+            release_escrowed_lamports(&ticket_info, &ctx.accounts.referrer, referral_commission)?;
+        }
+
+        Ok(())
+    }
+
+    /// migrations bring an account from whatever version it's at to the current one in a single call.
+    /// every field added after the stored version is defaulted, oldest first.
+    pub fn migrate_program_metadata(ctx: Context<MigrateProgramMetadata>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let version = grow_for_migration(&account, &ctx.accounts.payer, &ctx.accounts.system_program,
+            ProgramMetadata::discriminator(), 2, PROGRAM_VERSION, &PROGRAM_METADATA_VERSION_GROWTH)?;
+        let mut program_metadata = ProgramMetadata::try_deserialize(&mut &account.data.borrow()[..])?;
+
+        if version < 1 {
+            //fee became a FeeSchedule, the existing flat fee carries over
+            program_metadata.fee = FeeSchedule { flat_fee: program_metadata.fee.flat_fee, ..FeeSchedule::default() };
+            program_metadata.pending_authority = Pubkey::default();
+            program_metadata.pending_secondary_authority = Pubkey::default();
+            program_metadata.multisig = Pubkey::default();
+            program_metadata.pending_fee = FeeSchedule::default();
+            program_metadata.pending_fee_timestamp = 0;
+            program_metadata.paused = 0;
+        }
+
+        if version < 2 {
+            program_metadata.fee_recipients = Vec::new();
+        }
+
+        program_metadata.version = PROGRAM_VERSION;
+        program_metadata.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+
+        //every purchase before the registry was in the original payment mint, so it stays accepted
+        let legacy_accepted_mint = &mut ctx.accounts.legacy_accepted_mint;
+        if legacy_accepted_mint.mint == Pubkey::default() {
+            let clock = Clock::get()?;
+
+            legacy_accepted_mint.bump = *ctx.bumps.get("legacy_accepted_mint").unwrap();
+            legacy_accepted_mint.version = ACCEPTED_MINT_VERSION;
+            legacy_accepted_mint.mint = crate::payment_token::ID;
+            legacy_accepted_mint.enabled = true;
+            legacy_accepted_mint.fee_override = false;
+            legacy_accepted_mint.fee = FeeSchedule::default();
+            legacy_accepted_mint.pending_fee_override = false;
+            legacy_accepted_mint.pending_fee = FeeSchedule::default();
+            legacy_accepted_mint.pending_fee_timestamp = 0;
+            legacy_accepted_mint.slot = clock.slot;
+            legacy_accepted_mint.timestamp = clock.unix_timestamp;
+        }
+
+        Ok(())
+    }
+
+    pub fn migrate_store(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let version = grow_for_migration(&account, &ctx.accounts.payer, &ctx.accounts.system_program,
+            Store::discriminator(), 1, STORE_VERSION, &STORE_VERSION_GROWTH)?;
+        let mut store = Store::try_deserialize(&mut &account.data.borrow()[..])?;
+
+        if version < 1 {
+            store.fee_tier = Pubkey::default();
+        }
+
+        if version < 2 {
+            store.payout_split = Pubkey::default();
+        }
+
+        if version < 3 {
+            store.arbiter = Pubkey::default();
+        }
+
+        store.version = STORE_VERSION;
+        store.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    pub fn migrate_product(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let version = grow_for_migration(&account, &ctx.accounts.payer, &ctx.accounts.system_program,
+            Product::discriminator(), 1, PRODUCT_VERSION, &PRODUCT_VERSION_GROWTH)?;
+        let mut product = Product::try_deserialize(&mut &account.data.borrow()[..])?;
+
+        if version < 1 {
+            product.payment_mint = crate::payment_token::ID; //every purchase used to be in the original payment mint
+        }
+
+        if version < 2 {
+            product.price_feed = Pubkey::default();
+        }
+
+        if version < 3 {
+            product.payout_split = Pubkey::default();
+        }
+
+        if version < 4 {
+            product.royalty_basis_points = 0;
+        }
+
+        if version < 5 {
+            product.resale_policy = ResalePolicy::default(); //transfers were always allowed
+        }
+
+        if version < 6 {
+            product.layaway_policy = LayawayPolicy::default();
+        }
+
+        if version < 7 {
+            product.subscription_period_minutes = 0;
+        }
+
+        if version < 8 {
+            product.dutch_auction = DutchAuction::default();
+        }
+
+        product.version = PRODUCT_VERSION;
+        product.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    pub fn migrate_ticket(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let version = grow_for_migration(&account, &ctx.accounts.payer, &ctx.accounts.system_program,
+            PurchaseTicket::discriminator(), 1, PURCHASE_TICKET_VERSION, &PURCHASE_TICKET_VERSION_GROWTH)?;
+        let mut ticket = PurchaseTicket::try_deserialize(&mut &account.data.borrow()[..])?;
+
+        if version < 1 {
+            ticket.fee = 0; //wasn't recorded
+        }
+
+        if version < 2 {
+            ticket.referral = Pubkey::default();
+            ticket.referrer = Pubkey::default();
+            ticket.referral_basis_points = 0;
+            ticket.referral_commission = 0;
+        }
+
+        if version < 3 {
+            ticket.payment_mint = crate::payment_token::ID;
+        }
+
+        if version < 4 {
+            ticket.payout_split = Pubkey::default();
+        }
+
+        if version < 5 {
+            ticket.listed_quantity = 0;
+        }
+
+        if version < 6 {
+            ticket.amount_due = 0;
+            ticket.payment_deadline = 0;
+            ticket.forfeit_basis_points = 0;
+        }
+
+        if version < 7 {
+            ticket.coupon = Pubkey::default();
+            ticket.discount = 0;
+        }
+
+        if version < 8 {
+            ticket.dispute = Pubkey::default();
+            ticket.arbiter = Pubkey::default();
+        }
+
+        ticket.version = PURCHASE_TICKET_VERSION;
+        ticket.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    pub fn migrate_fee_tier(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let version = grow_for_migration(&account, &ctx.accounts.payer, &ctx.accounts.system_program,
+            FeeTier::discriminator(), 1, FEE_TIER_VERSION, &FEE_TIER_VERSION_GROWTH)?;
+        let mut fee_tier = FeeTier::try_deserialize(&mut &account.data.borrow()[..])?;
+
+        if version < 1 {
+            fee_tier.pending_fee = FeeSchedule::default();
+            fee_tier.pending_expiration_timestamp = 0;
+            fee_tier.pending_fee_timestamp = 0;
+        }
+
+        fee_tier.version = FEE_TIER_VERSION;
+        fee_tier.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    pub fn migrate_redemption(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        grow_for_migration(&account, &ctx.accounts.payer, &ctx.accounts.system_program,
+            Redemption::discriminator(), 1, REDEMPTION_VERSION, &REDEMPTION_VERSION_GROWTH)?;
+        let mut redemption = Redemption::try_deserialize(&mut &account.data.borrow()[..])?;
+
+        //no fields to default yet
+
+        redemption.version = REDEMPTION_VERSION;
+        redemption.try_serialize(&mut &mut account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+
+}
+
+
+
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: owner and discriminator are checked before it's resized and deserialized at the next version
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProgramMetadata<'info> {
+    /// CHECK: owner and discriminator are checked before it's resized and deserialized at the next version
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ACCEPTED_MINT_SIZE,
+        seeds = [ACCEPTED_MINT_BYTES, crate::payment_token::ID.as_ref()],
+        bump
+    )]
+    pub legacy_accepted_mint: Account<'info, AcceptedMint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init,
+        payer = creator,
+        space = 8 + PROGRAM_METADATA_SIZE,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump)]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK:
+    pub authority: AccountInfo<'info>,
+    /// CHECK:
+    pub secondary_authority: AccountInfo<'info>,
+    /// CHECK:
+    #[account(owner=anchor_lang::system_program::ID)]
+    pub fee_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramMetadata<'info> {
+    #[account(
+        mut,
+        constraint= program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump= program_metadata.bump)]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    #[account(
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::authority = program_metadata,
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeNativeFees<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeFeeAccount<'info> {
+
+    #[account(
+        mut,
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump=program_metadata.bump
+    )]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK:
+    #[account(owner = anchor_lang::system_program::ID)]
+    pub fee_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthorityChange<'info> {
+    #[account(
+        mut,
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: has to sign accept_authority_change before it becomes the authority
+    pub new_authority: AccountInfo<'info>,
+
+    /// CHECK: doesn't much need validation
+    pub new_secondary_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthorityChange<'info> {
+    #[account(
+        mut,
+        constraint = program_metadata.pending_authority == new_authority.key() @ ErrorCode::IncorrectAuthority,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+}
+
+
+#[derive(Accounts)]
+pub struct CreateAdminMultisig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ADMIN_MULTISIG_SIZE,
+        seeds = [ADMIN_MULTISIG_BYTES],
+        bump
+    )]
+    pub admin_multisig: Account<'info, AdminMultisig>,
+
+    #[account(
+        mut,
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Account<'info, ProgramMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(action: u8, fee: FeeSchedule, fee_account: Pubkey, authority: Pubkey, secondary_authority: Pubkey, threshold: u8, signers: Vec<Pubkey>, paused: u64,
+    fee_recipients: Vec<FeeRecipient>)]
+pub struct CreateAdminProposal<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + ADMIN_PROPOSAL_SIZE,
+        seeds = [ADMIN_PROPOSAL_BYTES, admin_multisig.key().as_ref(), &admin_multisig.proposal_count.to_be_bytes()],
+        bump
+    )]
+    pub proposal: Box<Account<'info, AdminProposal>>,
+
+    #[account(
+        mut,
+        seeds = [ADMIN_MULTISIG_BYTES],
+        bump = admin_multisig.bump
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveAdminProposal<'info> {
+    #[account(
+        mut,
+        seeds = [ADMIN_PROPOSAL_BYTES, admin_multisig.key().as_ref(), &proposal.id.to_be_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Box<Account<'info, AdminProposal>>,
+
+    #[account(
+        seeds = [ADMIN_MULTISIG_BYTES],
+        bump = admin_multisig.bump
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAdminProposal<'info> {
+    #[account(
+        mut,
+        seeds = [ADMIN_PROPOSAL_BYTES, admin_multisig.key().as_ref(), &proposal.id.to_be_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Box<Account<'info, AdminProposal>>,
+
+    #[account(
+        mut,
+        seeds = [ADMIN_MULTISIG_BYTES],
+        bump = admin_multisig.bump
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+
+    #[account(
+        mut,
+        constraint = program_metadata.multisig == admin_multisig.key(),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    pub executor: Signer<'info>,
+}
+
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ACCEPTED_MINT_SIZE,
+        seeds = [ACCEPTED_MINT_BYTES, mint.key().as_ref()],
+        bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddNativeAcceptedMint<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ACCEPTED_MINT_SIZE,
+        seeds = [ACCEPTED_MINT_BYTES, NATIVE_PAYMENT_MINT.as_ref()],
+        bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePriceFeed<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PRICE_FEED_SIZE,
+        seeds = [PRICE_FEED_BYTES, accepted_mint.mint.as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, accepted_mint.mint.as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    ///CHECK: any account with a signer can be the updater
+    pub updater: AccountInfo<'info>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePriceFeed<'info> {
+    #[account(
+        mut,
+        seeds = [PRICE_FEED_BYTES, price_feed.mint.as_ref()],
+        constraint = price_feed.version == PRICE_FEED_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = price_feed.bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    ///CHECK: any account with a signer can be the updater
+    pub updater: AccountInfo<'info>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(
+        mut,
+        seeds = [PRICE_FEED_BYTES, price_feed.mint.as_ref()],
+        constraint = price_feed.version == PRICE_FEED_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = price_feed.bump,
+        constraint = price_feed.updater == updater.key() @ ErrorCode::InvalidPriceFeedUpdater
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub updater: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [ACCEPTED_MINT_BYTES, accepted_mint.mint.as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    #[account(
+        constraint = program_metadata.is_authorized(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeAcceptedMintFee<'info> {
+    #[account(
+        mut,
+        seeds = [ACCEPTED_MINT_BYTES, accepted_mint.mint.as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u16, status: u8, name: String, description: String, data: Vec<u8>)]
+pub struct CreateStore<'info> {
+    #[account(init,
+        payer=creator,
+        space=8 + STORE_SIZE + data.len(),
+        seeds=[STORE_SEED_BYTES, creator.key().as_ref(), &id.to_be_bytes()],
+        bump)]
+    pub store: Box<Account<'info, Store>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account()]
+    pub authority: AccountInfo<'info>,
+     
+    /// CHECK: doesn't much need validation
+     #[account()]
+    pub secondary_authority: AccountInfo<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::CREATE_STORE) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+
+#[derive(Accounts)]
+#[instruction(status: u8, name: String, description: String, data: Vec<u8>)]
+pub struct UpdateStore<'info> {
+    #[account(mut,
+        constraint = store.is_authorized(&authority.key),
+        realloc = 8 + STORE_SIZE + data.len(),
+        realloc::payer = authority,
+        realloc::zero = true,
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = store.bump)]   
+    pub store: Box<Account<'info, Store>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+//consider using metaplex for this.
+#[derive(Accounts)]
+#[instruction(id: u32, status: u8, //_mint_decimals: u8,
+    price: u64, inventory: u64, redemption_type: u8,
+    expiration_timestamp: i64, expiration_minutes_after_purchase: u32, expiration_minutes_after_redemption: u32,
+    name: String, description: String, data: Vec<u8>)]
+pub struct CreateStoreProduct<'info> {
+/*
+    #[account(
+        init,
+        payer=creator,
+        seeds=[PRODUCT_MINT_BYTES, product.key().as_ref()],
+        bump,
+        mint::decimals=_mint_decimals,
+        mint::authority=mint, //dont give the owner this, or they could mint tokens without going through this program for validation
+        mint::freeze_authority=mint,
+    )]
+    pub mint: Account<'info, Mint>, //mint account for this product. The owner mints tokens to the product_mint account for this program to use
+  */
+    #[account(init,
+        payer=creator,
+        space=8 + PRODUCT_SIZE + data.len(), 
+        seeds=[PRODUCT_SEED_BYTES, creator.key().as_ref(), &id.to_be_bytes()], 
+        bump)]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(mut,
+        constraint = store.is_authorized(&creator.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=store.bump)]
+    pub store: Box<Account<'info, Store>>,
+  
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account()]
+    pub authority: AccountInfo<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account()]
+    pub secondary_authority: AccountInfo<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account(owner=anchor_lang::system_program::ID)]
+    pub pay_to: AccountInfo<'info>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, accepted_mint.mint.as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u32, status: u8,
+    price: u64, inventory: u64, redemption_type: u8,
+    expiration_timestamp: i64, expiration_minutes_after_purchase: u32, expiration_minutes_after_redemption: u32,
+    name: String, description: String, data: Vec<u8>)]
+pub struct CreateProduct<'info> {
+/*
+    #[account(
+        init,
+        payer=creator,
+        seeds=[PRODUCT_MINT_BYTES, product.key().as_ref()],
+        bump,
+        mint::decimals=_mint_decimals,
+        mint::authority=mint, //dont give the owner this, or they could mint tokens without going through this program for validation
+        mint::freeze_authority=mint,
+    )]
+    pub mint: Account<'info, Mint>, //mint account for this product. The owner mints tokens to the product_mint account for this program to use
+ */ 
+    #[account(init,
+        payer=creator,
+        space=8 + PRODUCT_SIZE + data.len(), 
+        seeds=[PRODUCT_SEED_BYTES, creator.key().as_ref(), &id.to_be_bytes()], 
+        bump)]
+    pub product: Box<Account<'info, Product>>,
+  
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account()]
+    pub authority: AccountInfo<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account()]
+    pub secondary_authority: AccountInfo<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account(owner=anchor_lang::system_program::ID)]
+    pub pay_to: AccountInfo<'info>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, accepted_mint.mint.as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(status: u8, price: u64, inventory: u64, redemption_type: u8,
+    expiration_timestamp: i64, expiration_minutes_after_purchase: u32, expiration_minutes_after_redemption: u32,
+    name: String, description: String, data: Vec<u8>)]
+pub struct UpdateProduct<'info> {
+    #[account(mut,
+        constraint = product.is_authorized(&authority.key),
+        realloc = 8 + PRODUCT_SIZE + data.len(),
+        realloc::payer = authority,
+        realloc::zero = true,
+        //has_one=authority,
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],  
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u32, recipients: Vec<PayoutRecipient>)]
+pub struct CreatePayoutSplit<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PAYOUT_SPLIT_SIZE,
+        seeds = [PAYOUT_SPLIT_BYTES, creator.key().as_ref(), &id.to_be_bytes()],
+        bump
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetStorePayoutSplit<'info> {
+    #[account(mut,
+        constraint = store.is_authorized(&authority.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = store.bump
+    )]
+    pub store: Box<Account<'info, Store>>,
+
+    /// CHECK: PayoutSplit used by default for new store products. the system program clears it
+    pub payout_split: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStoreArbiter<'info> {
+    #[account(mut,
+        constraint = store.is_authorized(&authority.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = store.bump
+    )]
+    pub store: Box<Account<'info, Store>>,
+
+    /// CHECK: any wallet the store trusts to rule on disputes
+    pub arbiter: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProductPayoutSplit<'info> {
+    #[account(mut,
+        constraint = product.is_authorized(&authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    /// CHECK: PayoutSplit for the product's revenue. the system program clears it
+    pub payout_split: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProductTerms<'info> {
+    #[account(mut,
+        constraint = product.is_authorized(&authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProductPriceTiers<'info> {
+    #[account(
+        constraint = product.is_authorized(&authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PRICE_TIERS_SIZE,
+        seeds = [PRICE_TIERS_BYTES, product.key().as_ref()],
+        bump
+    )]
+    pub price_tiers: Box<Account<'info, PriceTiers>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProductPriceFeed<'info> {
+    #[account(mut,
+        constraint = product.is_authorized(&authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    /// CHECK: PriceFeed for the product's payment mint. the system program clears it
+    pub price_feed: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16, quantity: u64, agreed_price: u64, agreed_fee: u64,
+    agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32)]
+pub struct BuyProduct<'info> {
+
+    #[account(
+        mut,
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()], 
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PRODUCT_SNAPSHOT_METADATA_SIZE,
+        seeds = [
+            PRODUCT_SNAPSHOT_METADATA_BYTES,
+            product.key().as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()
+        ],
+        bump
+    )]
+    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PRODUCT_SIZE + product.data.len(),
+        seeds=[PRODUCT_SNAPSHOT_BYTES, product_snapshot_metadata.key().as_ref()], 
+        bump
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        //make nonce part of the seed, because in the future a new snapshot will only be created when a product changes
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata.key().as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()], 
+        bump
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = purchase_ticket,
+    )]
+    pub purchase_ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(address = product.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub purchase_ticket_payment_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, purchase_ticket_payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        //init_if_needed,
+        //payer=buyer,
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: we good
+    #[account(address = product.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+   
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: doesn't much need validation  
+    #[account(owner=system_program.key())] 
+    pub buy_for: AccountInfo<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::BUY_PRODUCT) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump=program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = fee_account,
+    )]
+    pub fee_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK:
+    #[account(address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    /// CHECK: fee tier of the product's store, or of its creator for lone products. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, product.fee_tier_entity().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PriceFeed. the system program when the product isn't priced off a feed
+    #[account(address = product.price_feed)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: the product's Store, for the arbiter recorded on the ticket. the system program for lone products
+    #[account(address = product.store)]
+    pub store: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PayoutSplit. the system program when the product pays out to pay_to
+    #[account(address = product.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    /// CHECK: Referral of the product or its store. the system program stands in for no referral
+    pub referral: UncheckedAccount<'info>,
+
+    /// CHECK: referrer's token account, validated when there's a referral. otherwise any writable account, like pay_to_token_account
+    #[account(mut)]
+    pub referrer_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PriceTiers. may not exist
+    #[account(
+        seeds = [PRICE_TIERS_BYTES, product.key().as_ref()],
+        bump
+    )]
+    pub price_tiers: UncheckedAccount<'info>,
+
+    /// CHECK: the snapshot's copy of price_tiers, created when the product has tiers
+    #[account(
+        mut,
+        seeds = [PRICE_TIERS_BYTES, product_snapshot.key().as_ref()],
+        bump
+    )]
+    pub price_tiers_snapshot: UncheckedAccount<'info>,
+
+    /// CHECK: Coupon of the product or its store, writable. the system program stands in for no coupon
+    pub coupon: UncheckedAccount<'info>,
+
+    /// CHECK: the buyer's CouponUsage PDA, writable, when the coupon has a per buyer limit. otherwise any account, like the system program
+    pub coupon_usage: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16)]
+pub struct Checkout<'info> {
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + ORDER_SIZE,
+        seeds = [ORDER_BYTES, buyer.key().as_ref(), &nonce.to_be_bytes()],
+        bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        seeds = [STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = store.bump
+    )]
+    pub store: Box<Account<'info, Store>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account(owner=system_program.key())]
+    pub buy_for: AccountInfo<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::BUY_PRODUCT) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = fee_account,
+    )]
+    pub fee_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK:
+    #[account(address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    /// CHECK: fee tier of the store. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, store.key().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16, quantity: u64, agreed_price: u64, agreed_fee: u64,
+    agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32)]
+pub struct BuyProductLayaway<'info> {
+
+    #[account(
+        mut,
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PRODUCT_SNAPSHOT_METADATA_SIZE,
+        seeds = [
+            PRODUCT_SNAPSHOT_METADATA_BYTES,
+            product.key().as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()
+        ],
+        bump
+    )]
+    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PRODUCT_SIZE + product.data.len(),
+        seeds=[PRODUCT_SNAPSHOT_BYTES, product_snapshot_metadata.key().as_ref()],
+        bump
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata.key().as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()],
+        bump
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = purchase_ticket,
+    )]
+    pub purchase_ticket_payment: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = product.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub purchase_ticket_payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, purchase_ticket_payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account(owner=system_program.key())]
+    pub buy_for: AccountInfo<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::BUY_PRODUCT) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump=program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = fee_account,
+    )]
+    pub fee_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK:
+    #[account(address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    /// CHECK: fee tier of the product's store, or of its creator for lone products. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, product.fee_tier_entity().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PriceFeed. the system program when the product isn't priced off a feed
+    #[account(address = product.price_feed)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: the product's Store, for the arbiter recorded on the ticket. the system program for lone products
+    #[account(address = product.store)]
+    pub store: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PayInstallment<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = ticket.bump)]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket,
+        address = ticket.payment
+    )]
+    pub ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(address = ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ForfeitLayaway<'info> {
+
+    #[account(
+        mut,
+        address = ticket.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.dispute == Pubkey::default() @ ErrorCode::TicketIsDisputed,
+        bump = ticket.bump)]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket,
+        address = ticket.payment
+    )]
+    pub ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(address = ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: we good
+    #[account(address = ticket.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the ticket's PayoutSplit. the system program when the forfeit goes to pay_to
+    #[account(address = ticket.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket_authority,
+    )]
+    pub refund_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: owner of the ticket, refunded what wasn't forfeited
+    #[account(address = ticket.authority)]
+    pub ticket_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Subscribe<'info> {
+
+    #[account(
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + SUBSCRIPTION_SIZE,
+        seeds = [SUBSCRIPTION_BYTES, product.key().as_ref(), subscriber.key().as_ref()],
+        bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = subscriber,
+    )]
+    pub subscriber_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = product.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: we good
+    #[account(address = product.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the product's PayoutSplit. the system program when the product pays out to pay_to
+    #[account(address = product.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::BUY_PRODUCT) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump=program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = fee_account,
+    )]
+    pub fee_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK:
+    #[account(address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    /// CHECK: fee tier of the product's store, or of its creator for lone products. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, product.fee_tier_entity().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PriceFeed. the system program when the product isn't priced off a feed
+    #[account(address = product.price_feed)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RenewSubscription<'info> {
+
+    #[account(
+        mut,
+        seeds = [SUBSCRIPTION_BYTES, subscription.product.as_ref(), subscription.subscriber.as_ref()],
+        constraint = subscription.version == SUBSCRIPTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = subscription.bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        address = subscription.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        address = subscription.payment_token_account
+    )]
+    pub subscriber_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = subscription.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: we good
+    #[account(address = subscription.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the subscription's PayoutSplit. the system program when the subscription pays out to pay_to
+    #[account(address = subscription.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::BUY_PRODUCT) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump=program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = fee_account,
+    )]
+    pub fee_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK:
+    #[account(address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    /// CHECK: fee tier of the product's store, or of its creator for lone products. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, product.fee_tier_entity().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+
+    #[account(
+        mut,
+        seeds = [SUBSCRIPTION_BYTES, subscription.product.as_ref(), subscription.subscriber.as_ref()],
+        constraint = subscription.version == SUBSCRIPTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = subscription.bump,
+        constraint = subscription.subscriber == authority.key() || product.is_authorized(&authority.key) @ ErrorCode::IncorrectAuthority
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        address = subscription.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        address = subscription.payment_token_account
+    )]
+    pub subscriber_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16)]
+pub struct CreateAuction<'info> {
+
+    #[account(
+        mut,
+        constraint = product.is_authorized(&authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = product.bump
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AUCTION_SIZE,
+        seeds = [AUCTION_BYTES, product.key().as_ref()],
+        bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PRODUCT_SNAPSHOT_METADATA_SIZE,
+        seeds = [
+            PRODUCT_SNAPSHOT_METADATA_BYTES,
+            product.key().as_ref(),
+            authority.key().as_ref(),
+            &nonce.to_be_bytes()
+        ],
+        bump
+    )]
+    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PRODUCT_SIZE + product.data.len(),
+        seeds=[PRODUCT_SNAPSHOT_BYTES, product_snapshot_metadata.key().as_ref()],
+        bump
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = payment_mint,
+        associated_token::authority = auction
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = product.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::AUCTION) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    /// CHECK: fee tier of the product's store, or of its creator for lone products. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, product.fee_tier_entity().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+
+    #[account(
+        mut,
+        seeds = [AUCTION_BYTES, auction.product.as_ref()],
+        constraint = auction.version == AUCTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        address = auction.escrow
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = auction.payment_mint,
+        token::authority = bidder,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: a token account of the bidder being outbid, checked in the instruction. any writable account on the first bid
+    #[account(mut)]
+    pub previous_bidder_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::AUCTION) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    pub bidder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [AUCTION_BYTES, auction.product.as_ref()],
+        constraint = auction.version == AUCTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = auction.highest_bidder != Pubkey::default() @ ErrorCode::AuctionHasNoBids,
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        address = auction.escrow
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: created the auction, gets the rent back
+    #[account(mut, address = auction.authority)]
+    pub authority: AccountInfo<'info>,
+
+    #[account(address = auction.product_snapshot_metadata)]
+    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+
+    #[account(address = auction.product_snapshot)]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    /// CHECK: the product's Store, for the arbiter recorded on the ticket. the system program for lone products
+    #[account(address = product_snapshot.store)]
+    pub store: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata.key().as_ref(),
+            auction.highest_bidder.as_ref(),
+            &auction.nonce.to_be_bytes()],
+        bump
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = purchase_ticket
+    )]
+    pub purchase_ticket_payment: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = auction.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = fee_account,
+    )]
+    pub fee_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK:
+    #[account(address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = product_snapshot.pay_to,
+    )]
+    pub pay_to_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [AUCTION_BYTES, auction.product.as_ref()],
+        constraint = auction.version == AUCTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = auction.highest_bidder == Pubkey::default() @ ErrorCode::AuctionHasBids,
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        address = auction.escrow
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        close = authority,
+        address = auction.product_snapshot_metadata
+    )]
+    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+
+    #[account(
+        mut,
+        close = authority,
+        address = auction.product_snapshot
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        address = auction.product,
+        constraint = product.is_authorized(&authority.key),
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+        token::authority = authority,
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStoreFeeTier<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FEE_TIER_SIZE,
+        seeds = [FEE_TIER_BYTES, store.key().as_ref()],
+        bump
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    #[account(
+        mut,
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=store.bump
+    )]
+    pub store: Box<Account<'info, Store>>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCreatorFeeTier<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FEE_TIER_SIZE,
+        seeds = [FEE_TIER_BYTES, creator.key().as_ref()],
+        bump
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    /// CHECK: creator of lone products the tier applies to
+    pub creator: AccountInfo<'info>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeTier<'info> {
+    #[account(
+        mut,
+        seeds = [FEE_TIER_BYTES, fee_tier.entity.as_ref()],
+        constraint = fee_tier.version == FEE_TIER_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = fee_tier.bump
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    #[account(
+        constraint = program_metadata.is_admin(&authority.key),
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStoreTicketTaker<'info> {
+
+    #[account(
+        init,
+        payer=store_authority,
+        space = 8 + TICKET_TAKER_SIZE,
+        seeds = [STORE_TAKER_BYTES, store.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    pub ticket_taker: Account<'info, TicketTaker>,
+
+    ///CHECK: any account with a signer can be authorized by the store_authority
+    pub taker: AccountInfo<'info>,
+
+    #[account(
+        constraint = store.is_authorized(&store_authority.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=store.bump
+    )]
+    pub store: Account<'info, Store>,
+
+    #[account(mut)]
+    pub store_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProductTicketTaker<'info> {
+
+    #[account(
+        init,
+        payer=product_authority,
+        space = 8 + TICKET_TAKER_SIZE,
+        seeds = [PRODUCT_TAKER_BYTES, product.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    pub ticket_taker: Account<'info, TicketTaker>,
+
+    ///CHECK: any account with a signer can be authorized by the store_authority
+    pub taker: AccountInfo<'info>,
+
+    #[account(
+        constraint = product.is_authorized(&product_authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(mut)]
+    pub product_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStoreReferral<'info> {
+
+    #[account(
+        init,
+        payer=store_authority,
+        space = 8 + REFERRAL_SIZE,
+        seeds = [REFERRAL_BYTES, store.key().as_ref(), referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    ///CHECK: any wallet can be a referrer
+    pub referrer: AccountInfo<'info>,
+
+    #[account(
+        constraint = store.is_authorized(&store_authority.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=store.bump
+    )]
+    pub store: Account<'info, Store>,
+
+    #[account(mut)]
+    pub store_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProductReferral<'info> {
+
+    #[account(
+        init,
+        payer=product_authority,
+        space = 8 + REFERRAL_SIZE,
+        seeds = [REFERRAL_BYTES, product.key().as_ref(), referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    ///CHECK: any wallet can be a referrer
+    pub referrer: AccountInfo<'info>,
+
+    #[account(
+        constraint = product.is_authorized(&product_authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(mut)]
+    pub product_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStoreReferral<'info> {
+
+    #[account(
+        mut,
+        close = store_authority,
+        seeds = [REFERRAL_BYTES, store.key().as_ref(), referral.referrer.as_ref()],
+        constraint = referral.entity_type == EntityType::STORE @ ErrorCode::InvalidReferral,
+        bump = referral.bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(
+        constraint = store.is_authorized(&store_authority.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=store.bump
+    )]
+    pub store: Account<'info, Store>,
+
+    #[account(mut)]
+    pub store_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProductReferral<'info> {
+
+    #[account(
+        mut,
+        close = product_authority,
+        seeds = [REFERRAL_BYTES, product.key().as_ref(), referral.referrer.as_ref()],
+        constraint = referral.entity_type == EntityType::PRODUCT @ ErrorCode::InvalidReferral,
+        bump = referral.bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(
+        constraint = product.is_authorized(&product_authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(mut)]
+    pub product_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct CreateStoreCoupon<'info> {
+
+    #[account(
+        init,
+        payer=store_authority,
+        space = 8 + COUPON_SIZE,
+        seeds = [COUPON_BYTES, store.key().as_ref(), code.as_bytes()],
+        bump
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    #[account(
+        constraint = store.is_authorized(&store_authority.key),
+        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=store.bump
+    )]
+    pub store: Account<'info, Store>,
+
+    #[account(mut)]
+    pub store_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct CreateProductCoupon<'info> {
+
+    #[account(
+        init,
+        payer=product_authority,
+        space = 8 + COUPON_SIZE,
+        seeds = [COUPON_BYTES, product.key().as_ref(), code.as_bytes()],
+        bump
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    #[account(
+        constraint = product.is_authorized(&product_authority.key),
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump
+    )]
+    pub product: Account<'info, Product>,
+
+    #[account(mut)]
+    pub product_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCoupon<'info> {
+
+    #[account(
+        mut,
+        seeds = [COUPON_BYTES, coupon.entity.as_ref(), coupon.code.as_bytes()],
+        constraint = coupon.version == COUPON_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = coupon.bump,
+        constraint = coupon.authorized_by == authority.key() @ ErrorCode::IncorrectAuthority
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u32, quantity: u64, take_expiration_minutes: u32)]
+pub struct InitiateRedemption<'info> {
+
+   #[account(
+        init,
+        payer = purchase_ticket_authority,
+        space = 8 + REDEMPTION_SIZE,
+        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &nonce.to_be_bytes()],
+        bump
+    )]
+    pub redemption: Box<Account<'info, Redemption>>,
+
+    #[account(
+        mut,
+        seeds = 
+        [
+            PURCHASE_TICKET_BYTES,
+            purchase_ticket.product_snapshot_metadata.as_ref(),
+            purchase_ticket.buyer.as_ref(),
+            &purchase_ticket.nonce.to_be_bytes()
+        ], 
+        constraint = purchase_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = purchase_ticket.bump,
+        constraint = purchase_ticket.authority == purchase_ticket_authority.key()
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(mut)]
+    pub purchase_ticket_authority: Signer<'info>,
+
+    #[account(
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = purchase_ticket,
+    )]
+    pub purchase_ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(address = purchase_ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub purchase_ticket_payment_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, purchase_ticket_payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::INITIATE_REDEMPTION) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TakeRedemption<'info> {
+
+    #[account(
+        mut,
+        seeds = 
+        [
+            PURCHASE_TICKET_BYTES,
+            purchase_ticket.product_snapshot_metadata.as_ref(),
+            purchase_ticket.buyer.as_ref(),
+            &purchase_ticket.nonce.to_be_bytes()
+        ],
+        constraint = purchase_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = purchase_ticket.bump,
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &redemption.nonce.to_be_bytes()],
+        constraint = redemption.version == REDEMPTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = redemption.bump
+    )]
+    pub redemption: Box<Account<'info, Redemption>>,
+
+    #[account(
+        constraint = ticket_taker.entity == redemption.product || ticket_taker.entity == redemption.store @ ErrorCode::InvalidTicketTaker
+    )]
+    pub ticket_taker: Box<Account<'info, TicketTaker>>,
+
+    #[account(
+        mut,
+        constraint = ticket_taker_signer.key() == ticket_taker.taker @ ErrorCode::InvalidTicketTakerSigner
+    )]
+    pub ticket_taker_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = purchase_ticket,
+    )]
+    pub purchase_ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(address = purchase_ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub purchase_ticket_payment_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, purchase_ticket_payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_ticket_payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: we good
+    #[account(address = redemption.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the ticket's PayoutSplit. the system program when the ticket pays out to pay_to
+    #[account(address = purchase_ticket.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    /// CHECK: the ticket referrer's token account, validated when there's a commission. otherwise any writable account, like pay_to_token_account
+    #[account(mut)]
+    pub referrer_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::TAKE_REDEMPTION) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+    pub token_program: Program<'info, Token>,
+}
+
+
+#[derive(Accounts)]
+pub struct CancelRedemption<'info> {
+    #[account(
+        mut,
+        close = purchase_ticket_authority,
+        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &redemption.nonce.to_be_bytes()],
+        constraint = redemption.version == REDEMPTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = redemption.bump,
+        constraint = redemption.purchase_ticket == purchase_ticket.key()
+    )]
+    pub redemption: Box<Account<'info, Redemption>>,
+
+    #[account(
+        mut,
+        seeds = 
+        [
+            PURCHASE_TICKET_BYTES,
+            purchase_ticket.product_snapshot_metadata.as_ref(),
+            purchase_ticket.buyer.as_ref(),
+            &purchase_ticket.nonce.to_be_bytes()
+        ], 
+        constraint = purchase_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = purchase_ticket.dispute == Pubkey::default() @ ErrorCode::TicketIsDisputed,
+        bump = purchase_ticket.bump,
+        constraint = purchase_ticket.authority == purchase_ticket_authority.key()
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(mut)]
+    pub purchase_ticket_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16, quantity: u64)]
+pub struct TransferTicket<'info> {
+
+    #[account(
+        init,
+        payer = source_ticket_authority,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            source_ticket.product_snapshot_metadata.as_ref(),
+            source_ticket_authority.key().as_ref(),
+            &nonce.to_be_bytes()],
+        bump
+    )]
+    pub destination_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        init,
+        payer = source_ticket_authority,
+        associated_token::mint = payment_mint,
+        associated_token::authority = destination_ticket
+    )]
+    pub destination_ticket_payment: Account<'info, TokenAccount>,
+  
+    /// CHECK: owner of the new ticket
+    #[account()]
+    pub destination_ticket_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            source_ticket.product_snapshot_metadata.as_ref(),
+            source_ticket.buyer.as_ref(),
+            &source_ticket.nonce.to_be_bytes()],
+        constraint = source_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = source_ticket.bump,
+        constraint = source_ticket.authority == source_ticket_authority.key())]
+    pub source_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = source_ticket,
+        address = source_ticket.payment
+    )]
+    pub source_ticket_payment: Account<'info, TokenAccount>,
+  
+    #[account(address = source_ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        address = source_ticket.product_snapshot,
+        constraint = product_snapshot.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::TRANSFER_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub source_ticket_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTicket<'info> {
+
+    #[account(
+        mut,
+        address = ticket.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = ticket.bump,
+        constraint = ticket.authority == ticket_authority.key())]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket,
+        address = ticket.payment
+    )]
+    pub ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+    )]
+    pub payment_return: Account<'info, TokenAccount>,
+  
+    #[account(address = ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::CANCEL_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub ticket_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+
+#[derive(Accounts)]
+#[instruction(nonce: u16, quantity: u64, agreed_price: u64, agreed_fee: u64,
+    agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32)]
+pub struct BuyProductNative<'info> {
+
+    #[account(
+        mut,
+        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump=product.bump,
+        constraint = product.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PRODUCT_SNAPSHOT_METADATA_SIZE,
+        seeds = [
+            PRODUCT_SNAPSHOT_METADATA_BYTES,
+            product.key().as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()
+        ],
+        bump
+    )]
+    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PRODUCT_SIZE + product.data.len(),
+        seeds=[PRODUCT_SNAPSHOT_BYTES, product_snapshot_metadata.key().as_ref()],
+        bump
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            product_snapshot_metadata.key().as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()],
+        bump
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, NATIVE_PAYMENT_MINT.as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ ErrorCode::PaymentMintNotAccepted
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    /// CHECK: we good
+    #[account(mut, address = product.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: doesn't much need validation
+    #[account(owner=system_program.key())]
+    pub buy_for: AccountInfo<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::BUY_PRODUCT) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump=program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    /// CHECK:
+    #[account(mut, address = program_metadata.fee_account)]
+    pub fee_account: AccountInfo<'info>,
+
+    /// CHECK: fee tier of the product's store, or of its creator for lone products. may not exist
+    #[account(
+        seeds = [FEE_TIER_BYTES, product.fee_tier_entity().as_ref()],
+        bump
+    )]
+    pub fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PriceFeed. the system program when the product isn't priced off a feed
+    #[account(address = product.price_feed)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: the product's Store, for the arbiter recorded on the ticket. the system program for lone products
+    #[account(address = product.store)]
+    pub store: UncheckedAccount<'info>,
+
+    /// CHECK: the product's PayoutSplit. the system program when the product pays out to pay_to
+    #[account(address = product.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    /// CHECK: Referral of the product or its store. the system program stands in for no referral
+    pub referral: UncheckedAccount<'info>,
+
+    /// CHECK: referrer's wallet, validated when there's a referral. otherwise any writable account, like pay_to
+    #[account(mut)]
+    pub referrer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u32, quantity: u64, take_expiration_minutes: u32)]
+pub struct InitiateRedemptionNative<'info> {
+
+   #[account(
+        init,
+        payer = purchase_ticket_authority,
+        space = 8 + REDEMPTION_SIZE,
+        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &nonce.to_be_bytes()],
+        bump
+    )]
+    pub redemption: Box<Account<'info, Redemption>>,
+
+    #[account(
+        mut,
+        seeds =
+        [
+            PURCHASE_TICKET_BYTES,
+            purchase_ticket.product_snapshot_metadata.as_ref(),
+            purchase_ticket.buyer.as_ref(),
+            &purchase_ticket.nonce.to_be_bytes()
+        ],
+        constraint = purchase_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = purchase_ticket.bump,
+        constraint = purchase_ticket.authority == purchase_ticket_authority.key(),
+        constraint = purchase_ticket.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(mut)]
+    pub purchase_ticket_authority: Signer<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::INITIATE_REDEMPTION) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TakeRedemptionNative<'info> {
+
+    #[account(
+        mut,
+        seeds =
+        [
+            PURCHASE_TICKET_BYTES,
+            purchase_ticket.product_snapshot_metadata.as_ref(),
+            purchase_ticket.buyer.as_ref(),
+            &purchase_ticket.nonce.to_be_bytes()
+        ],
+        constraint = purchase_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = purchase_ticket.bump,
+        constraint = purchase_ticket.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch
+    )]
+    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &redemption.nonce.to_be_bytes()],
+        constraint = redemption.version == REDEMPTION_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = redemption.bump
+    )]
+    pub redemption: Box<Account<'info, Redemption>>,
+
+    #[account(
+        constraint = ticket_taker.entity == redemption.product || ticket_taker.entity == redemption.store @ ErrorCode::InvalidTicketTaker
+    )]
+    pub ticket_taker: Box<Account<'info, TicketTaker>>,
+
+    #[account(
+        mut,
+        constraint = ticket_taker_signer.key() == ticket_taker.taker @ ErrorCode::InvalidTicketTakerSigner
+    )]
+    pub ticket_taker_signer: Signer<'info>,
+
+    /// CHECK: we good
+    #[account(mut, address = redemption.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the ticket's PayoutSplit. the system program when the ticket pays out to pay_to
+    #[account(address = purchase_ticket.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    /// CHECK: the ticket referrer's wallet, validated when there's a commission. otherwise any writable account, like pay_to
+    #[account(mut)]
+    pub referrer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::TAKE_REDEMPTION) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16, quantity: u64)]
+pub struct TransferTicketNative<'info> {
+
+    #[account(
+        init,
+        payer = source_ticket_authority,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            source_ticket.product_snapshot_metadata.as_ref(),
+            source_ticket_authority.key().as_ref(),
+            &nonce.to_be_bytes()],
+        bump
+    )]
+    pub destination_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    /// CHECK: owner of the new ticket
+    #[account()]
+    pub destination_ticket_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            source_ticket.product_snapshot_metadata.as_ref(),
+            source_ticket.buyer.as_ref(),
+            &source_ticket.nonce.to_be_bytes()],
+        constraint = source_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = source_ticket.bump,
+        constraint = source_ticket.authority == source_ticket_authority.key(),
+        constraint = source_ticket.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch)]
+    pub source_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        address = source_ticket.product_snapshot,
+        constraint = product_snapshot.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::TRANSFER_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub source_ticket_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTicketNative<'info> {
+
+    #[account(
+        mut,
+        address = ticket.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = ticket.bump,
+        constraint = ticket.authority == ticket_authority.key(),
+        constraint = ticket.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch)]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    /// CHECK: any account the ticket authority wants the refund sent to
+    #[account(mut)]
+    pub payment_return: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::CANCEL_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub ticket_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ListTicket<'info> {
+
+    #[account(
+        init,
+        payer = ticket_authority,
+        space = 8 + TICKET_LISTING_SIZE,
+        seeds = [TICKET_LISTING_BYTES, ticket.key().as_ref()],
+        bump
+    )]
+    pub listing: Box<Account<'info, TicketListing>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.dispute == Pubkey::default() @ ErrorCode::TicketIsDisputed,
+        constraint = ticket.amount_due == 0 @ ErrorCode::TicketIsNotPaidInFull,
+        bump = ticket.bump,
+        constraint = ticket.authority == ticket_authority.key())]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        address = ticket.product_snapshot,
+        constraint = product_snapshot.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    /// listings are paid in the ticket's payment mint. native tickets can't be listed
+    #[account(address = ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::RESELL_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub ticket_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u16, quantity: u64, agreed_price: u64)]
+pub struct BuyListedTicket<'info> {
+
+    #[account(
+        mut,
+        seeds = [TICKET_LISTING_BYTES, source_ticket.key().as_ref()],
+        constraint = listing.version == TICKET_LISTING_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = listing.bump
+    )]
+    pub listing: Box<Account<'info, TicketListing>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PURCHASE_TICKET_SIZE,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            source_ticket.product_snapshot_metadata.as_ref(),
+            buyer.key().as_ref(),
+            &nonce.to_be_bytes()],
+        bump
+    )]
+    pub destination_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = destination_ticket
+    )]
+    pub destination_ticket_payment: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            source_ticket.product_snapshot_metadata.as_ref(),
+            source_ticket.buyer.as_ref(),
+            &source_ticket.nonce.to_be_bytes()],
+        constraint = source_ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = source_ticket.dispute == Pubkey::default() @ ErrorCode::TicketIsDisputed,
+        bump = source_ticket.bump)]
+    pub source_ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = source_ticket,
+        address = source_ticket.payment
+    )]
+    pub source_ticket_payment: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        address = source_ticket.product_snapshot,
+        constraint = product_snapshot.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(address = source_ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [ACCEPTED_MINT_BYTES, payment_mint.key().as_ref()],
+        constraint = accepted_mint.version == ACCEPTED_MINT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Box<Account<'info, AcceptedMint>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: receives the listing's rent once it sells out
+    #[account(mut, address = listing.seller)]
+    pub seller: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: we good
+    #[account(address = source_ticket.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the ticket's PayoutSplit. the system program when the royalty goes to pay_to
+    #[account(address = source_ticket.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::RESELL_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct DelistTicket<'info> {
+
+    #[account(
+        mut,
+        seeds = [TICKET_LISTING_BYTES, ticket.key().as_ref()],
+        constraint = listing.version == TICKET_LISTING_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = listing.bump,
+        constraint = listing.seller == seller.key(),
+        close = seller
+    )]
+    pub listing: Box<Account<'info, TicketListing>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = ticket.bump)]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateGift<'info> {
+
+    #[account(
+        init,
+        payer = giver,
+        space = 8 + GIFT_SIZE,
+        seeds = [GIFT_BYTES, ticket.key().as_ref()],
+        bump
+    )]
+    pub gift: Box<Account<'info, Gift>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.listed_quantity == 0 && ticket.pending_redemption == 0 @ ErrorCode::InvalidGift,
+        bump = ticket.bump,
+        constraint = ticket.authority == giver.key())]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(address = ticket.product_snapshot)]
+    pub product_snapshot: Box<Account<'info, Product>>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::TRANSFER_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub giver: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimGift<'info> {
+
+    #[account(
+        mut,
+        close = giver,
+        seeds = [GIFT_BYTES, gift.ticket.as_ref()],
+        constraint = gift.version == GIFT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = gift.bump
+    )]
+    pub gift: Box<Account<'info, Gift>>,
+
+    #[account(
+        mut,
+        address = gift.ticket,
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    /// CHECK: paid the gift's rent, gets it back
+    #[account(mut, address = gift.giver)]
+    pub giver: AccountInfo<'info>,
+
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimGift<'info> {
+
+    #[account(
+        mut,
+        close = giver,
+        seeds = [GIFT_BYTES, gift.ticket.as_ref()],
+        constraint = gift.version == GIFT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = gift.bump,
+        constraint = gift.giver == giver.key()
+    )]
+    pub gift: Box<Account<'info, Gift>>,
+
+    #[account(
+        mut,
+        address = ticket.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        address = gift.ticket,
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket,
+        address = ticket.payment
+    )]
+    pub ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+    )]
+    pub payment_return: Account<'info, TokenAccount>,
+
+    #[account(address = ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::CANCEL_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub giver: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimGiftNative<'info> {
+
+    #[account(
+        mut,
+        close = giver,
+        seeds = [GIFT_BYTES, gift.ticket.as_ref()],
+        constraint = gift.version == GIFT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = gift.bump,
+        constraint = gift.giver == giver.key()
+    )]
+    pub gift: Box<Account<'info, Gift>>,
+
+    #[account(
+        mut,
+        address = ticket.product,
+        constraint = product.version == PRODUCT_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub product: Box<Account<'info, Product>>,
+
+    #[account(
+        mut,
+        address = gift.ticket,
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch)]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    /// CHECK: any account the giver wants the refund sent to
+    #[account(mut)]
+    pub payment_return: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = !program_metadata.is_paused(PauseFlag::CANCEL_TICKET) @ ErrorCode::InstructionIsPaused,
+        seeds = [PROGRAM_METADATA_BYTES],
+        bump = program_metadata.bump
+    )]
+    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+
+    #[account(mut)]
+    pub giver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+
+    #[account(
+        init,
+        payer = opened_by,
+        space = 8 + DISPUTE_SIZE,
+        seeds = [DISPUTE_BYTES, ticket.key().as_ref()],
+        bump
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_TICKET_BYTES,
+            ticket.product_snapshot_metadata.as_ref(),
+            ticket.buyer.as_ref(),
+            &ticket.nonce.to_be_bytes()],
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.amount_due == 0 @ ErrorCode::TicketIsNotPaidInFull,
+        bump = ticket.bump,
+        constraint = ticket.authority == opened_by.key() || store.is_authorized(&opened_by.key) @ ErrorCode::IncorrectAuthority)]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        address = ticket.store,
+        constraint = store.version == STORE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+    )]
+    pub store: Box<Account<'info, Store>>,
+
+    /// CHECK: waiting Redemption of the ticket being disputed, checked in the instruction. the system program for the ticket's remaining quantity
+    pub redemption: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub opened_by: Signer<'info>,
     pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+
+    #[account(
+        mut,
+        close = opened_by,
+        seeds = [DISPUTE_BYTES, dispute.ticket.as_ref()],
+        constraint = dispute.version == DISPUTE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = dispute.bump,
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    /// CHECK: paid the dispute's rent, gets it back
+    #[account(mut, address = dispute.opened_by)]
+    pub opened_by: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        address = dispute.ticket,
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.arbiter == arbiter.key() @ ErrorCode::IncorrectAuthority
+    )]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket,
+        address = ticket.payment
+    )]
+    pub ticket_payment: Account<'info, TokenAccount>,
+
+    #[account(address = ticket.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = ticket_authority,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: owner of the ticket, gets the buyer's share
+    #[account(address = ticket.authority)]
+    pub ticket_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = pay_to,
+    )]
+    pub pay_to_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: we good
+    #[account(address = ticket.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the ticket's PayoutSplit. the system program when the ticket pays out to pay_to
+    #[account(address = ticket.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    /// CHECK: the ticket referrer's token account, validated when there's a commission. otherwise any writable account, like pay_to_token_account
+    #[account(mut)]
+    pub referrer_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: the disputed Redemption. the system program when the ticket's remaining quantity was disputed
+    #[account(mut, address = dispute.redemption)]
+    pub redemption: UncheckedAccount<'info>,
+
+    pub arbiter: Signer<'info>,
     pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-#[derive(Accounts)]
-#[instruction(status: u8, price: u64, inventory: u64, redemption_type: u8,
-    expiration_timestamp: i64, expiration_minutes_after_purchase: u32, expiration_minutes_after_redemption: u32,
-    name: String, description: String, data: Vec<u8>)]
-pub struct UpdateProduct<'info> {
-    #[account(mut,
-        constraint = product.is_authorized(&authority.key),
-        realloc = 8 + PRODUCT_SIZE + data.len(),
-        realloc::payer = authority,
-        realloc::zero = true,
-        //has_one=authority,
-        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],  
-        bump = product.bump
-    )]
-    pub product: Box<Account<'info, Product>>,
+#[derive(Accounts)]
+pub struct ResolveDisputeNative<'info> {
+
+    #[account(
+        mut,
+        close = opened_by,
+        seeds = [DISPUTE_BYTES, dispute.ticket.as_ref()],
+        constraint = dispute.version == DISPUTE_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        bump = dispute.bump,
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    /// CHECK: paid the dispute's rent, gets it back
+    #[account(mut, address = dispute.opened_by)]
+    pub opened_by: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        address = dispute.ticket,
+        constraint = ticket.version == PURCHASE_TICKET_VERSION @ ErrorCode::UnsupportedAccountVersion,
+        constraint = ticket.payment_mint == NATIVE_PAYMENT_MINT @ ErrorCode::PaymentMintMismatch,
+        constraint = ticket.arbiter == arbiter.key() @ ErrorCode::IncorrectAuthority
+    )]
+    pub ticket: Box<Account<'info, PurchaseTicket>>,
+
+    /// CHECK: owner of the ticket, gets the buyer's share
+    #[account(mut, address = ticket.authority)]
+    pub ticket_authority: AccountInfo<'info>,
+
+    /// CHECK: we good
+    #[account(mut, address = ticket.pay_to)]
+    pub pay_to: AccountInfo<'info>,
+
+    /// CHECK: the ticket's PayoutSplit. the system program when the ticket pays out to pay_to
+    #[account(address = ticket.payout_split)]
+    pub payout_split: UncheckedAccount<'info>,
+
+    /// CHECK: the ticket referrer's wallet, validated when there's a commission. otherwise any writable account, like pay_to
+    #[account(mut)]
+    pub referrer: UncheckedAccount<'info>,
+
+    /// CHECK: the disputed Redemption. the system program when the ticket's remaining quantity was disputed
+    #[account(mut, address = dispute.redemption)]
+    pub redemption: UncheckedAccount<'info>,
+
+    pub arbiter: Signer<'info>,
+}
+
+const PROGRAM_METADATA_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 +32 + FEE_SCHEDULE_SIZE + 32 + 32 + 32 + FEE_SCHEDULE_SIZE + 8 + 8
+    + (4 + FEE_RECIPIENT_SIZE * MAX_FEE_RECIPIENTS);
+/// bytes added going from each version to the next
+const PROGRAM_METADATA_VERSION_GROWTH: [usize; PROGRAM_VERSION as usize] = [
+    (FEE_SCHEDULE_SIZE - 8) + 32 + 32 + 32 + FEE_SCHEDULE_SIZE + 8 + 8, //0 -> 1
+    4 + FEE_RECIPIENT_SIZE * MAX_FEE_RECIPIENTS, //1 -> 2; fee_recipients
+];
+#[account]
+pub struct ProgramMetadata {
+    pub bump: u8, //1;
+    pub initialized: bool, //1;
+    pub version: u8, //1;
+    pub creator: Pubkey, //32;
+    pub authority: Pubkey, //32;
+    pub secondary_authority: Pubkey, //32;
+    pub fee_account: Pubkey, //32;
+    pub fee: FeeSchedule, //FEE_SCHEDULE_SIZE;
+    pub pending_authority: Pubkey, //32; proposed authority, has to accept before taking over
+    pub pending_secondary_authority: Pubkey, //32; becomes the secondary_authority when the pending authority accepts
+    pub multisig: Pubkey, //32; AdminMultisig that administers the program. default to all zeros for none
+    pub pending_fee: FeeSchedule, //FEE_SCHEDULE_SIZE; replaces fee once pending_fee_timestamp is reached
+    pub pending_fee_timestamp: i64, //8; unix timestamp pending_fee takes effect. 0 for none
+    pub paused: u64, //8; PauseFlag bitmask of paused instructions
+    pub fee_recipients: Vec<FeeRecipient>, //4+FEE_RECIPIENT_SIZE*MAX_FEE_RECIPIENTS; split of this account's vaults. fee_account is this account while collecting for them
+}
+
+/// fee charged on a purchase: flat_fee plus basis_points of the total purchase price, clamped to min_fee and max_fee.
+/// flat only, percentage only and hybrid fees are all expressed by leaving the unused parts at zero.
+pub const FEE_SCHEDULE_SIZE: usize = 8 + 2 + 8 + 8;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct FeeSchedule {
+    pub flat_fee: u64, //8;
+    pub basis_points: u16, //2; of the total purchase price
+    pub min_fee: u64, //8; floor
+    pub max_fee: u64, //8; ceiling. 0 for none
+}
+
+pub const RESALE_POLICY_SIZE: usize = 1 + 4 + 4;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ResalePolicy {
+    pub transfers_disabled: bool, //1; blocks transfers and listings. default allows them
+    pub max_resale_basis_points: u32, //4; listing price cap as basis points of PurchaseTicket.price, ie 15000 for 1.5x. 0 for no cap
+    pub cooldown_minutes: u32, //4; minutes after a ticket is bought or received before it can move again
+}
+
+pub const DUTCH_AUCTION_SIZE: usize = 8 + 8 + 8 + 8;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct DutchAuction {
+    pub start_price: u64, //8; 0 when the product isn't auctioned. same units as price
+    pub floor_price: u64, //8; the price stops declining here
+    pub start_timestamp: i64, //8; unix timestamp the price starts declining from. purchases before it are rejected
+    pub decay_per_minute: u64, //8;
+}
+
+pub const LAYAWAY_POLICY_SIZE: usize = 4 + 2 + 2;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LayawayPolicy {
+    pub payment_window_minutes: u32, //4; time after purchase to pay in full. 0 when layaway isn't offered
+    pub min_deposit_basis_points: u16, //2; of the total purchase price
+    pub forfeit_basis_points: u16, //2; of what was paid in, kept by the merchant if the deadline passes. the rest is refunded
+}
+
+pub const MAX_FEE_RECIPIENTS: usize = 5;
+pub const FEE_RECIPIENT_SIZE: usize = 32 + 2;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct FeeRecipient {
+    pub wallet: Pubkey, //32; owner of the token accounts fees are distributed to
+    pub basis_points: u16, //2; share of collected fees
+}
+
+pub const MAX_ADMIN_SIGNERS: usize = 10;
+const ADMIN_MULTISIG_SIZE: usize = 1 + 1 + 1 + 4 + 8 + (4 + 32 * MAX_ADMIN_SIGNERS);
+#[account]
+pub struct AdminMultisig {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub threshold: u8, //1; approvals needed to execute a proposal
+    pub signers_version: u32, //4; bumped when the signer set changes, so outstanding proposals go stale
+    pub proposal_count: u64, //8; used as the seed of the next proposal
+    pub signers: Vec<Pubkey>, //4+32*MAX_ADMIN_SIGNERS;
+}
+
+const ADMIN_PROPOSAL_SIZE: usize = 1 + 1 + 8 + 1 + 32 + 4 + 2 + 1 + 8 + 8 + 8 + 8 + FEE_SCHEDULE_SIZE + 32 + 32 + 32 + 1 + (4 + 32 * MAX_ADMIN_SIGNERS) + 8
+    + (4 + FEE_RECIPIENT_SIZE * MAX_FEE_RECIPIENTS);
+#[account]
+pub struct AdminProposal {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub id: u64, //8; proposal_count of the multisig when this was created
+    pub action: u8, //1; AdminAction
+    pub proposer: Pubkey, //32;
+    pub signers_version: u32, //4; signer set this proposal was created against
+    pub approvals: u16, //2; bitmask of approving signer indexes
+    pub executed: bool, //1;
+    pub created_slot: u64, //8;
+    pub created_timestamp: i64, //8;
+    pub executed_slot: u64, //8;
+    pub executed_timestamp: i64, //8;
+    pub fee: FeeSchedule, //FEE_SCHEDULE_SIZE; CHANGE_FEE
+    pub fee_account: Pubkey, //32; CHANGE_FEE_ACCOUNT
+    pub authority: Pubkey, //32; CHANGE_AUTHORITY
+    pub secondary_authority: Pubkey, //32; CHANGE_AUTHORITY
+    pub threshold: u8, //1; CHANGE_SIGNERS
+    pub signers: Vec<Pubkey>, //4+32*MAX_ADMIN_SIGNERS; CHANGE_SIGNERS
+    pub paused: u64, //8; SET_PAUSED
+    pub fee_recipients: Vec<FeeRecipient>, //4+FEE_RECIPIENT_SIZE*MAX_FEE_RECIPIENTS; SET_FEE_RECIPIENTS
+}
+
+
+pub const STORE_NAME_SIZE: usize = 100;
+pub const STORE_DESCRIPTION_SIZE: usize = 200;
+pub const STORE_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 + 2 + 8 + 8 + (4+STORE_NAME_SIZE) + (4+STORE_DESCRIPTION_SIZE) + 4 + 32 + 32 + 32;
+const STORE_VERSION_GROWTH: [usize; STORE_VERSION as usize] = [
+    32, //0 -> 1; fee_tier
+    32, //1 -> 2; payout_split
+    32, //2 -> 3; arbiter
+];
+
+#[account]
+pub struct Store{
+    pub bump: u8, //1; bump used for this PDA
+    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
+    pub status: u8, //1; can be used for various status: active, inactive, etc... better than a just an active/inactive boolean
+    pub creator: Pubkey, //32; used as part of PDA seed to make it harder for spamming store creation
+    pub authority: Pubkey, //32; authorized to make changes
+    pub secondary_authority: Pubkey, //32; burner wallet or delegation...
+    pub id: u16, //2; unique store id used as part of the PDA seed 
+    pub tag: u64, //8; used for misc. tagging for simplifying queries. bitmasking maybe?
+    pub product_count: u64, //8; tracks product count.
+    pub name: String, //4+100; eventually used for indexing and querying
+    pub description: String, //4+200; eventually used for indexing and querying    
+    pub data: Vec<u8>, //4+ whatever size they pay for
+    pub fee_tier: Pubkey, //32; FeeTier negotiated for this store. default to all zeros for none
+    pub payout_split: Pubkey, //32; PayoutSplit copied to new store products. default to all zeros for none
+    pub arbiter: Pubkey, //32; rules on disputes over the store's tickets. default to all zeros for none
+    
+    /* UNDECIDED STUFF */
+    //pub category: u64, //64; bitwise AND masked identifier  
+    //pub verified_by: Option<Pubkey>, //1 + 32; If verified, verified by who? store this outside this account
+    //pub rating: u8, //8; current rating; store this outside of the account
+    //pub version: u8, //1; version changes in data structure to provide decision making on serialization/deserialization
+}
+
+
+
+//pub const PRODUCT_SKU_SIZE: usize = 4+25;
+pub const PRODUCT_NAME_SIZE: usize = 100;
+pub const PRODUCT_DESCRIPTION_SIZE: usize = 200;
+pub const PRODUCT_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 + 4 + 8 + 1 + 32 + 32 + 32 + 8 + 8 + 1 + 8 + 4 + 4 + (4+PRODUCT_NAME_SIZE) + (4+PRODUCT_DESCRIPTION_SIZE) + 4 + 32 + 32 + 32 + 2 + RESALE_POLICY_SIZE + LAYAWAY_POLICY_SIZE + 4 + DUTCH_AUCTION_SIZE;
+const PRODUCT_VERSION_GROWTH: [usize; PRODUCT_VERSION as usize] = [
+    32, //0 -> 1; payment_mint
+    32, //1 -> 2; price_feed
+    32, //2 -> 3; payout_split
+    2, //3 -> 4; royalty_basis_points
+    RESALE_POLICY_SIZE, //4 -> 5; resale_policy
+    LAYAWAY_POLICY_SIZE, //5 -> 6; layaway_policy
+    4, //6 -> 7; subscription_period_minutes
+    DUTCH_AUCTION_SIZE, //7 -> 8; dutch_auction
+];
+
+#[account]
+pub struct Product{
+    pub bump: u8, //1; bump used for this PDA
+    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
+    pub status: u8, //1; can be used for various status: active, inactive, etc... better than a just an active/inactive boolean
+    pub creator: Pubkey, //32; used as part of PDA seed to make it harder for spamming store creation
+    pub authority: Pubkey, //32; authorized to make changes
+    pub secondary_authority: Pubkey, //32; burner wallet or delegation...
+    pub id: u32, //4; unique store id used as part of the PDA seed 
+    pub tag: u64, //8; used for misc. tagging for simplifying queries. bitmasking maybe?    
+    pub is_snapshot: bool, //1;
+    pub usable_snapshot: Pubkey, //32; default to all zeros for none. Option<> doesn't work. On buys, if it's none, take a snapshot, otherwise use the existing snapshot
+    //pub mint: Pubkey, //32; used to mint a product token to the buyer
+    pub pay_to: Pubkey, //32; where payments should be sent. can be different than the authority
+    pub store: Pubkey, //32; address of store PDA. maybe set to default Pubkey and save a byte?
+    pub price: u64, //8; price of product. needs to be stable, but stablecoins can die, so most likely lamports since they'll be around as long as Solana is
+    pub inventory: u64, //8;
+    pub redemption_type: u8, //1;
+    pub expiration_timestamp: i64, //8    
+    pub expiration_minutes_after_purchase: u32, //4;
+    pub expiration_minutes_after_redemption: u32, //4;    
+    pub name: String, //4+100; product name
+    pub description: String, //4+200; product description
+    pub data: Vec<u8>, //4+ whatever size they pay for
+    pub payment_mint: Pubkey, //32; mint the price is in. has to be an AcceptedMint when the product is created
+    pub price_feed: Pubkey, //32; default for none. when set, price is in PRICE_QUOTE_DECIMALS and converted to payment_mint at purchase
+    pub payout_split: Pubkey, //32; default for none. when set, revenue goes to the PayoutSplit recipients instead of pay_to
+    pub royalty_basis_points: u16, //2; merchant's cut of resales through ticket listings
+    pub resale_policy: ResalePolicy, //RESALE_POLICY_SIZE; enforced from the snapshot whenever a ticket moves
+    pub layaway_policy: LayawayPolicy, //LAYAWAY_POLICY_SIZE; terms for buy_product_layaway
+    pub subscription_period_minutes: u32, //4; 0 for none. when set, the product is sold through subscribe and renewed every period
+    pub dutch_auction: DutchAuction, //DUTCH_AUCTION_SIZE; declining price schedule used instead of price when start_price is set
+
+    /* UNDECIDED STUFF */
+    //pub sku: String, //4+25; This gives the ability to relate the product to a sku in some catalog - not used natively. most won't have this, store it in another account if needed
+}
+
+///help protect the seller and buyer from product changes
+/// make sure to update sizes and fields as product changes. maybe just serialize and compress the whole thing?
+/// it's more searchable when not compressed
+
+const PRODUCT_SNAPSHOT_METADATA_SIZE: usize = 1 + 1 + 8 + 8 + 32 + 32 + 2;
+#[account]
+pub struct ProductSnapshotMetadata {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unixtimestamp
+    pub product: Pubkey, //32; 
+    pub product_snapshot: Pubkey, //32; pointer to snapshot
+    pub nonce: u16, //2;
+}
+
+const PURCHASE_TICKET_SIZE: usize = 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 2 + 8 + 32 + 32 + 8 + 4 + 8 + 32 + 32 + 2 + 8 + 32 + 32 + 8 + 8 + 8 + 2 + 32 + 8 + 32 + 32;
+const PURCHASE_TICKET_VERSION_GROWTH: [usize; PURCHASE_TICKET_VERSION as usize] = [
+    8, //0 -> 1; fee
+    32 + 32 + 2 + 8, //1 -> 2; referral
+    32, //2 -> 3; payment_mint
+    32, //3 -> 4; payout_split
+    8, //4 -> 5; listed_quantity
+    8 + 8 + 2, //5 -> 6; layaway
+    32 + 8, //6 -> 7; coupon
+    32 + 32, //7 -> 8; dispute and arbiter
+];
+#[account]
+#[derive(Default)]
+pub struct PurchaseTicket {
+    pub bump: u8, //1;
+    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unixtimestamp
+    pub product: Pubkey, //32;
+    pub product_snapshot_metadata: Pubkey, //32;
+    pub product_snapshot: Pubkey, //32;
+    pub buyer: Pubkey, //32;
+    pub pay_to: Pubkey, //32;
+    pub authority: Pubkey, //32;
+    pub remaining_quantity: u64, //8;
+    pub redeemed: u64, //8;
+    pub pending_redemption: u64, //8;
+    pub nonce: u16, //2;
+    pub price:u64, //8;
+    pub store: Pubkey, //32;
+    pub payment: Pubkey, //32;
+    pub expiration: i64, //8;
+    pub expiration_minutes_after_redemption: u32, //4;
+    pub fee: u64, //8; fee charged on the purchase. not part of the escrowed payment and not refunded on cancel
+    pub referral: Pubkey, //32; Referral used on the purchase. default for none
+    pub referrer: Pubkey, //32; wallet earning the commission
+    pub referral_basis_points: u16, //2; commission rate, taken out of the merchant's share
+    pub referral_commission: u64, //8; commission paid out so far
+    pub payment_mint: Pubkey, //32; mint the ticket was paid in, from the product
+    pub payout_split: Pubkey, //32; PayoutSplit from the product at purchase. default for pay_to
+    pub listed_quantity: u64, //8; moved out of remaining_quantity while listed for resale
+    pub amount_due: u64, //8; layaway balance still owed into the escrow. 0 once paid in full
+    pub payment_deadline: i64, //8; unix timestamp the layaway balance is due by. 0 for none
+    pub forfeit_basis_points: u16, //2; of what was paid in, kept by the merchant if the deadline passes
+    pub coupon: Pubkey, //32; Coupon applied to the purchase. default for none
+    pub discount: u64, //8; taken off each unit by the coupon. price is after the discount
+    pub dispute: Pubkey, //32; open Dispute freezing the ticket. default for none
+    pub arbiter: Pubkey, //32; the store's arbiter when the ticket was bought, rules on its disputes. default for none
+}
+
+const FEE_TIER_SIZE: usize = 1 + 1 + 1 + 32 + 32 + FEE_SCHEDULE_SIZE + 8 + 8 + 8 + FEE_SCHEDULE_SIZE + 8 + 8;
+const FEE_TIER_VERSION_GROWTH: [usize; FEE_TIER_VERSION as usize] = [
+    FEE_SCHEDULE_SIZE + 8 + 8, //0 -> 1; pending fee and expiration
+];
+#[account]
+pub struct FeeTier {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub entity_type: u8, //1; store or creator?
+    pub entity: Pubkey, //32; reference to store or creator
+    pub authorized_by: Pubkey, //32; program authority that set the tier
+    pub fee: FeeSchedule, //FEE_SCHEDULE_SIZE; used instead of the program fee
+    pub expiration_timestamp: i64, //8; unix timestamp. 0 for never
+    pub slot: u64, //8; last changed
+    pub timestamp: i64, //8; last changed, unix timestamp
+    pub pending_fee: FeeSchedule, //FEE_SCHEDULE_SIZE; takes effect at pending_fee_timestamp
+    pub pending_expiration_timestamp: i64, //8; takes effect with pending_fee
+    pub pending_fee_timestamp: i64, //8; unix timestamp. 0 when nothing is scheduled
+}
+
+const ACCEPTED_MINT_SIZE: usize = 1 + 1 + 32 + 1 + 1 + FEE_SCHEDULE_SIZE + 1 + FEE_SCHEDULE_SIZE + 8 + 8 + 8;
+#[account]
+pub struct AcceptedMint {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub mint: Pubkey, //32; token mint accepted as payment
+    pub enabled: bool, //1; only checked on purchases, so existing tickets can always be redeemed or refunded
+    pub fee_override: bool, //1; use fee instead of the program fee
+    pub fee: FeeSchedule, //FEE_SCHEDULE_SIZE;
+    pub pending_fee_override: bool, //1;
+    pub pending_fee: FeeSchedule, //FEE_SCHEDULE_SIZE; takes effect at pending_fee_timestamp
+    pub pending_fee_timestamp: i64, //8; unix timestamp. 0 when nothing is scheduled
+    pub slot: u64, //8; last changed
+    pub timestamp: i64, //8; last changed, unix timestamp
+}
+
+pub const MAX_PAYOUT_RECIPIENTS: usize = 8;
+pub const PAYOUT_RECIPIENT_SIZE: usize = 32 + 2;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PayoutRecipient {
+    pub wallet: Pubkey, //32; owner of the token accounts revenue is paid to
+    pub basis_points: u16, //2; share of revenue
+}
+
+const PAYOUT_SPLIT_SIZE: usize = 1 + 1 + 32 + 4 + (4 + PAYOUT_RECIPIENT_SIZE * MAX_PAYOUT_RECIPIENTS);
+#[account]
+pub struct PayoutSplit {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub creator: Pubkey, //32; used as part of the PDA seed
+    pub id: u32, //4; used as part of the PDA seed
+    pub recipients: Vec<PayoutRecipient>, //4+PAYOUT_RECIPIENT_SIZE*MAX_PAYOUT_RECIPIENTS;
+}
+
+const TICKET_LISTING_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 8 + 8 + 8 + 8;
+#[account]
+pub struct TicketListing {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub ticket: Pubkey, //32; used as the PDA seed. one listing per ticket
+    pub seller: Pubkey, //32; ticket authority when listed. paid for the sale
+    pub payment_mint: Pubkey, //32; the ticket's payment mint
+    pub price: u64, //8; asking price per unit
+    pub quantity: u64, //8; still for sale
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unix timestamp
+}
+
+const GIFT_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 8 + 8 + 8;
+#[account]
+pub struct Gift {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub ticket: Pubkey, //32; used as the PDA seed. the gift is the ticket's authority until claimed or reclaimed
+    pub giver: Pubkey, //32; ticket authority when gifted. can reclaim after the deadline
+    pub secret_hash: [u8; 32], //32; sha256 of the secret the recipient claims with, followed by the recipient's key
+    pub claim_deadline: i64, //8; unix timestamp
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unix timestamp
+}
+
+const DISPUTE_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8;
+#[account]
+pub struct Dispute {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub ticket: Pubkey, //32; used as the PDA seed. one open dispute per ticket
+    pub redemption: Pubkey, //32; disputed Redemption. default when the ticket's remaining quantity is disputed
+    pub store: Pubkey, //32;
+    pub arbiter: Pubkey, //32; the store's arbiter when the dispute was opened
+    pub opened_by: Pubkey, //32; ticket authority or store authority. paid the rent
+    pub quantity: u64, //8; disputed units
+    pub price: u64, //8; per unit, from the ticket
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unix timestamp
+}
+
+const SUBSCRIPTION_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 4 + 8 + 1 + 4 + 8 + 8;
+#[account]
+pub struct Subscription {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub product: Pubkey, //32; used as part of the PDA seed
+    pub subscriber: Pubkey, //32; used as part of the PDA seed
+    pub payment_token_account: Pubkey, //32; subscriber's token account the subscription is a delegate on
+    pub payment_mint: Pubkey, //32;
+    pub pay_to: Pubkey, //32; from the product at subscribe
+    pub payout_split: Pubkey, //32; from the product at subscribe. default for none
+    pub price: u64, //8; per period, locked in at subscribe
+    pub agreed_fee: u64, //8; most the subscriber agreed to pay in fees per period
+    pub period_minutes: u32, //4;
+    pub expiration: i64, //8; unix timestamp the subscription is paid through
+    pub status: u8, //1; SubscriptionStatus
+    pub renewals: u32, //4;
+    pub slot: u64, //8; last changed
+    pub timestamp: i64, //8; last changed, unix timestamp
+}
+
+const AUCTION_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 32 + 32 + 32 + 2 + FEE_SCHEDULE_SIZE + 8 + 8 + 8 + 8 + 8 + 32 + 4 + 8 + 8;
+#[account]
+pub struct Auction {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub product: Pubkey, //32; used as the PDA seed. one auction per product at a time
+    pub authority: Pubkey, //32; created the auction, paid the rent
+    pub payment_mint: Pubkey, //32; the product's payment mint
+    pub escrow: Pubkey, //32; auction's associated token account, holds the highest bid
+    pub product_snapshot_metadata: Pubkey, //32; made at create_auction, seeded with the auction's creator and nonce
+    pub product_snapshot: Pubkey, //32; terms the winner's ticket is issued under
+    pub nonce: u16, //2; of the snapshot, reused for the winner's ticket
+    pub fee_schedule: FeeSchedule, //FEE_SCHEDULE_SIZE; in effect at create_auction, charged on the winning bid at settlement
+    pub min_bid: u64, //8; opening bid
+    pub min_increment: u64, //8; each bid has to beat the highest by at least this
+    pub start_timestamp: i64, //8; unix timestamp
+    pub end_timestamp: i64, //8; unix timestamp
+    pub highest_bid: u64, //8;
+    pub highest_bidder: Pubkey, //32; default until the first bid
+    pub bids: u32, //4;
+    pub slot: u64, //8; last changed
+    pub timestamp: i64, //8; last changed, unix timestamp
+}
+
+pub const MAX_ORDER_ITEMS: usize = 8;
+pub const CHECKOUT_ITEM_ACCOUNTS: usize = 9;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CheckoutItem {
+    pub quantity: u64, //8;
+    pub agreed_price: u64, //8; most the buyer will pay per unit
+    pub agreed_expiration_timestamp: i64, //8; has to match the product's, same as buy_product
+    pub agreed_expiration_minutes_after_purchase: u32, //4;
+    pub agreed_expiration_minutes_after_redemption: u32, //4;
+}
+
+const ORDER_SIZE: usize = 1 + 1 + 32 + 32 + 32 + 2 + 8 + 8 + (4 + 32 * MAX_ORDER_ITEMS) + 8 + 8;
+#[account]
+pub struct Order {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub buyer: Pubkey, //32; used as part of the PDA seed
+    pub store: Pubkey, //32; every product in the order is from this store
+    pub payment_mint: Pubkey, //32;
+    pub nonce: u16, //2; used as part of the PDA seed
+    pub total: u64, //8; paid for the items, not counting the fee
+    pub fee: u64, //8; charged once on the total
+    pub tickets: Vec<Pubkey>, //4 + 32 * MAX_ORDER_ITEMS; a PurchaseTicket per item, in item order
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unix timestamp
+}
+
+pub const MAX_PRICE_TIERS: usize = 8;
+pub const PRICE_TIER_SIZE: usize = 8 + 2;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PriceTier {
+    pub min_quantity: u64, //8; purchases of at least this many units get the tier
+    pub basis_points: u16, //2; off the unit price
+}
+
+const PRICE_TIERS_SIZE: usize = 1 + 1 + 32 + (4 + PRICE_TIER_SIZE * MAX_PRICE_TIERS);
+/// kept out of Product so PRODUCT_SIZE doesn't change. snapshots get their own copy at purchase
+#[account]
+pub struct PriceTiers {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub product: Pubkey, //32; product or snapshot, used as the PDA seed
+    pub tiers: Vec<PriceTier>, //4+PRICE_TIER_SIZE*MAX_PRICE_TIERS; in increasing min_quantity
+}
+
+pub const COUPON_CODE_SIZE: usize = 32; //max seed length
+const COUPON_SIZE: usize = 1 + 1 + 1 + 32 + 32 + (4 + COUPON_CODE_SIZE) + 1 + 8 + 8 + 4 + 4 + 4 + 1 + 8 + 8;
+#[account]
+pub struct Coupon {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub entity_type: u8, //1; store or product?
+    pub entity: Pubkey, //32; the coupon only applies to this product, or to the products of this store
+    pub authorized_by: Pubkey, //32; store or product authority that created the coupon
+    pub code: String, //4+32; used as part of the PDA seed
+    pub discount_type: u8, //1; DiscountType
+    pub discount: u64, //8; basis points off for percentage coupons, payment mint units off each unit for fixed coupons
+    pub expiration_timestamp: i64, //8; unix timestamp. 0 for never
+    pub max_uses: u32, //4; 0 for unlimited
+    pub uses: u32, //4; purchases the coupon was applied to
+    pub max_uses_per_buyer: u32, //4; 0 for unlimited. tracked in CouponUsage accounts
+    pub enabled: bool, //1;
+    pub slot: u64, //8; last changed
+    pub timestamp: i64, //8; last changed, unix timestamp
+}
+
+const COUPON_USAGE_SIZE: usize = 1 + 1 + 32 + 32 + 4;
+#[account]
+pub struct CouponUsage {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub coupon: Pubkey, //32; used as part of the PDA seed
+    pub buyer: Pubkey, //32; used as part of the PDA seed
+    pub uses: u32, //4;
+}
+
+const PRICE_FEED_SIZE: usize = 1 + 1 + 32 + 32 + 8 + 4 + 8 + 4 + 8 + 8;
+#[account]
+pub struct PriceFeed {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub mint: Pubkey, //32; token being priced
+    pub updater: Pubkey, //32; only signer allowed to publish prices
+    pub price: i64, //8; quote currency per whole token is price * 10^exponent
+    pub exponent: i32, //4;
+    pub publish_time: i64, //8; unix timestamp the updater observed the price
+    pub max_age_seconds: u32, //4; purchases are rejected once the price is older than this
+    pub slot: u64, //8; last updated
+    pub timestamp: i64, //8; last updated, unix timestamp
+}
+
+const REFERRAL_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 + 2 + 8 + 8;
+#[account]
+pub struct Referral {
+    pub bump: u8, //1;
+    pub version: u8, //1;
+    pub entity_type: u8, //1; store or product?
+    pub entity: Pubkey, //32; reference to store or product
+    pub referrer: Pubkey, //32; wallet earning the commission
+    pub authorized_by: Pubkey, //32; store or product authority that registered the referrer
+    pub basis_points: u16, //2; commission rate on the purchase price
+    pub slot: u64, //8;
+    pub timestamp: i64, //8; unix timestamp
+}
+
+const TICKET_TAKER_SIZE: usize = 1 + 1 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8;
+#[account]
+pub struct TicketTaker {
+    pub bump: u8, //1;
+    pub version: u8, //1,
+    pub taker: Pubkey, //32,
+    pub entity_type: u8, //1, store or product?
+    pub entity: Pubkey, //32, reference to store or product
+    pub authorized_by: Pubkey, //32, account that authorized this ticket taker
+    pub enabled_slot: u64, //8,
+    pub enabled_timestamp: i64, //8, unix timestamp
+    pub disabled_slot: u64, //8,
+    pub disabled_timestamp: i64, //8, unix timestamp
+}
+
+const REDEMPTION_SIZE: usize = 1 + 1 + 4 + 8 + 8 + 8 + 8 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 32 + 1 + 8 + 8;
+const REDEMPTION_VERSION_GROWTH: [usize; REDEMPTION_VERSION as usize] = [];
+#[account]
+pub struct Redemption {
+    pub bump: u8, //1;    
+    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
+    pub nonce: u32, //4;
+    pub init_slot: u64, //8;
+    pub close_slot: u64, //8;
+    pub init_timestamp: i64, //8; unixtimestamp
+    pub close_timestamp: i64, //8;
+    pub store: Pubkey, //32;
+    pub product: Pubkey, //32;
+    pub product_snapshot_metadata: Pubkey, //32;
+    pub product_snapshot: Pubkey, //32;
+    pub purchase_ticket: Pubkey, //32;
+    pub purchase_ticket_signer: Pubkey, //32;
+    pub buyer: Pubkey, //32;
+    pub pay_to: Pubkey, //32;
+    //pub purchase_ticket_remaining_quantity: u64, //8;
+    pub redeem_quantity: u64, //8;
+    pub price:u64, //8;
+    pub ticket_taker: Pubkey, //32;
+    pub ticket_taker_signer: Pubkey, //32;
+    pub status: u8, //1;
+    pub take_expiration: i64, //8;
+    pub usage_expiration: i64, //8;
+}
+
+
+/// Used as a bitwise mask for the product category
+/// this isn't a scalable way to store all the product categories - revisit this
+#[repr(u64)]
+pub enum ProductCategory{
+    None = 0,
+    Media = 1,
+    Merch = 2,
+    Event = 4,
+    Social = 8,
+}
+
+/// Used as a bitwise mask for the product type
+/// this isn't a scalable way to store all the product types - revisit this
+#[repr(u64)]
+pub enum ProductType {
+    None = 0,
+    Music = 1,
+    Video = 2,
+    Clothing = 4,
+    Concert = 8,
+    Conference = 16,
+    Award = 32,
+}
+
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("AlreadyInitialized")]
+    AlreadyInitialized,
+    #[msg("PublicKeyMismatch")]
+    PublicKeyMismatch,
+    #[msg("InvalidMintAuthority")]
+    InvalidMintAuthority,
+    #[msg("UninitializedAccount")]
+    UninitializedAccount,
+    #[msg("IncorrectAuthority")]
+    IncorrectAuthority,
+    #[msg("NotMutable")]
+    NotMutable,
+    #[msg("authority Doesn't Exist")]
+    AuthorityDoesntExist,
+    #[msg("pay_to Doesn't Exist")]
+    PayToDoesntExist,
+    #[msg("price is greater than payment")]
+    PriceIsGreaterThanPayment,
+    #[msg("name is too long")]
+    NameIsTooLong,
+    #[msg("description is too long")]
+    DescriptionIsTooLong,
+    #[msg("not enough inventory")]
+    NotEnoughInventory,
+    #[msg("insufficient funds")]
+    InsufficientFunds,
+    #[msg("unable to deduct from buyer account")]
+    UnableToDeductFromBuyerAccount,
+    #[msg("unable to add to pay_to account")]
+    UnableToAddToPayToAccount,
+    #[msg("product is not active")]
+    ProductIsNotActive,
+    #[msg("store is not active")]
+    StoreIsNotActive,
+    #[msg("modifying snapshots is not allowed")]
+    UnableToModifySnapshot,
+    #[msg("purchasing snapshots is not allowed")]
+    UnableToPurchaseSnapshot,
+    #[msg("invalid token account")]
+    InvalidTokenAccount,
+    #[msg("signer isn't the ticket taker")]
+    InvalidTicketTakerSigner,
+    #[msg("ticket taker isn't authorized to take the ticket")]
+    InvalidTicketTaker,
+    #[msg("not enough redemptions remain")]
+    InsufficientRemainingRedemptions,
+    #[msg("quantity must be greater than zero")]
+    QuantityMustBeGreaterThanZero,
+    #[msg("insufficient quantity")]
+    InsufficientQuantity,
+    #[msg("incorrect seed")]
+    IncorrectSeed,
+    #[msg("already processed")]
+    AlreadyProcessed,
+    #[msg("already redeemed")]
+    AlreadyRedeemed,
+    #[msg("product is expired")]
+    ProductIsExpired,
+    #[msg("ticket is expired")]
+    TicketIsExpired,
+    #[msg("agreed expiration doesn't match the currently configured product setting")]
+    AgreedExpirationDoesntMatch,
+    #[msg("agreed expiration after purchase doesn't match the currently configured product setting")]
+    AgreedExpirationAfterPurchaseDoesntMatch,
+    #[msg("agreed expiration after redemption doesn't match the currently configured product setting")]
+    AgreedExpirationAfterRedemptionDoesntMatch,
+    #[msg("the ability to take has expired")]
+    TakeIsExpired,
+    #[msg("there is no pending authority change")]
+    NoPendingAuthorityChange,
+    #[msg("signer isn't part of the admin multisig")]
+    NotAMultisigSigner,
+    #[msg("invalid multisig threshold or signer set")]
+    InvalidMultisigSigners,
+    #[msg("invalid admin action")]
+    InvalidAdminAction,
+    #[msg("already approved")]
+    AlreadyApproved,
+    #[msg("not enough approvals")]
+    NotEnoughApprovals,
+    #[msg("the multisig signer set changed after this proposal was created")]
+    StaleAdminProposal,
+    #[msg("fee is greater than the agreed fee")]
+    FeeIsGreaterThanAgreedFee,
+    #[msg("numerical overflow")]
+    NumericalOverflow,
+    #[msg("invalid fee schedule")]
+    InvalidFeeSchedule,
+    #[msg("instruction is paused")]
+    InstructionIsPaused,
+    #[msg("unsupported account version")]
+    UnsupportedAccountVersion,
+    #[msg("account is already at the current version")]
+    AccountIsCurrentVersion,
+    #[msg("fee recipients have to be unique and their basis points have to add up to 10000")]
+    InvalidFeeRecipients,
+    #[msg("fees aren't being collected for distribution")]
+    NoFeeRecipients,
+    #[msg("token accounts don't match the fee recipients")]
+    FeeRecipientMismatch,
+    #[msg("referral commission has to be between 1 and 10000 basis points")]
+    InvalidReferralRate,
+    #[msg("referral isn't for this product or its store")]
+    InvalidReferral,
+    #[msg("token account doesn't belong to the referrer")]
+    InvalidReferrerTokenAccount,
+    #[msg("payment mint isn't accepted")]
+    PaymentMintNotAccepted,
+    #[msg("payment mint doesn't match the product's")]
+    PaymentMintMismatch,
+    #[msg("account isn't the referrer's wallet")]
+    InvalidReferrer,
+    #[msg("price feed has to have a positive price and an exponent within 18")]
+    InvalidPriceFeed,
+    #[msg("price feed publish time can't go backwards or be in the future")]
+    InvalidPriceFeedPublishTime,
+    #[msg("signer isn't the price feed updater")]
+    InvalidPriceFeedUpdater,
+    #[msg("price feed is stale")]
+    StalePriceFeed,
+    #[msg("payout recipients have to be unique, at most 8, and their basis points have to add up to 10000")]
+    InvalidPayoutRecipients,
+    #[msg("accounts don't match the payout split recipients")]
+    PayoutRecipientMismatch,
+    #[msg("royalty can't be more than 10000 basis points")]
+    InvalidRoyaltyRate,
+    #[msg("the product doesn't allow ticket transfers")]
+    TransfersDisabled,
+    #[msg("ticket can't move until the resale cooldown passes")]
+    ResaleCooldown,
+    #[msg("asking price is over the product's resale cap")]
+    ResalePriceTooHigh,
+    #[msg("basis points can't be more than 10000")]
+    InvalidLayawayPolicy,
+    #[msg("the product isn't offered on layaway")]
+    LayawayNotOffered,
+    #[msg("deposit is less than the product's minimum")]
+    InsufficientDeposit,
+    #[msg("ticket isn't paid in full")]
+    TicketIsNotPaidInFull,
+    #[msg("ticket is already paid in full")]
+    TicketIsPaidInFull,
+    #[msg("installment is more than the amount due")]
+    InstallmentIsMoreThanAmountDue,
+    #[msg("payment deadline has passed")]
+    PaymentDeadlinePassed,
+    #[msg("payment deadline hasn't passed")]
+    PaymentDeadlineNotPassed,
+    #[msg("the product isn't sold as a subscription")]
+    NotASubscriptionProduct,
+    #[msg("subscription is cancelled")]
+    SubscriptionIsCancelled,
+    #[msg("subscription period hasn't elapsed")]
+    SubscriptionPeriodHasntElapsed,
+    #[msg("subscription products are bought with subscribe")]
+    SubscriptionProductMustBeSubscribedTo,
+    #[msg("invalid coupon")]
+    InvalidCoupon,
+    #[msg("coupon is expired or disabled")]
+    CouponIsExpired,
+    #[msg("coupon has no uses left")]
+    CouponIsUsedUp,
+    #[msg("price tiers have to be in increasing min quantity above 1, at most 8, with at most 10000 basis points off")]
+    InvalidPriceTiers,
+    #[msg("auction floor price can't be more than the start price")]
+    InvalidDutchAuction,
+    #[msg("auction hasn't started")]
+    AuctionHasntStarted,
+    #[msg("auction has to end after it starts, with a minimum bid, on a product with delayed redemption")]
+    InvalidAuction,
+    #[msg("auction has ended")]
+    AuctionHasEnded,
+    #[msg("auction hasn't ended")]
+    AuctionHasntEnded,
+    #[msg("bid is less than the minimum")]
+    BidTooLow,
+    #[msg("auction has no bids")]
+    AuctionHasNoBids,
+    #[msg("auction has bids")]
+    AuctionHasBids,
+    #[msg("checkout takes 1 to 8 distinct products from the store, with 6 accounts each")]
+    InvalidCheckout,
+    #[msg("gifts need a claim window and a ticket with remaining quantity that isn't listed or being redeemed")]
+    InvalidGift,
+    #[msg("secret doesn't match the gift")]
+    InvalidGiftSecret,
+    #[msg("gift claim deadline hasn't passed")]
+    GiftClaimDeadlineNotPassed,
+    #[msg("disputes need a store arbiter and a waiting redemption or remaining quantity on the ticket")]
+    InvalidDispute,
+    #[msg("ticket is frozen by an open dispute")]
+    TicketIsDisputed,
+    #[msg("buyer's share can't be more than 10000 basis points")]
+    InvalidDisputeRuling,
+    #[msg("deposit is more than the purchase price")]
+    DepositIsMoreThanPrice,
+    #[msg("subscription is still active")]
+    SubscriptionIsActive,
+    #[msg("arbiter can't be the store's authority or the ticket's pay_to")]
+    InvalidArbiter,
+    #[msg("ticket is gifted until it's claimed or reclaimed")]
+    TicketIsGifted,
+}
+
+impl ProgramMetadata {
+    /// day to day administration: enabling and disabling accepted mints.
+    /// Under a multisig the authorities are whoever its last CHANGE_AUTHORITY proposal installed.
+    fn is_authorized(&self, key: &Pubkey) -> bool {
+        *key == self.authority || *key == self.secondary_authority
+    }
+
+    /// program settings, and what buyers pay with and how much: fee tiers, accepted mints and their fees, and price feeds.
+    /// Disabled once administration is handed to a multisig.
+    fn is_admin(&self, key: &Pubkey) -> bool {
+        self.multisig == Pubkey::default() && self.is_authorized(key)
+    }
+
+    /// fee in effect at the given unix timestamp
+    fn fee_at(&self, timestamp: i64) -> FeeSchedule {
+        if self.pending_fee_timestamp > 0 && self.pending_fee_timestamp <= timestamp {
+            self.pending_fee
+        } else {
+            self.fee
+        }
+    }
+
+    fn is_paused(&self, flag: u64) -> bool {
+        self.paused & flag != 0
+    }
+
+    fn schedule_fee(&mut self, fee: FeeSchedule, timestamp: i64) {
+        self.fee = self.fee_at(timestamp);
+        self.pending_fee = fee;
+        self.pending_fee_timestamp = timestamp + FEE_CHANGE_DELAY_SECONDS;
+    }
+}
+
+impl FeeTier {
+    /// fee in effect at the given unix timestamp. None once the tier in effect has expired
+    fn fee_at(&self, timestamp: i64) -> Option<FeeSchedule> {
+        let (fee, expiration_timestamp) = if self.pending_fee_timestamp > 0 && self.pending_fee_timestamp <= timestamp {
+            (self.pending_fee, self.pending_expiration_timestamp)
+        } else {
+            (self.fee, self.expiration_timestamp)
+        };
+
+        if expiration_timestamp > 0 && expiration_timestamp < timestamp {
+            None
+        } else {
+            Some(fee)
+        }
+    }
+
+    fn schedule_fee(&mut self, fee: FeeSchedule, expiration_timestamp: i64, timestamp: i64) {
+        if self.pending_fee_timestamp > 0 && self.pending_fee_timestamp <= timestamp {
+            self.fee = self.pending_fee;
+            self.expiration_timestamp = self.pending_expiration_timestamp;
+        }
+        self.pending_fee = fee;
+        self.pending_expiration_timestamp = expiration_timestamp;
+        self.pending_fee_timestamp = timestamp + FEE_CHANGE_DELAY_SECONDS;
+    }
+}
+
+impl AcceptedMint {
+    /// fee override in effect at the given unix timestamp
+    fn fee_at(&self, timestamp: i64) -> Option<FeeSchedule> {
+        let (fee_override, fee) = if self.pending_fee_timestamp > 0 && self.pending_fee_timestamp <= timestamp {
+            (self.pending_fee_override, self.pending_fee)
+        } else {
+            (self.fee_override, self.fee)
+        };
+
+        if fee_override {
+            Some(fee)
+        } else {
+            None
+        }
+    }
+
+    fn schedule_fee(&mut self, fee_override: bool, fee: FeeSchedule, timestamp: i64) {
+        if self.pending_fee_timestamp > 0 && self.pending_fee_timestamp <= timestamp {
+            self.fee_override = self.pending_fee_override;
+            self.fee = self.pending_fee;
+        }
+        self.pending_fee_override = fee_override;
+        self.pending_fee = fee;
+        self.pending_fee_timestamp = timestamp + FEE_CHANGE_DELAY_SECONDS;
+    }
+}
+
+impl PriceFeed {
+    /// token units for an amount quoted in PRICE_QUOTE_DECIMALS, rounded up in the merchant's favor
+    fn quote_to_token_amount(&self, quote_amount: u64, decimals: u8) -> Result<u64> {
+        if self.price <= 0 || self.exponent.unsigned_abs() > MAX_PRICE_FEED_EXPONENT {
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+
+        let token_scale = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::NumericalOverflow)?;
+        let exponent_scale = 10u128.pow(self.exponent.unsigned_abs());
+        let mut numerator = u128::from(quote_amount).checked_mul(token_scale).ok_or(ErrorCode::NumericalOverflow)?;
+        let mut denominator = (self.price as u128) * 10u128.pow(PRICE_QUOTE_DECIMALS);
+
+        if self.exponent < 0 {
+            numerator = numerator.checked_mul(exponent_scale).ok_or(ErrorCode::NumericalOverflow)?;
+        } else {
+            denominator = denominator.checked_mul(exponent_scale).ok_or(ErrorCode::NumericalOverflow)?;
+        }
+
+        let amount = (numerator + denominator - 1) / denominator;
+        u64::try_from(amount).map_err(|_| ErrorCode::NumericalOverflow.into())
+    }
+}
+
+impl FeeSchedule {
+    fn validate(&self) -> Result<()> {
+        if u64::from(self.basis_points) > BASIS_POINTS_DIVISOR || (self.max_fee > 0 && self.min_fee > self.max_fee) {
+            return Err(ErrorCode::InvalidFeeSchedule.into());
+        }
+
+        Ok(())
+    }
+
+    fn calculate(&self, total_purchase_price: u64) -> Result<u64> {
+        let percentage_fee = apply_basis_points(total_purchase_price, self.basis_points)?;
+        let mut fee = self.flat_fee.checked_add(percentage_fee).ok_or(ErrorCode::NumericalOverflow)?;
+
+        if fee < self.min_fee {
+            fee = self.min_fee;
+        }
+
+        if self.max_fee > 0 && fee > self.max_fee {
+            fee = self.max_fee;
+        }
+
+        Ok(fee)
+    }
+}
+
+fn apply_basis_points(amount: u64, basis_points: u16) -> Result<u64> {
+    let result = u128::from(amount)
+        .checked_mul(u128::from(basis_points))
+        .ok_or(ErrorCode::NumericalOverflow)?
+        / u128::from(BASIS_POINTS_DIVISOR);
+
+    u64::try_from(result).map_err(|_| ErrorCode::NumericalOverflow.into())
+}
+
+impl DutchAuction {
+    fn price_at(&self, timestamp: i64) -> Result<u64> {
+        if timestamp < self.start_timestamp {
+            return Err(ErrorCode::AuctionHasntStarted.into());
+        }
+
+        let elapsed_minutes = u128::try_from((timestamp - self.start_timestamp) / 60).map_err(|_| ErrorCode::NumericalOverflow)?;
+        let decay = elapsed_minutes.saturating_mul(u128::from(self.decay_per_minute));
+        let price = u128::from(self.start_price).saturating_sub(decay).max(u128::from(self.floor_price));
+
+        u64::try_from(price).map_err(|_| ErrorCode::NumericalOverflow.into())
+    }
+}
+
+impl PriceTiers {
+    /// unit price after the largest tier the quantity reaches
+    fn unit_price(&self, list_price: u64, quantity: u64) -> Result<u64> {
+        let basis_points = self.tiers.iter()
+            .filter(|tier| quantity >= tier.min_quantity)
+            .last()
+            .map_or(0, |tier| tier.basis_points);
+
+        Ok(list_price - apply_basis_points(list_price, basis_points)?)
+    }
+}
+
+impl ResalePolicy {
+    fn check_transfer(&self, ticket: &PurchaseTicket, timestamp: i64) -> Result<()> {
+        if self.transfers_disabled {
+            return Err(ErrorCode::TransfersDisabled.into());
+        }
+
+        if self.cooldown_minutes > 0 && timestamp < ticket.timestamp + i64::from(self.cooldown_minutes) * 60 {
+            return Err(ErrorCode::ResaleCooldown.into());
+        }
+
+        Ok(())
+    }
+
+    fn check_resale_price(&self, ticket: &PurchaseTicket, price: u64) -> Result<()> {
+        if self.max_resale_basis_points == 0 {
+            return Ok(());
+        }
+
+        let max_price = u128::from(ticket.price) * u128::from(self.max_resale_basis_points) / u128::from(BASIS_POINTS_DIVISOR);
+        if u128::from(price) > max_price {
+            return Err(ErrorCode::ResalePriceTooHigh.into());
+        }
+
+        Ok(())
+    }
+}
+
+impl AdminMultisig {
+    fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers.iter().position(|s| s == key)
+    }
+}
+
+fn validate_fee_recipients(fee_recipients: &[FeeRecipient]) -> Result<()> {
+    if fee_recipients.is_empty() || fee_recipients.len() > MAX_FEE_RECIPIENTS {
+        return Err(ErrorCode::InvalidFeeRecipients.into());
+    }
+
+    let mut total_basis_points: u64 = 0;
+    for (i, fee_recipient) in fee_recipients.iter().enumerate() {
+        if fee_recipient.basis_points == 0 || fee_recipients[i + 1..].iter().any(|r| r.wallet == fee_recipient.wallet) {
+            return Err(ErrorCode::InvalidFeeRecipients.into());
+        }
+        total_basis_points += u64::from(fee_recipient.basis_points);
+    }
+
+    if total_basis_points != BASIS_POINTS_DIVISOR {
+        return Err(ErrorCode::InvalidFeeRecipients.into());
+    }
+
+    Ok(())
+}
+
+fn validate_payout_recipients(recipients: &[PayoutRecipient]) -> Result<()> {
+    if recipients.is_empty() || recipients.len() > MAX_PAYOUT_RECIPIENTS {
+        return Err(ErrorCode::InvalidPayoutRecipients.into());
+    }
+
+    let mut total_basis_points: u64 = 0;
+    for (i, recipient) in recipients.iter().enumerate() {
+        if recipient.basis_points == 0 || recipients[i + 1..].iter().any(|r| r.wallet == recipient.wallet) {
+            return Err(ErrorCode::InvalidPayoutRecipients.into());
+        }
+        total_basis_points += u64::from(recipient.basis_points);
+    }
+
+    if total_basis_points != BASIS_POINTS_DIVISOR {
+        return Err(ErrorCode::InvalidPayoutRecipients.into());
+    }
+
+    Ok(())
+}
+
+fn validate_admin_signers(threshold: u8, signers: &[Pubkey]) -> Result<()> {
+    if signers.is_empty() || signers.len() > MAX_ADMIN_SIGNERS || threshold == 0 || usize::from(threshold) > signers.len() {
+        return Err(ErrorCode::InvalidMultisigSigners.into());
+    }
+
+    for (i, signer) in signers.iter().enumerate() {
+        if signers[i + 1..].contains(signer) {
+            return Err(ErrorCode::InvalidMultisigSigners.into());
+        }
+    }
+
+    Ok(())
+}
+
+impl Store {
+    fn is_authorized(&self, key: &Pubkey) -> bool {
+        *key == self.authority || *key == self.secondary_authority
+    }
+}
+
+impl Product {
+    fn is_authorized(&self, key: &Pubkey) -> bool {
+        *key == self.authority || *key == self.secondary_authority
+    }
+
+    /// price before any price feed conversion. dutch auctioned products decline from their start price
+    fn price_at(&self, timestamp: i64) -> Result<u64> {
+        if self.dutch_auction.start_price == 0 {
+            return Ok(self.price);
+        }
+
+        self.dutch_auction.price_at(timestamp)
+    }
+
+    /// store products use the store's fee tier, lone products use the creator's
+    fn fee_tier_entity(&self) -> Pubkey {
+        if self.store == Pubkey::default() {
+            self.creator
+        } else {
+            self.store
+        }
+    }
+}
+
+impl PurchaseTicket {
+    /// disputed tickets are frozen, and layaway tickets can't be used until they're paid off
+    fn check_usable(&self) -> Result<()> {
+        if self.dispute != Pubkey::default() {
+            return Err(ErrorCode::TicketIsDisputed.into());
+        }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+        if self.amount_due > 0 {
+            return Err(ErrorCode::TicketIsNotPaidInFull.into());
+        }
+
+        Ok(())
+    }
+
+    fn is_expired(&self, timestamp: i64) -> bool {
+        self.expiration > 0 && self.expiration < timestamp
+    }
+
+    /// the fields a purchase takes from the product and its snapshot. price, payment, fee and quantities are left to the caller
+    fn init_purchase(&mut self, bump: u8, product: &Product, product_snapshot_metadata_key: Pubkey, product_snapshot_metadata: &ProductSnapshotMetadata,
+        buyer: Pubkey, authority: Pubkey, clock: &Clock) {
+
+        self.bump = bump;
+        self.version = PURCHASE_TICKET_VERSION;
+        self.slot = clock.slot;
+        self.timestamp = clock.unix_timestamp;
+        self.product = product_snapshot_metadata.product;
+        self.product_snapshot_metadata = product_snapshot_metadata_key;
+        self.product_snapshot = product_snapshot_metadata.product_snapshot;
+        self.buyer = buyer;
+        self.pay_to = product.pay_to;
+        self.authority = authority;
+        self.nonce = product_snapshot_metadata.nonce;
+        self.store = product.store;
+        self.expiration_minutes_after_redemption = product.expiration_minutes_after_redemption;
+        self.payment_mint = product.payment_mint;
+        self.payout_split = product.payout_split;
+        self.listed_quantity = 0;
+        self.amount_due = 0; //paid in full up front
+
+        if product.expiration_minutes_after_purchase > 0 {
+            self.expiration = clock.unix_timestamp + (i64::from(product.expiration_minutes_after_purchase) * 60);
+        }
+
+        if product.expiration_timestamp > 0 && (self.expiration == 0 || product.expiration_timestamp < self.expiration) {
+            self.expiration = product.expiration_timestamp;
+        }
+    }
+
+    /// commission on delayed redemptions is paid out as they're taken
+    fn set_referral(&mut self, referral_key: Pubkey, referral: &Option<Referral>) {
+        if let Some(referral) = referral {
+            self.referral = referral_key;
+            self.referrer = referral.referrer;
+            self.referral_basis_points = referral.basis_points;
+        }
+    }
+
+    /// uses up the disputed quantity, closing a disputed redemption as arbitrated, and unfreezes the ticket.
+    /// returns the buyer's share, the merchant's share after the referral commission, and the commission
+    fn resolve_dispute(&mut self, dispute: &Dispute, buyer_basis_points: u16, redemption_info: &AccountInfo, clock: &Clock) -> Result<(u64, u64, u64)> {
+        if u64::from(buyer_basis_points) > BASIS_POINTS_DIVISOR {
+            return Err(ErrorCode::InvalidDisputeRuling.into());
+        }
+
+        let amount = dispute.price.checked_mul(dispute.quantity).ok_or(ErrorCode::NumericalOverflow)?;
+        let buyer_share = apply_basis_points(amount, buyer_basis_points)?;
+        let merchant_share = amount - buyer_share;
+        let referral_commission = if self.referrer != Pubkey::default() {
+            apply_basis_points(merchant_share, self.referral_basis_points)?
+        } else {
+            0
+        };
+
+        if dispute.redemption == Pubkey::default() {
+            self.remaining_quantity -= dispute.quantity;
+        }
+        else {
+            let mut redemption = Account::<Redemption>::try_from(redemption_info)?;
+            redemption.status = RedemptionStatus::ARBITRATED;
+            redemption.close_slot = clock.slot;
+            redemption.close_timestamp = clock.unix_timestamp;
+            redemption.exit(&crate::ID)?;
+
+            self.pending_redemption -= dispute.quantity;
+        }
+
+        self.referral_commission += referral_commission;
+        self.dispute = Pubkey::default();
+
+        Ok((buyer_share, merchant_share - referral_commission, referral_commission))
+    }
+
+    /// takes quantity back off the ticket and into the product's inventory. returns the escrowed price to refund
+    fn cancel(&mut self, product: &mut Product, quantity: u64) -> Result<u64> {
+        self.check_usable()?;
+
+        if quantity > self.remaining_quantity {
+            return Err(ErrorCode::InsufficientQuantity.into());
+        }
+
+        self.remaining_quantity -= quantity;
+        product.inventory += quantity;
+
+        Ok(self.price * quantity)
+    }
 }
 
+impl Redemption {
+    /// moves quantity of the ticket into a waiting redemption. escrowed is what the ticket holds in payment
+    fn open(&mut self, purchase_ticket: &mut Account<PurchaseTicket>, purchase_ticket_signer: Pubkey, quantity: u64,
+        take_expiration_minutes: u32, escrowed: u64, clock: &Clock) -> Result<()> {
 
-#[derive(Accounts)]
-#[instruction(nonce: u16, quantity: u64, agreed_price: u64,
-    agreed_expiration_timestamp: i64, agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32)]
-pub struct BuyProduct<'info> {
+        purchase_ticket.check_usable()?;
 
-    #[account(
-        mut,
-        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()], 
-        bump=product.bump
-    )]
-    pub product: Box<Account<'info, Product>>,
+        if quantity == 0 {
+            return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
+        }
 
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + PRODUCT_SNAPSHOT_METADATA_SIZE,
-        seeds = [
-            PRODUCT_SNAPSHOT_METADATA_BYTES,
-            product.key().as_ref(),
-            buyer.key().as_ref(),
-            &nonce.to_be_bytes()
-        ],
-        bump
-    )]
-    pub product_snapshot_metadata: Box<Account<'info, ProductSnapshotMetadata>>,
+        if quantity > purchase_ticket.remaining_quantity {
+            return Err(ErrorCode::InsufficientRemainingRedemptions.into());
+        }
 
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + PRODUCT_SIZE + product.data.len(),
-        seeds=[PRODUCT_SNAPSHOT_BYTES, product_snapshot_metadata.key().as_ref()], 
-        bump
-    )]
-    pub product_snapshot: Box<Account<'info, Product>>,
+        if escrowed < purchase_ticket.price * quantity {
+            return Err(ErrorCode::InsufficientFunds.into());
+        }
 
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + PURCHASE_TICKET_SIZE,
-        //make nonce part of the seed, because in the future a new snapshot will only be created when a product changes
-        seeds = [
-            PURCHASE_TICKET_BYTES,
-            product_snapshot_metadata.key().as_ref(),
-            buyer.key().as_ref(),
-            &nonce.to_be_bytes()], 
-        bump
-    )]
-    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+        if purchase_ticket.is_expired(clock.unix_timestamp) {
+            return Err(ErrorCode::TicketIsExpired.into());
+        }
 
-    #[account(
-        mut,
-        token::mint = purchase_ticket_payment_mint,
-        token::authority = purchase_ticket,
-    )]
-    pub purchase_ticket_payment: Account<'info, TokenAccount>,
+        self.version = REDEMPTION_VERSION;
+        self.init_slot = clock.slot;
+        self.init_timestamp = clock.unix_timestamp;
+        self.close_slot = 0;
+        self.close_timestamp = 0;
+        self.store = purchase_ticket.store;
+        self.product = purchase_ticket.product;
+        self.product_snapshot_metadata = purchase_ticket.product_snapshot_metadata;
+        self.product_snapshot = purchase_ticket.product_snapshot;
+        self.purchase_ticket = purchase_ticket.key();
+        self.buyer = purchase_ticket.buyer;
+        self.pay_to = purchase_ticket.pay_to;
+        self.purchase_ticket_signer = purchase_ticket_signer;
+        self.redeem_quantity = quantity;
+        self.price = purchase_ticket.price;
+        self.status = RedemptionStatus::WAITING;
 
-    #[account(address = crate::payment_token::ID)]
-    pub purchase_ticket_payment_mint: Account<'info, Mint>,
+        if take_expiration_minutes > 0 {
+            self.take_expiration = clock.unix_timestamp + (i64::from(take_expiration_minutes) * 60);
+        }
 
-    #[account(
-        //init_if_needed,
-        //payer=buyer,
-        mut,
-        token::mint = purchase_ticket_payment_mint,
-        token::authority = pay_to,
-    )]
-    pub pay_to_token_account: Account<'info, TokenAccount>,
+        purchase_ticket.remaining_quantity -= quantity;
+        purchase_ticket.pending_redemption += quantity;
 
-    /// CHECK: we good
-    #[account(address = product.pay_to)]
-    pub pay_to: AccountInfo<'info>,
-   
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+        Ok(())
+    }
 
-    /// CHECK: doesn't much need validation  
-    #[account(owner=system_program.key())] 
-    pub buy_for: AccountInfo<'info>,
+    /// marks the redemption taken. returns the merchant's payment and the referrer's commission to release from escrow
+    fn take(&mut self, purchase_ticket: &mut PurchaseTicket, ticket_taker: Pubkey, ticket_taker_signer: Pubkey, clock: &Clock) -> Result<(u64, u64)> {
+        if purchase_ticket.dispute != Pubkey::default() {
+            return Err(ErrorCode::TicketIsDisputed.into());
+        }
 
-    #[account(seeds = [PROGRAM_METADATA_BYTES], bump=program_metadata.bump)]
-    pub program_metadata: Box<Account<'info, ProgramMetadata>>,
+        if self.ticket_taker != Pubkey::default() {
+            return Err(ErrorCode::AlreadyRedeemed.into());
+        }
 
-    #[account(
-        mut,
-        token::mint = purchase_ticket_payment_mint,
-        token::authority = fee_account,
-    )]
-    pub fee_token_account: Account<'info, TokenAccount>,
+        if self.close_slot > 0 {
+            return Err(ErrorCode::AlreadyProcessed.into());
+        }
 
-    /// CHECK:
-    #[account(address = program_metadata.fee_account)]
-    pub fee_account: AccountInfo<'info>,
+        if purchase_ticket.is_expired(clock.unix_timestamp) {
+            return Err(ErrorCode::TicketIsExpired.into());
+        }
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+        if self.take_expiration > 0 && self.take_expiration < clock.unix_timestamp {
+            return Err(ErrorCode::TakeIsExpired.into());
+        }
+
+        self.ticket_taker = ticket_taker;
+        self.ticket_taker_signer = ticket_taker_signer;
+        self.close_slot = clock.slot;
+        self.close_timestamp = clock.unix_timestamp;
+        self.status = RedemptionStatus::REDEEMED;
+
+        if purchase_ticket.expiration_minutes_after_redemption > 0 {
+            self.usage_expiration = clock.unix_timestamp + (i64::from(purchase_ticket.expiration_minutes_after_redemption) * 60);
+        }
+
+        let redemption_payment = self.price * self.redeem_quantity;
+        let referral_commission = if purchase_ticket.referrer != Pubkey::default() {
+            apply_basis_points(redemption_payment, purchase_ticket.referral_basis_points)?
+        } else {
+            0
+        };
+
+        purchase_ticket.redeemed += self.redeem_quantity;
+        purchase_ticket.pending_redemption -= self.redeem_quantity;
+        purchase_ticket.referral_commission += referral_commission;
+
+        Ok((redemption_payment - referral_commission, referral_commission))
+    }
 }
 
-#[derive(Accounts)]
-pub struct CreateStoreTicketTaker<'info> {
+/// records the purchase's copy of the product as it was bought
+/// the snapshot's tiers live in a companion account, same as the product's
+fn snapshot_price_tiers<'info>(price_tiers: PriceTiers, price_tiers_snapshot_info: &AccountInfo<'info>, price_tiers_snapshot_bump: u8,
+    product_snapshot_key: Pubkey, payer: &AccountInfo<'info>, system_program: &AccountInfo<'info>) -> Result<()> {
+
+    create_pda_account(price_tiers_snapshot_info, payer, system_program, 8 + PRICE_TIERS_SIZE,
+        &[PRICE_TIERS_BYTES, product_snapshot_key.as_ref(), &[price_tiers_snapshot_bump]])?;
+
+    let price_tiers_snapshot = PriceTiers {
+        bump: price_tiers_snapshot_bump,
+        version: PRICE_TIERS_VERSION,
+        product: product_snapshot_key,
+        tiers: price_tiers.tiers,
+    };
+    price_tiers_snapshot.try_serialize(&mut &mut price_tiers_snapshot_info.data.borrow_mut()[..])
+}
 
-    #[account(
-        init,
-        payer=store_authority,
-        space = 8 + TICKET_TAKER_SIZE,
-        seeds = [STORE_TAKER_BYTES, store.key().as_ref(), taker.key().as_ref()],
-        bump
-    )]
-    pub ticket_taker: Account<'info, TicketTaker>,
+fn snapshot_product(product: &Account<Product>, product_snapshot_metadata: &mut Account<ProductSnapshotMetadata>, bump: u8,
+    product_snapshot: &mut Account<Product>, nonce: u16, clock: &Clock) {
 
-    ///CHECK: any account with a signer can be authorized by the store_authority
-    pub taker: AccountInfo<'info>,
+    product_snapshot_metadata.bump = bump;
+    product_snapshot_metadata.version = PRODUCT_SNAPSHOT_METADATA_VERSION;
+    product_snapshot_metadata.slot = clock.slot;
+    product_snapshot_metadata.timestamp = clock.unix_timestamp;
+    product_snapshot_metadata.product = product.key();
+    product_snapshot_metadata.product_snapshot = product_snapshot.key();
+    product_snapshot_metadata.nonce = nonce;
 
-    #[account(
-        constraint = store.is_authorized(&store_authority.key),
-        seeds=[STORE_SEED_BYTES, store.creator.as_ref(), &store.id.to_be_bytes()],
-        bump=store.bump
-    )]
-    pub store: Account<'info, Store>,
+    product_snapshot.set_inner(product.clone().into_inner());
+    product_snapshot.is_snapshot = true;
+}
 
-    #[account(mut)]
-    pub store_authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+/// checks quantity of the source ticket can move to a new ticket under the snapshot's resale policy
+fn validate_ticket_transfer(source_ticket: &PurchaseTicket, product_snapshot: &Product, quantity: u64, timestamp: i64) -> Result<()> {
+    source_ticket.check_usable()?;
+
+    if quantity == 0 {
+        return Err(ErrorCode::QuantityMustBeGreaterThanZero.into());
+    }
+
+    if quantity > source_ticket.remaining_quantity {
+        return Err(ErrorCode::InsufficientQuantity.into());
+    }
+
+    if source_ticket.is_expired(timestamp) {
+        return Err(ErrorCode::TicketIsExpired.into());
+    }
+
+    product_snapshot.resale_policy.check_transfer(source_ticket, timestamp)
 }
 
-#[derive(Accounts)]
-pub struct CreateProductTicketTaker<'info> {
+/// copies the source ticket onto the destination for quantity of it. the destination's payment is left to the caller
+fn split_ticket(source_ticket: &mut Account<PurchaseTicket>, destination_ticket: &mut Account<PurchaseTicket>, bump: u8, nonce: u16,
+    destination_authority: Pubkey, quantity: u64, clock: &Clock) {
+
+    destination_ticket.set_inner(source_ticket.clone().into_inner());
+    destination_ticket.bump = bump;
+    destination_ticket.nonce = nonce;
+    destination_ticket.slot = clock.slot;
+    destination_ticket.timestamp = clock.unix_timestamp;
+    destination_ticket.buyer = source_ticket.authority;
+    destination_ticket.authority = destination_authority;
+    destination_ticket.remaining_quantity = quantity;
+    destination_ticket.redeemed = 0;
+    destination_ticket.pending_redemption = 0;
+    destination_ticket.fee = 0; //the fee was paid on the source ticket
+    destination_ticket.referral_commission = 0;
+    destination_ticket.listed_quantity = 0;
+
+    source_ticket.remaining_quantity -= quantity;
+}
 
-    #[account(
-        init,
-        payer=product_authority,
-        space = 8 + TICKET_TAKER_SIZE,
-        seeds = [PRODUCT_TAKER_BYTES, product.key().as_ref(), taker.key().as_ref()],
-        bump
-    )]
-    pub ticket_taker: Account<'info, TicketTaker>,
+/// unit price in payment mint units. converts through the product's price feed when it has one
+fn load_unit_price(product: &Product, price_feed_info: &AccountInfo, decimals: u8, timestamp: i64) -> Result<u64> {
+    let price = product.price_at(timestamp)?;
+
+    if product.price_feed == Pubkey::default() {
+        return Ok(price);
+    }
+
+    if *price_feed_info.owner != crate::ID {
+        return Err(ErrorCode::PublicKeyMismatch.into());
+    }
+
+    let price_feed = PriceFeed::try_deserialize(&mut &price_feed_info.data.borrow()[..])?;
+    if price_feed.version != PRICE_FEED_VERSION {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
+
+    if price_feed.mint != product.payment_mint {
+        return Err(ErrorCode::PaymentMintMismatch.into());
+    }
+
+    if price_feed.publish_time + i64::from(price_feed.max_age_seconds) < timestamp {
+        return Err(ErrorCode::StalePriceFeed.into());
+    }
+
+    price_feed.quote_to_token_amount(price, decimals)
+}
+
+fn load_payout_split(payout_split_info: &AccountInfo) -> Result<PayoutSplit> {
+    if *payout_split_info.owner != crate::ID {
+        return Err(ErrorCode::PublicKeyMismatch.into());
+    }
+
+    let payout_split = PayoutSplit::try_deserialize(&mut &payout_split_info.data.borrow()[..])?;
+    if payout_split.version != PAYOUT_SPLIT_VERSION {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
+
+    Ok(payout_split)
+}
+
+/// splits amount between the payout split's recipients with pay. recipient accounts are their wallets for native payouts,
+/// otherwise their token accounts, in recipient order. the last recipient picks up rounding dust
+fn pay_out_split<'info>(payout_split_info: &AccountInfo, recipient_infos: &[AccountInfo<'info>], amount: u64, native: bool,
+    mut pay: impl FnMut(&AccountInfo<'info>, u64) -> Result<()>) -> Result<()> {
+
+    let payout_split = load_payout_split(payout_split_info)?;
+    let recipients = &payout_split.recipients;
+
+    if recipient_infos.len() != recipients.len() {
+        return Err(ErrorCode::PayoutRecipientMismatch.into());
+    }
+
+    let mut paid: u64 = 0;
+    for (i, (recipient, recipient_info)) in recipients.iter().zip(recipient_infos.iter()).enumerate() {
+        let recipient_wallet = if native {
+            *recipient_info.key
+        } else {
+            Account::<TokenAccount>::try_from(recipient_info)?.owner
+        };
+
+        if recipient_wallet != recipient.wallet {
+            return Err(ErrorCode::PayoutRecipientMismatch.into());
+        }
+
+        let share = if i == recipients.len() - 1 {
+            amount - paid
+        } else {
+            apply_basis_points(amount, recipient.basis_points)?
+        };
+
+        pay(recipient_info, share)?;
+        paid += share;
+    }
+
+    Ok(())
+}
+
+/// checks shared by token and native purchases
+fn validate_purchase(product: &Product, quantity: u64, agreed_expiration_timestamp: i64,
+    agreed_expiration_minutes_after_purchase: u32, agreed_expiration_minutes_after_redemption: u32, timestamp: i64) -> Result<()> {
+
+    validate_sale(product, quantity, timestamp)?;
+
+    //msg!("expiration: {}/{}, afterPurchase: {}/{}, afterRedemption: {}/{}", 
+    //    product.expiration_timestamp, agreed_expiration_timestamp,
+    //    product.expiration_minutes_after_purchase, agreed_expiration_minutes_after_purchase,
+    //    product.expiration_minutes_after_redemption, agreed_expiration_minutes_after_redemption
+    //);
+    if product.expiration_timestamp != agreed_expiration_timestamp {
+        return Err(ErrorCode::AgreedExpirationDoesntMatch.into())
+    }
+
+    if product.expiration_minutes_after_purchase != agreed_expiration_minutes_after_purchase {
+        return Err(ErrorCode::AgreedExpirationAfterPurchaseDoesntMatch.into())
+    }
+    if product.expiration_minutes_after_redemption != agreed_expiration_minutes_after_redemption {
+        return Err(ErrorCode::AgreedExpirationAfterRedemptionDoesntMatch.into())
+    }
+
+    Ok(())
+}
+
+/// whether the product can be sold in the given quantity right now, without the buyer's agreed terms
+fn validate_sale(product: &Product, quantity: u64, timestamp: i64) -> Result<()> {
+
+    if product.status != ProductStatus::ACTIVE {
+        return Err(ErrorCode::ProductIsNotActive.into());
+    }
+
+    if product.inventory < quantity {
+        return Err(ErrorCode::NotEnoughInventory.into());
+    }
 
-    ///CHECK: any account with a signer can be authorized by the store_authority
-    pub taker: AccountInfo<'info>,
+    if product.subscription_period_minutes > 0 {
+        return Err(ErrorCode::SubscriptionProductMustBeSubscribedTo.into());
+    }
 
-    #[account(
-        constraint = product.is_authorized(&product_authority.key),
-        seeds=[PRODUCT_SEED_BYTES, product.creator.as_ref(), &product.id.to_be_bytes()],
-        bump=product.bump
-    )]
-    pub product: Account<'info, Product>,
+    if product.is_snapshot {
+        return Err(ErrorCode::UnableToPurchaseSnapshot.into());
+    }
 
-    #[account(mut)]
-    pub product_authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    if product.expiration_timestamp > 0 && product.expiration_timestamp < timestamp {
+        return Err(ErrorCode::ProductIsExpired.into());
+    }
+
+    Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(nonce: u32, quantity: u64, take_expiration_minutes: u32)]
-pub struct InitiateRedemption<'info> {
+/// refunds quantity off the ticket at the price paid and puts it back in inventory. shared by cancel_ticket and reclaim_gift
+/// returns price * quantity from the ticket's escrow. fees are non-refundable, so ticket.fee stays with the fee account
+fn refund_ticket<'info>(ticket: &mut Account<'info, PurchaseTicket>, ticket_payment: &AccountInfo<'info>, payment_return: &AccountInfo<'info>,
+    product: &mut Product, token_program: &AccountInfo<'info>, quantity: u64) -> Result<()> {
+
+    let refund = ticket.cancel(product, quantity)?;
+
+    let ticket_seed_bump = ticket.bump;
+    let product_snapshot_metadata_key = ticket.product_snapshot_metadata;
+    let buyer_key = ticket.buyer;
+    let ticket_seeds = &[
+        PURCHASE_TICKET_BYTES,
+        product_snapshot_metadata_key.as_ref(),
+        buyer_key.as_ref(),
+        &ticket.nonce.to_be_bytes(),
+        &[ticket_seed_bump]
+    ];
+    let payment_transfer_signer = &[&ticket_seeds[..]];
+
+    //payment transfer
+    let payment_transfer_accounts = anchor_spl::token::Transfer {
+        from: ticket_payment.clone(),
+        to: payment_return.clone(),
+        authority: ticket.to_account_info(),
+    };
+
+    let payment_transfer_cpicontext = CpiContext::new_with_signer(
+        token_program.clone(),
+        payment_transfer_accounts,
+        payment_transfer_signer,
+    );
+
+    token::transfer(payment_transfer_cpicontext, refund)
+}
 
-   #[account(
-        init,
-        payer = purchase_ticket_authority,
-        space = 8 + REDEMPTION_SIZE,
-        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &nonce.to_be_bytes()],
-        bump
-    )]
-    pub redemption: Box<Account<'info, Redemption>>,
+/// system transfer out of a wallet that signed the transaction
+fn transfer_lamports<'info>(from: &AccountInfo<'info>, to: &AccountInfo<'info>, system_program: &AccountInfo<'info>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
 
-    #[account(
-        mut,
-        seeds = 
-        [
-            PURCHASE_TICKET_BYTES,
-            purchase_ticket.product_snapshot_metadata.as_ref(),
-            purchase_ticket.buyer.as_ref(),
-            &purchase_ticket.nonce.to_be_bytes()
-        ], 
-        bump = purchase_ticket.bump,
-        constraint = purchase_ticket.authority == purchase_ticket_authority.key()
-    )]
-    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+    let lamport_transfer_accounts = anchor_lang::system_program::Transfer {
+        from: from.clone(),
+        to: to.clone(),
+    };
 
-    #[account(mut)]
-    pub purchase_ticket_authority: Signer<'info>,
+    let lamport_transfer_cpicontext = CpiContext::new(system_program.clone(), lamport_transfer_accounts);
+    anchor_lang::system_program::transfer(lamport_transfer_cpicontext, amount)
+}
 
-    #[account(
-        token::mint = purchase_ticket_payment_mint,
-        token::authority = purchase_ticket,
-    )]
-    pub purchase_ticket_payment: Account<'info, TokenAccount>,
+/// lamports a program owned account holds on top of its rent exemption
+fn escrowed_lamports(account: &AccountInfo) -> Result<u64> {
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(account.data_len());
+    Ok(account.lamports().saturating_sub(rent_exempt_lamports))
+}
 
-    #[account(address = crate::payment_token::ID)]
-    pub purchase_ticket_payment_mint: Account<'info, Mint>,
-    pub system_program: Program<'info, System>,
+/// moves escrowed lamports out of a program owned account. never dips into its rent
+fn release_escrowed_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    if escrowed_lamports(from)? < amount {
+        return Err(ErrorCode::InsufficientFunds.into());
+    }
+
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct TakeRedemption<'info> {
+/// grows a migrating account to the size of its next version, topping up rent from the payer.
+/// returns the version the account is migrating from.
+fn grow_for_migration<'info>(account: &AccountInfo<'info>, payer: &Signer<'info>, system_program: &Program<'info, System>,
+    discriminator: [u8; 8], version_offset: usize, current_version: u8, version_growth: &[usize]) -> Result<u8> {
 
-    #[account(
-        mut,
-        seeds = 
-        [
-            PURCHASE_TICKET_BYTES,
-            purchase_ticket.product_snapshot_metadata.as_ref(),
-            purchase_ticket.buyer.as_ref(),
-            &purchase_ticket.nonce.to_be_bytes()
-        ],
-        bump = purchase_ticket.bump,
-    )]
-    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+    let version = {
+        let data = account.try_borrow_data()?;
+        if data.len() <= 8 + version_offset || data[..8] != discriminator {
+            return Err(ErrorCode::UninitializedAccount.into());
+        }
+        data[8 + version_offset]
+    };
 
-    #[account(
-        mut,
-        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &redemption.nonce.to_be_bytes()],
-        bump = redemption.bump
-    )]
-    pub redemption: Box<Account<'info, Redemption>>,
+    if version == current_version {
+        return Err(ErrorCode::AccountIsCurrentVersion.into());
+    }
 
-    #[account(
-        constraint = ticket_taker.entity == redemption.product || ticket_taker.entity == redemption.store @ ErrorCode::InvalidTicketTaker
-    )]
-    pub ticket_taker: Box<Account<'info, TicketTaker>>,
+    if version > current_version {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
 
-    #[account(
-        mut,
-        constraint = ticket_taker_signer.key() == ticket_taker.taker @ ErrorCode::InvalidTicketTakerSigner
-    )]
-    pub ticket_taker_signer: Signer<'info>,
+    //every version after the stored one appended its fields, so the account grows by all of them at once
+    let new_len = account.data_len() + version_growth[usize::from(version)..].iter().sum::<usize>();
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
 
-    #[account(
-        mut,
-        token::mint = purchase_ticket_payment_mint,
-        token::authority = purchase_ticket,
-    )]
-    pub purchase_ticket_payment: Account<'info, TokenAccount>,
+    if account.lamports() < required_lamports {
+        let rent_transfer_accounts = anchor_lang::system_program::Transfer {
+            from: payer.to_account_info(),
+            to: account.clone(),
+        };
 
-    #[account(address = crate::payment_token::ID)]
-    pub purchase_ticket_payment_mint: Account<'info, Mint>,
+        let rent_transfer_cpicontext = CpiContext::new(system_program.to_account_info(), rent_transfer_accounts);
+        anchor_lang::system_program::transfer(rent_transfer_cpicontext, required_lamports - account.lamports())?;
+    }
 
-    #[account(
-        mut,
-        token::mint = purchase_ticket_payment_mint,
-        token::authority = pay_to,
-    )]
-    pub pay_to_token_account: Account<'info, TokenAccount>,
+    account.realloc(new_len, true)?;
 
-    /// CHECK: we good
-    #[account(address = redemption.pay_to)]
-    pub pay_to: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    Ok(version)
 }
 
+/// the entity's active fee tier, then the accepted mint's fee override, then the program fee
+fn purchase_fee_schedule(fee_tier_info: &AccountInfo, accepted_mint: &AcceptedMint, program_metadata: &ProgramMetadata, timestamp: i64) -> Result<FeeSchedule> {
+    match load_active_fee_tier(fee_tier_info, timestamp)? {
+        Some(fee) => Ok(fee),
+        None => Ok(accepted_mint.fee_at(timestamp).unwrap_or_else(|| program_metadata.fee_at(timestamp))),
+    }
+}
 
-#[derive(Accounts)]
-pub struct CancelRedemption<'info> {
-    #[account(
-        mut,
-        close = purchase_ticket_authority,
-        seeds = [REDEMPTION_BYTES, purchase_ticket.key().as_ref(), &redemption.nonce.to_be_bytes()],
-        bump = redemption.bump,
-        constraint = redemption.purchase_ticket == purchase_ticket.key()
-    )]
-    pub redemption: Box<Account<'info, Redemption>>,
+/// returns the fee tier's fee at the timestamp if one exists and hasn't expired
+fn load_active_fee_tier(fee_tier_info: &AccountInfo, timestamp: i64) -> Result<Option<FeeSchedule>> {
+    if fee_tier_info.data_is_empty() {
+        return Ok(None);
+    }
 
-    #[account(
-        mut,
-        seeds = 
-        [
-            PURCHASE_TICKET_BYTES,
-            purchase_ticket.product_snapshot_metadata.as_ref(),
-            purchase_ticket.buyer.as_ref(),
-            &purchase_ticket.nonce.to_be_bytes()
-        ], 
-        bump = purchase_ticket.bump,
-        constraint = purchase_ticket.authority == purchase_ticket_authority.key()
-    )]
-    pub purchase_ticket: Box<Account<'info, PurchaseTicket>>,
+    if *fee_tier_info.owner != crate::ID {
+        return Err(ErrorCode::PublicKeyMismatch.into());
+    }
 
-    #[account(mut)]
-    pub purchase_ticket_authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    let fee_tier = FeeTier::try_deserialize(&mut &fee_tier_info.data.borrow()[..])?;
+
+    Ok(fee_tier.fee_at(timestamp))
 }
 
-#[derive(Accounts)]
-#[instruction(nonce: u16, quantity: u64)]
-pub struct TransferTicket<'info> {
+fn load_referral(referral_info: &AccountInfo, product: Pubkey, store: Pubkey) -> Result<Option<Referral>> {
+    if *referral_info.key == Pubkey::default() {
+        return Ok(None);
+    }
 
-    #[account(
-        init,
-        payer = source_ticket_authority,
-        space = 8 + PURCHASE_TICKET_SIZE,
-        seeds = [
-            PURCHASE_TICKET_BYTES,
-            source_ticket.product_snapshot_metadata.as_ref(),
-            source_ticket_authority.key().as_ref(),
-            &nonce.to_be_bytes()],
-        bump
-    )]
-    pub destination_ticket: Box<Account<'info, PurchaseTicket>>,
+    if *referral_info.owner != crate::ID {
+        return Err(ErrorCode::PublicKeyMismatch.into());
+    }
 
-    #[account(
-        init,
-        payer = source_ticket_authority,
-        associated_token::mint = payment_mint,
-        associated_token::authority = destination_ticket
-    )]
-    pub destination_ticket_payment: Account<'info, TokenAccount>,
-  
-    /// CHECK: owner of the new ticket
-    #[account()]
-    pub destination_ticket_authority: AccountInfo<'info>,
+    let referral = Referral::try_deserialize(&mut &referral_info.data.borrow()[..])?;
+    if referral.version != REFERRAL_VERSION {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
 
-    #[account(
-        mut,
-        seeds = [
-            PURCHASE_TICKET_BYTES,
-            source_ticket.product_snapshot_metadata.as_ref(),
-            source_ticket.buyer.as_ref(),
-            &source_ticket.nonce.to_be_bytes()],
-        bump = source_ticket.bump,
-        constraint = source_ticket.authority == source_ticket_authority.key())]
-    pub source_ticket: Box<Account<'info, PurchaseTicket>>,
+    if referral.entity != product && (store == Pubkey::default() || referral.entity != store) {
+        return Err(ErrorCode::InvalidReferral.into());
+    }
 
-    #[account(
-        mut,
-        token::mint = payment_mint,
-        token::authority = source_ticket,
-        address = source_ticket.payment
-    )]
-    pub source_ticket_payment: Account<'info, TokenAccount>,
-  
-    #[account(address = crate::payment_token::ID)]
-    pub payment_mint: Account<'info, Mint>,
+    Ok(Some(referral))
+}
 
-    #[account(mut)]
-    pub source_ticket_authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
+fn validate_coupon(code: &str, discount_type: u8, discount: u64) -> Result<()> {
+    if code.is_empty() || code.len() > COUPON_CODE_SIZE {
+        return Err(ErrorCode::InvalidCoupon.into());
+    }
+
+    let valid_discount = match discount_type {
+        DiscountType::PERCENTAGE => discount > 0 && discount <= BASIS_POINTS_DIVISOR,
+        DiscountType::FIXED => discount > 0,
+        _ => false,
+    };
+
+    if !valid_discount {
+        return Err(ErrorCode::InvalidCoupon.into());
+    }
+
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct CancelTicket<'info> {
+/// validates the optional coupon and counts the use against it, and against the buyer's CouponUsage when it has a per buyer limit.
+/// the CouponUsage is created on the buyer's first use. returns the discount off each unit
+fn redeem_coupon<'info>(coupon_info: &AccountInfo<'info>, coupon_usage_info: &AccountInfo<'info>, buyer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>, product: Pubkey, store: Pubkey, unit_price: u64, timestamp: i64) -> Result<u64> {
 
-    #[account(
-        mut,
-        address = ticket.product,
-    )]
-    pub product: Box<Account<'info, Product>>,
+    if *coupon_info.key == Pubkey::default() {
+        return Ok(0);
+    }
 
-    #[account(
-        mut,
-        seeds = [
-            PURCHASE_TICKET_BYTES,
-            ticket.product_snapshot_metadata.as_ref(),
-            ticket.buyer.as_ref(),
-            &ticket.nonce.to_be_bytes()],
-        bump = ticket.bump,
-        constraint = ticket.authority == ticket_authority.key())]
-    pub ticket: Box<Account<'info, PurchaseTicket>>,
+    if *coupon_info.owner != crate::ID {
+        return Err(ErrorCode::PublicKeyMismatch.into());
+    }
 
-    #[account(
-        mut,
-        token::mint = payment_mint,
-        token::authority = ticket,
-        address = ticket.payment
-    )]
-    pub ticket_payment: Account<'info, TokenAccount>,
+    let mut coupon = Coupon::try_deserialize(&mut &coupon_info.data.borrow()[..])?;
+    if coupon.version != COUPON_VERSION {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
 
-    #[account(
-        mut,
-        token::mint = payment_mint,
-    )]
-    pub payment_return: Account<'info, TokenAccount>,
-  
-    #[account(address = crate::payment_token::ID)]
-    pub payment_mint: Account<'info, Mint>,    
+    if coupon.entity != product && (store == Pubkey::default() || coupon.entity != store) {
+        return Err(ErrorCode::InvalidCoupon.into());
+    }
 
-    #[account(mut)]
-    pub ticket_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-}
+    if !coupon.enabled || (coupon.expiration_timestamp > 0 && coupon.expiration_timestamp < timestamp) {
+        return Err(ErrorCode::CouponIsExpired.into());
+    }
 
+    if coupon.max_uses > 0 && coupon.uses >= coupon.max_uses {
+        return Err(ErrorCode::CouponIsUsedUp.into());
+    }
 
-const PROGRAM_METADATA_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 +32 + 8;
-#[account]
-pub struct ProgramMetadata {
-    pub bump: u8, //1;
-    pub initialized: bool, //1;
-    pub version: u8, //1;
-    pub creator: Pubkey, //32;
-    pub authority: Pubkey, //32;
-    pub secondary_authority: Pubkey, //32;
-    pub fee_account: Pubkey, //32;
-    pub fee: u64, //8;
-}
+    if coupon.max_uses_per_buyer > 0 {
+        let (coupon_usage_key, coupon_usage_bump) = Pubkey::find_program_address(
+            &[COUPON_USAGE_BYTES, coupon_info.key.as_ref(), buyer.key.as_ref()], &crate::ID);
+        if *coupon_usage_info.key != coupon_usage_key {
+            return Err(ErrorCode::InvalidCoupon.into());
+        }
+
+        let mut coupon_usage = if coupon_usage_info.data_is_empty() {
+            create_pda_account(coupon_usage_info, buyer, system_program, 8 + COUPON_USAGE_SIZE,
+                &[COUPON_USAGE_BYTES, coupon_info.key.as_ref(), buyer.key.as_ref(), &[coupon_usage_bump]])?;
+
+            CouponUsage {
+                bump: coupon_usage_bump,
+                version: COUPON_USAGE_VERSION,
+                coupon: coupon_info.key(),
+                buyer: buyer.key(),
+                uses: 0,
+            }
+        }
+        else {
+            CouponUsage::try_deserialize(&mut &coupon_usage_info.data.borrow()[..])?
+        };
 
+        if coupon_usage.uses >= coupon.max_uses_per_buyer {
+            return Err(ErrorCode::CouponIsUsedUp.into());
+        }
 
-pub const STORE_NAME_SIZE: usize = 100;
-pub const STORE_DESCRIPTION_SIZE: usize = 200;
-pub const STORE_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 + 2 + 8 + 8 + (4+STORE_NAME_SIZE) + (4+STORE_DESCRIPTION_SIZE) + 4;
+        coupon_usage.uses += 1;
+        coupon_usage.try_serialize(&mut &mut coupon_usage_info.data.borrow_mut()[..])?;
+    }
 
-#[account]
-pub struct Store{
-    pub bump: u8, //1; bump used for this PDA
-    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
-    pub status: u8, //1; can be used for various status: active, inactive, etc... better than a just an active/inactive boolean
-    pub creator: Pubkey, //32; used as part of PDA seed to make it harder for spamming store creation
-    pub authority: Pubkey, //32; authorized to make changes
-    pub secondary_authority: Pubkey, //32; burner wallet or delegation...
-    pub id: u16, //2; unique store id used as part of the PDA seed 
-    pub tag: u64, //8; used for misc. tagging for simplifying queries. bitmasking maybe?
-    pub product_count: u64, //8; tracks product count.
-    pub name: String, //4+100; eventually used for indexing and querying
-    pub description: String, //4+200; eventually used for indexing and querying    
-    pub data: Vec<u8>, //4+ whatever size they pay for
-    
-    /* UNDECIDED STUFF */
-    //pub category: u64, //64; bitwise AND masked identifier  
-    //pub verified_by: Option<Pubkey>, //1 + 32; If verified, verified by who? store this outside this account
-    //pub rating: u8, //8; current rating; store this outside of the account
-    //pub version: u8, //1; version changes in data structure to provide decision making on serialization/deserialization
-}
+    coupon.uses += 1;
+    coupon.try_serialize(&mut &mut coupon_info.data.borrow_mut()[..])?;
 
+    match coupon.discount_type {
+        DiscountType::PERCENTAGE => apply_basis_points(unit_price, coupon.discount as u16),
+        _ => Ok(coupon.discount.min(unit_price)),
+    }
+}
 
+fn validate_price_tiers(tiers: &[PriceTier]) -> Result<()> {
+    if tiers.len() > MAX_PRICE_TIERS {
+        return Err(ErrorCode::InvalidPriceTiers.into());
+    }
 
-//pub const PRODUCT_SKU_SIZE: usize = 4+25;
-pub const PRODUCT_NAME_SIZE: usize = 100;
-pub const PRODUCT_DESCRIPTION_SIZE: usize = 200;
-pub const PRODUCT_SIZE: usize = 1 + 1 + 1 + 32 + 32 + 32 + 4 + 8 + 1 + 32 + 32 + 32 + 8 + 8 + 1 + 8 + 4 + 4 + (4+PRODUCT_NAME_SIZE) + (4+PRODUCT_DESCRIPTION_SIZE) + 4;
+    let mut previous_min_quantity: u64 = 1;
+    for tier in tiers.iter() {
+        if tier.min_quantity <= previous_min_quantity || u64::from(tier.basis_points) > BASIS_POINTS_DIVISOR {
+            return Err(ErrorCode::InvalidPriceTiers.into());
+        }
 
-#[account]
-pub struct Product{
-    pub bump: u8, //1; bump used for this PDA
-    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
-    pub status: u8, //1; can be used for various status: active, inactive, etc... better than a just an active/inactive boolean
-    pub creator: Pubkey, //32; used as part of PDA seed to make it harder for spamming store creation
-    pub authority: Pubkey, //32; authorized to make changes
-    pub secondary_authority: Pubkey, //32; burner wallet or delegation...
-    pub id: u32, //4; unique store id used as part of the PDA seed 
-    pub tag: u64, //8; used for misc. tagging for simplifying queries. bitmasking maybe?    
-    pub is_snapshot: bool, //1;
-    pub usable_snapshot: Pubkey, //32; default to all zeros for none. Option<> doesn't work. On buys, if it's none, take a snapshot, otherwise use the existing snapshot
-    //pub mint: Pubkey, //32; used to mint a product token to the buyer
-    pub pay_to: Pubkey, //32; where payments should be sent. can be different than the authority
-    pub store: Pubkey, //32; address of store PDA. maybe set to default Pubkey and save a byte?
-    pub price: u64, //8; price of product. needs to be stable, but stablecoins can die, so most likely lamports since they'll be around as long as Solana is
-    pub inventory: u64, //8;
-    pub redemption_type: u8, //1;
-    pub expiration_timestamp: i64, //8    
-    pub expiration_minutes_after_purchase: u32, //4;
-    pub expiration_minutes_after_redemption: u32, //4;    
-    pub name: String, //4+100; product name
-    pub description: String, //4+200; product description
-    pub data: Vec<u8>, //4+ whatever size they pay for
+        previous_min_quantity = tier.min_quantity;
+    }
 
-    /* UNDECIDED STUFF */
-    //pub sku: String, //4+25; This gives the ability to relate the product to a sku in some catalog - not used natively. most won't have this, store it in another account if needed
+    Ok(())
 }
 
-///help protect the seller and buyer from product changes
-/// make sure to update sizes and fields as product changes. maybe just serialize and compress the whole thing?
-/// it's more searchable when not compressed
+fn load_price_tiers(price_tiers_info: &AccountInfo) -> Result<Option<PriceTiers>> {
+    if price_tiers_info.data_is_empty() {
+        return Ok(None);
+    }
 
-const PRODUCT_SNAPSHOT_METADATA_SIZE: usize = 1 + 1 + 8 + 8 + 32 + 32 + 2;
-#[account]
-pub struct ProductSnapshotMetadata {
-    pub bump: u8, //1;
-    pub version: u8, //1;
-    pub slot: u64, //8;
-    pub timestamp: i64, //8; unixtimestamp
-    pub product: Pubkey, //32; 
-    pub product_snapshot: Pubkey, //32; pointer to snapshot
-    pub nonce: u16, //2;
-}
+    if *price_tiers_info.owner != crate::ID {
+        return Err(ErrorCode::PublicKeyMismatch.into());
+    }
 
-const PURCHASE_TICKET_SIZE: usize = 1 + 1 + 8 + 8 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 2 + 8 + 32 + 32 + 8 + 4;
-#[account]
-pub struct PurchaseTicket {
-    pub bump: u8, //1;
-    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
-    pub slot: u64, //8;
-    pub timestamp: i64, //8; unixtimestamp
-    pub product: Pubkey, //32;
-    pub product_snapshot_metadata: Pubkey, //32;
-    pub product_snapshot: Pubkey, //32;
-    pub buyer: Pubkey, //32;
-    pub pay_to: Pubkey, //32;
-    pub authority: Pubkey, //32;
-    pub remaining_quantity: u64, //8;
-    pub redeemed: u64, //8;
-    pub pending_redemption: u64, //8;
-    pub nonce: u16, //2;
-    pub price:u64, //8;
-    pub store: Pubkey, //32;
-    pub payment: Pubkey, //32;
-    pub expiration: i64, //8;
-    pub expiration_minutes_after_redemption: u32, //4;
-}
+    let price_tiers = PriceTiers::try_deserialize(&mut &price_tiers_info.data.borrow()[..])?;
+    if price_tiers.version != PRICE_TIERS_VERSION {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
 
-const TICKET_TAKER_SIZE: usize = 1 + 1 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8;
-#[account]
-pub struct TicketTaker {
-    pub bump: u8, //1;
-    pub version: u8, //1,
-    pub taker: Pubkey, //32,
-    pub entity_type: u8, //1, store or product?
-    pub entity: Pubkey, //32, reference to store or product
-    pub authorized_by: Pubkey, //32, account that authorized this ticket taker
-    pub enabled_slot: u64, //8,
-    pub enabled_timestamp: i64, //8, unix timestamp
-    pub disabled_slot: u64, //8,
-    pub disabled_timestamp: i64, //8, unix timestamp
+    Ok(Some(price_tiers))
 }
 
-const REDEMPTION_SIZE: usize = 1 + 1 + 4 + 8 + 8 + 8 + 8 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 32 + 1 + 8 + 8;
-#[account]
-pub struct Redemption {
-    pub bump: u8, //1;    
-    pub version: u8, //1; used for versioning schema, etc... to identify how to serialize/deserialize changes that may occur in the future
-    pub nonce: u32, //4;
-    pub init_slot: u64, //8;
-    pub close_slot: u64, //8;
-    pub init_timestamp: i64, //8; unixtimestamp
-    pub close_timestamp: i64, //8;
-    pub store: Pubkey, //32;
-    pub product: Pubkey, //32;
-    pub product_snapshot_metadata: Pubkey, //32;
-    pub product_snapshot: Pubkey, //32;
-    pub purchase_ticket: Pubkey, //32;
-    pub purchase_ticket_signer: Pubkey, //32;
-    pub buyer: Pubkey, //32;
-    pub pay_to: Pubkey, //32;
-    //pub purchase_ticket_remaining_quantity: u64, //8;
-    pub redeem_quantity: u64, //8;
-    pub price:u64, //8;
-    pub ticket_taker: Pubkey, //32;
-    pub ticket_taker_signer: Pubkey, //32;
-    pub status: u8, //1;
-    pub take_expiration: i64, //8;
-    pub usage_expiration: i64, //8;
-}
+/// creates a program owned PDA outside of anchor's init, for accounts that only sometimes need to exist.
+/// like init, an address someone already sent lamports to is topped up to rent exemption, allocated and assigned,
+/// since create_account would fail on it
+fn create_pda_account<'info>(account: &AccountInfo<'info>, payer: &AccountInfo<'info>, system_program: &AccountInfo<'info>,
+    space: usize, signer_seeds: &[&[u8]]) -> Result<()> {
 
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
 
-/// Used as a bitwise mask for the product category
-/// this isn't a scalable way to store all the product categories - revisit this
-#[repr(u64)]
-pub enum ProductCategory{
-    None = 0,
-    Media = 1,
-    Merch = 2,
-    Event = 4,
-    Social = 8,
-}
+    if current_lamports == 0 {
+        anchor_lang::solana_program::program::invoke_signed(
+            &anchor_lang::solana_program::system_instruction::create_account(
+                payer.key, account.key, rent, space as u64, &crate::ID),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
 
-/// Used as a bitwise mask for the product type
-/// this isn't a scalable way to store all the product types - revisit this
-#[repr(u64)]
-pub enum ProductType {
-    None = 0,
-    Music = 1,
-    Video = 2,
-    Clothing = 4,
-    Concert = 8,
-    Conference = 16,
-    Award = 32,
-}
+        return Ok(());
+    }
 
+    if rent > current_lamports {
+        anchor_lang::solana_program::program::invoke(
+            &anchor_lang::solana_program::system_instruction::transfer(payer.key, account.key, rent - current_lamports),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("AlreadyInitialized")]
-    AlreadyInitialized,
-    #[msg("PublicKeyMismatch")]
-    PublicKeyMismatch,
-    #[msg("InvalidMintAuthority")]
-    InvalidMintAuthority,
-    #[msg("UninitializedAccount")]
-    UninitializedAccount,
-    #[msg("IncorrectAuthority")]
-    IncorrectAuthority,
-    #[msg("NotMutable")]
-    NotMutable,
-    #[msg("authority Doesn't Exist")]
-    AuthorityDoesntExist,
-    #[msg("pay_to Doesn't Exist")]
-    PayToDoesntExist,
-    #[msg("price is greater than payment")]
-    PriceIsGreaterThanPayment,
-    #[msg("name is too long")]
-    NameIsTooLong,
-    #[msg("description is too long")]
-    DescriptionIsTooLong,
-    #[msg("not enough inventory")]
-    NotEnoughInventory,
-    #[msg("insufficient funds")]
-    InsufficientFunds,
-    #[msg("unable to deduct from buyer account")]
-    UnableToDeductFromBuyerAccount,
-    #[msg("unable to add to pay_to account")]
-    UnableToAddToPayToAccount,
-    #[msg("product is not active")]
-    ProductIsNotActive,
-    #[msg("store is not active")]
-    StoreIsNotActive,
-    #[msg("modifying snapshots is not allowed")]
-    UnableToModifySnapshot,
-    #[msg("purchasing snapshots is not allowed")]
-    UnableToPurchaseSnapshot,
-    #[msg("invalid token account")]
-    InvalidTokenAccount,
-    #[msg("signer isn't the ticket taker")]
-    InvalidTicketTakerSigner,
-    #[msg("ticket taker isn't authorized to take the ticket")]
-    InvalidTicketTaker,
-    #[msg("not enough redemptions remain")]
-    InsufficientRemainingRedemptions,
-    #[msg("quantity must be greater than zero")]
-    QuantityMustBeGreaterThanZero,
-    #[msg("insufficient quantity")]
-    InsufficientQuantity,
-    #[msg("incorrect seed")]
-    IncorrectSeed,
-    #[msg("already processed")]
-    AlreadyProcessed,
-    #[msg("already redeemed")]
-    AlreadyRedeemed,
-    #[msg("product is expired")]
-    ProductIsExpired,
-    #[msg("ticket is expired")]
-    TicketIsExpired,
-    #[msg("agreed expiration doesn't match the currently configured product setting")]
-    AgreedExpirationDoesntMatch,
-    #[msg("agreed expiration after purchase doesn't match the currently configured product setting")]
-    AgreedExpirationAfterPurchaseDoesntMatch,
-    #[msg("agreed expiration after redemption doesn't match the currently configured product setting")]
-    AgreedExpirationAfterRedemptionDoesntMatch,
-    #[msg("the ability to take has expired")]
-    TakeIsExpired,
+    anchor_lang::solana_program::program::invoke_signed(
+        &anchor_lang::solana_program::system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &anchor_lang::solana_program::system_instruction::assign(account.key, &crate::ID),
+        &[account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+
+    Ok(())
 }
 
-impl ProgramMetadata {
-    fn is_authorized(&self, key: &Pubkey) -> bool {
-        *key == self.authority || *key == self.secondary_authority
+/// the store's arbiter, recorded on tickets at purchase. default for lone products
+fn load_store_arbiter(store_info: &AccountInfo) -> Result<Pubkey> {
+    if *store_info.key == Pubkey::default() {
+        return Ok(Pubkey::default());
     }
-}
 
-impl Store {
-    fn is_authorized(&self, key: &Pubkey) -> bool {
-        *key == self.authority || *key == self.secondary_authority
+    let store = Account::<Store>::try_from(store_info)?;
+    if store.version != STORE_VERSION {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
     }
+
+    Ok(store.arbiter)
 }
 
-impl Product {
-    fn is_authorized(&self, key: &Pubkey) -> bool {
-        *key == self.authority || *key == self.secondary_authority
+fn check_referrer_token_account(referrer_token_account_info: &AccountInfo, referrer: Pubkey, mint: Pubkey) -> Result<()> {
+    let referrer_token_account = Account::<TokenAccount>::try_from(referrer_token_account_info)?;
+    if referrer_token_account.owner != referrer || referrer_token_account.mint != mint {
+        return Err(ErrorCode::InvalidReferrerTokenAccount.into());
     }
+
+    Ok(())
 }
 
 struct ProductStatus;
@@ -1553,6 +8001,18 @@ impl ProductStatus {
     //const INACTIVE: u8 = 1;
 }
 
+struct DiscountType;
+impl DiscountType {
+    const PERCENTAGE: u8 = 1;
+    const FIXED: u8 = 2;
+}
+
+struct SubscriptionStatus;
+impl SubscriptionStatus {
+    const ACTIVE: u8 = 1;
+    const CANCELLED: u8 = 2;
+}
+
 struct RedemptionType;
 impl RedemptionType {
     const IMMEDIATE: u8 = 1;
@@ -1564,10 +8024,35 @@ impl RedemptionStatus {
     const WAITING: u8 = 0;
     const REDEEMED: u8 = 1;
     const CANCELLED: u8 = 2;
+    const ARBITRATED: u8 = 3;
+}
+
+struct AdminAction;
+impl AdminAction {
+    const CHANGE_FEE: u8 = 1;
+    const CHANGE_FEE_ACCOUNT: u8 = 2;
+    const CHANGE_AUTHORITY: u8 = 3;
+    const CHANGE_SIGNERS: u8 = 4;
+    const SET_PAUSED: u8 = 5;
+    const SET_FEE_RECIPIENTS: u8 = 6;
+}
+
+/// Used as a bitwise mask for ProgramMetadata.paused
+struct PauseFlag;
+impl PauseFlag {
+    const CREATE_STORE: u64 = 1;
+    const BUY_PRODUCT: u64 = 2;
+    const INITIATE_REDEMPTION: u64 = 4;
+    const TAKE_REDEMPTION: u64 = 8;
+    const TRANSFER_TICKET: u64 = 16;
+    const CANCEL_TICKET: u64 = 32;
+    const RESELL_TICKET: u64 = 64;
+    const AUCTION: u64 = 128;
 }
 
 struct EntityType;
 impl EntityType {
     const STORE: u8 = 1;
     const PRODUCT: u8 = 2;
+    const CREATOR: u8 = 3;
 }
\ No newline at end of file
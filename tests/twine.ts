@@ -13,6 +13,7 @@ import type { Tokenfaucet }  from "./tokenfaucet.ts";
 import TransactionFactory from "@project-serum/anchor/dist/cjs/program/namespace/transaction";
 import * as brotli from 'brotli';
 import * as fflate from 'fflate';
+import { createHash } from 'crypto';
 
 
 const RUN_STANDARD_TESTS = true;
@@ -41,6 +42,17 @@ const toBytes = (data, type) =>
   type == "u64" ? uIntToBytes(BigInt(data), 8, "setBigUint")
                 : `Not Sure about type - ${type}`
 
+//resolves when the call fails with the given anchor error code, e.g. "NotEnoughApprovals" or "ConstraintRaw"
+const expectAnchorError = async (call: Promise<any>, code: string) => {
+  try {
+    await call;
+  } catch(ex) {
+    expect(ex.error?.errorCode?.code).is.equal(code);
+    return;
+  }
+  assert.fail(`expected ${code}`);
+}
+
 function compress(o){
   const json = JSON.stringify(o);
   const jsonBuffer = Buffer.from(json);
@@ -135,10 +147,200 @@ describe("[Twine]", () => {
       creatorKeypair.publicKey.toBuffer(),
       Buffer.from(uIntToBytes(storeProductId,4,"setUint"))
     ], program.programId);
+  let [storeFeeTierPda, storeFeeTierPdaBump] = PublicKey.findProgramAddressSync(
+    [
+      anchor.utils.bytes.utf8.encode("fee_tier"),
+      storePda.toBuffer(),
+    ], program.programId);
+  //lone products use their creator's fee tier, which the tests don't create
+  let [creatorFeeTierPda, creatorFeeTierPdaBump] = PublicKey.findProgramAddressSync(
+    [
+      anchor.utils.bytes.utf8.encode("fee_tier"),
+      creatorKeypair.publicKey.toBuffer(),
+    ], program.programId);
   let [paymentTokenMintAddress, paymentTokenMintAddressBump] = PublicKey.findProgramAddressSync(
     [
       anchor.utils.bytes.utf8.encode("mint"),
     ], tokenFaucetProgram.programId);
+  let [acceptedMintPda, acceptedMintPdaBump] = PublicKey.findProgramAddressSync(
+    [
+      anchor.utils.bytes.utf8.encode("accepted_mint"),
+      paymentTokenMintAddress.toBuffer(),
+    ], program.programId);
+
+  const pda = (...seeds: (string | Buffer)[]) => PublicKey.findProgramAddressSync(
+    seeds.map(seed => typeof seed === 'string' ? Buffer.from(anchor.utils.bytes.utf8.encode(seed)) : seed), program.programId)[0];
+
+  const paymentTokenAddress = (owner: PublicKey) => PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), paymentTokenMintAddress.toBuffer()], ASSOCIATED_TOKEN_PROGRAM_ID)[0];
+
+  const paymentTokenBalance = async (owner: PublicKey) =>
+    (await spl_token.getAccount(provider.connection, paymentTokenAddress(owner), 'confirmed', TOKEN_PROGRAM_ID)).amount;
+
+  //creates the owner's payment token account if needed and airdrops it the amount
+  const fundPaymentTokens = async (owner: PublicKey, amount: number) => {
+    const tokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      creatorKeypair,
+      paymentTokenMintAddress,
+      owner,
+      true,
+      'confirmed',
+      {commitment:'confirmed'},
+      TOKEN_PROGRAM_ID,
+      ASSOCIATED_TOKEN_PROGRAM_ID);
+
+    if(amount > 0) {
+      const airdropTx = await tokenFaucetProgram.methods
+        .executeAirdrop(new anchor.BN(amount))
+        .accounts({
+          signer: creatorKeypair.publicKey,
+          mint: paymentTokenMintAddress,
+          recipient: tokenAccount.address,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, airdropTx, [creatorKeypair], {commitment: 'confirmed'});
+    }
+
+    return tokenAccount.address;
+  }
+
+  //waits for the validator's clock, which is what deadlines are checked against, to pass the timestamp
+  const waitForClockToPass = async (timestamp: number) => {
+    while(true) {
+      const blockTime = await provider.connection.getBlockTime(await provider.connection.getSlot('confirmed'));
+      if(blockTime !== null && blockTime > timestamp)
+        return;
+
+      await new Promise(resolve => setTimeout(resolve, 2000));
+    }
+  }
+
+  const createFundedKeypair = async (lamports: number = 20000000) => {
+    const keypair = Keypair.generate();
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: keypair.publicKey,
+        lamports,
+      })
+    ), [], {commitment: 'confirmed'});
+
+    return keypair;
+  }
+
+  //lamports sent straight to an address, the way anyone could ahead of a PDA being created there
+  const sendLamports = async (to: PublicKey, lamports: number) =>
+    provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.publicKey,
+        toPubkey: to,
+        lamports,
+      })
+    ), [], {commitment: 'confirmed'});
+
+  const createTestProduct = async (redemptionType: number, price: number, inventory: number, creator: Keypair = creatorKeypair) => {
+    const productId = generateRandomU32();
+    const productPda = pda("product", creator.publicKey.toBuffer(), Buffer.from(uIntToBytes(productId,4,"setUint")));
+
+    await program.methods
+      .createProduct(productId, 0, new anchor.BN(price), new anchor.BN(inventory), redemptionType,
+        new anchor.BN(0), 0, 0, productName.toLowerCase(), productDescription.toLowerCase(), compress({displayName: productName}))
+      .accounts({
+        product: productPda,
+        creator: creator.publicKey,
+        authority: creator.publicKey,
+        secondaryAuthority: secondaryAuthorityPubkey,
+        payTo: payToAccountPubkey,
+        acceptedMint: acceptedMintPda,
+      })
+      .signers([creator])
+      .rpc({commitment: 'confirmed'});
+
+    return productPda;
+  }
+
+  type BuyOptions = {
+    quantity?: number,
+    agreedPrice?: number,
+    agreedFee?: number,
+    payment?: number, //moved into the ticket's payment account ahead of the purchase. defaults to (price + fee) * quantity
+    buyFor?: PublicKey,
+    coupon?: PublicKey,
+    couponUsage?: PublicKey,
+    payoutSplit?: PublicKey,
+    priceFeed?: PublicKey,
+    remainingAccounts?: anchor.web3.AccountMeta[],
+    nonce?: number, //for addresses that need to be known ahead of the purchase. random by default
+  };
+
+  //buys with creatorKeypair in one transaction, the way the app does, and returns the purchase's accounts
+  const buyTestProduct = async (productPda: PublicKey, options: BuyOptions = {}) => {
+    const product = await program.account.product.fetch(productPda, 'confirmed');
+    const quantity = options.quantity ?? 1;
+    const payment = options.payment ?? (product.price.toNumber() + PURCHASE_TRANSACTION_FEE) * quantity;
+    const nonce = options.nonce ?? generateRandomU16();
+    const buyerTokenAddress = await fundPaymentTokens(creatorKeypair.publicKey, payment);
+    const feeTokenAddress = await fundPaymentTokens(feeAccountPubkey, 0);
+    const payToTokenAddress = await fundPaymentTokens(product.payTo, 0);
+    const productSnapshotMetadataPda = pda("product_snapshot_metadata", productPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+    const productSnapshotPda = pda("product_snapshot", productSnapshotMetadataPda.toBuffer());
+    const purchaseTicketPda = pda("purchase_ticket", productSnapshotMetadataPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+    const purchaseTicketPaymentAddress = paymentTokenAddress(purchaseTicketPda);
+    const priceTiersSnapshotPda = pda("price_tiers", productSnapshotPda.toBuffer());
+
+    await program.methods
+      .buyProduct(nonce, new anchor.BN(quantity), new anchor.BN(options.agreedPrice ?? product.price.toNumber()), new anchor.BN(options.agreedFee ?? PURCHASE_TRANSACTION_FEE),
+        product.expirationTimestamp, product.expirationMinutesAfterPurchase, product.expirationMinutesAfterRedemption)
+      .accounts({
+        product: productPda,
+        productSnapshotMetadata: productSnapshotMetadataPda,
+        productSnapshot: productSnapshotPda,
+        buyer: creatorKeypair.publicKey,
+        buyFor: options.buyFor ?? creatorKeypair.publicKey,
+        payTo: product.payTo,
+        payToTokenAccount: payToTokenAddress,
+        purchaseTicket: purchaseTicketPda,
+        purchaseTicketPayment: purchaseTicketPaymentAddress,
+        purchaseTicketPaymentMint: paymentTokenMintAddress,
+        programMetadata: programMetadataPda,
+        feeTokenAccount: feeTokenAddress,
+        feeAccount: feeAccountPubkey,
+        feeTier: pda("fee_tier", (product.store.equals(PublicKey.default) ? product.creator : product.store).toBuffer()),
+        referral: anchor.web3.SystemProgram.programId,
+        referrerTokenAccount: payToTokenAddress,
+        acceptedMint: acceptedMintPda,
+        priceFeed: options.priceFeed ?? anchor.web3.SystemProgram.programId,
+        store: product.store,
+        payoutSplit: options.payoutSplit ?? anchor.web3.SystemProgram.programId,
+        coupon: options.coupon ?? anchor.web3.SystemProgram.programId,
+        couponUsage: options.couponUsage ?? anchor.web3.SystemProgram.programId,
+        priceTiers: pda("price_tiers", productPda.toBuffer()),
+        priceTiersSnapshot: priceTiersSnapshotPda,
+      })
+      .remainingAccounts(options.remainingAccounts ?? [])
+      .preInstructions([
+        spl_token.createAssociatedTokenAccountInstruction(
+          creatorKeypair.publicKey,
+          purchaseTicketPaymentAddress,
+          purchaseTicketPda,
+          paymentTokenMintAddress,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID),
+        spl_token.createTransferInstruction(
+          buyerTokenAddress,
+          purchaseTicketPaymentAddress,
+          creatorKeypair.publicKey,
+          payment,
+          [],
+          TOKEN_PROGRAM_ID),
+      ])
+      .signers([creatorKeypair])
+      .rpc({commitment: 'confirmed'});
+
+    return {productSnapshotMetadataPda, productSnapshotPda, purchaseTicketPda, purchaseTicketPaymentAddress, priceTiersSnapshotPda};
+  }
 
 
   before(() => {
@@ -221,12 +423,69 @@ describe("[Twine]", () => {
       programMetadata = await program.account.programMetadata.fetch(programMetadataPda);
       expect(programMetadata.bump).is.equal(programMetadataPdaBump);
       expect(programMetadata.initialized).is.equal(true);
-      expect(programMetadata.version).is.equal(0);
+      expect(programMetadata.version).is.equal(2);
       expect(programMetadata.creator).is.eql(creatorKeypair.publicKey);
       expect(programMetadata.authority).is.eql(provider.publicKey);
       expect(programMetadata.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
       expect(programMetadata.feeAccount).is.eql(feeAccountPubkey);
-      expect(programMetadata.fee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(programMetadata.fee.flatFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(programMetadata.fee.basisPoints).is.equal(0);
+    });
+
+    it("Add accepted mint", async () => {
+      await program.methods
+      .addAcceptedMint()
+      .accounts({
+        acceptedMint: acceptedMintPda,
+        mint: paymentTokenMintAddress,
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const acceptedMint = await program.account.acceptedMint.fetch(acceptedMintPda, 'confirmed');
+      expect(acceptedMint.bump).is.equal(acceptedMintPdaBump);
+      expect(acceptedMint.version).is.equal(0);
+      expect(acceptedMint.mint).is.eql(paymentTokenMintAddress);
+      expect(acceptedMint.enabled).is.equal(true);
+      expect(acceptedMint.feeOverride).is.equal(false);
+    });
+
+    it("Create and update price feed", async () => {
+      const [priceFeedPda, priceFeedPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("price_feed"),
+          paymentTokenMintAddress.toBuffer(),
+        ], program.programId);
+
+      await program.methods
+      .createPriceFeed(60)
+      .accounts({
+        priceFeed: priceFeedPda,
+        acceptedMint: acceptedMintPda,
+        updater: provider.publicKey,
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const publishTime = Math.floor(new Date().getTime() / 1000) - 5;
+      await program.methods
+      .updatePriceFeed(new anchor.BN(100000000), -8, new anchor.BN(publishTime))
+      .accounts({
+        priceFeed: priceFeedPda,
+        updater: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const priceFeed = await program.account.priceFeed.fetch(priceFeedPda, 'confirmed');
+      expect(priceFeed.bump).is.equal(priceFeedPdaBump);
+      expect(priceFeed.mint).is.eql(paymentTokenMintAddress);
+      expect(priceFeed.updater).is.eql(provider.publicKey);
+      expect(priceFeed.price.toNumber()).is.equal(100000000);
+      expect(priceFeed.exponent).is.equal(-8);
+      expect(priceFeed.publishTime.toNumber()).is.equal(publishTime);
+      expect(priceFeed.maxAgeSeconds).is.equal(60);
     });
 
     it("Change fee account", async () => {
@@ -254,6 +513,141 @@ describe("[Twine]", () => {
       expect(programMetadata.feeAccount).is.eql(feeAccountPubkey);
     });
 
+    it("Propose and accept authority change", async () => {
+      const proposeTx = await program.methods
+      .proposeAuthorityChange()
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+        newAuthority: provider.publicKey,
+        newSecondaryAuthority: secondaryAuthorityPubkey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const proposedProgramMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(proposedProgramMetadata.pendingAuthority).is.eql(provider.publicKey);
+      expect(proposedProgramMetadata.pendingSecondaryAuthority).is.eql(secondaryAuthorityPubkey);
+
+      const acceptTx = await program.methods
+      .acceptAuthorityChange()
+      .accounts({
+        programMetadata: programMetadataPda,
+        newAuthority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.authority).is.eql(provider.publicKey);
+      expect(programMetadata.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
+      expect(programMetadata.pendingAuthority).is.eql(PublicKey.default);
+      expect(programMetadata.pendingSecondaryAuthority).is.eql(PublicKey.default);
+    });
+
+    it("Change fee is scheduled", async () => {
+      const programMetadataBefore = await program.account.programMetadata.fetch(programMetadataPda);
+
+      //re-scheduling the current fee keeps purchases in the rest of the tests at PURCHASE_TRANSACTION_FEE
+      const tx = await program.methods
+      .changeFee({
+        flatFee: new anchor.BN(PURCHASE_TRANSACTION_FEE),
+        basisPoints: 0,
+        minFee: new anchor.BN(0),
+        maxFee: new anchor.BN(0),
+      })
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.fee.flatFee.toNumber()).is.equal(programMetadataBefore.fee.flatFee.toNumber());
+      expect(programMetadata.pendingFee.flatFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(programMetadata.pendingFee.basisPoints).is.equal(0);
+      expect(programMetadata.pendingFeeTimestamp.toNumber()).is.greaterThan(Math.floor(new Date().getTime() / 1000));
+    });
+
+    it("Pause and unpause purchases", async () => {
+      const BUY_PRODUCT_PAUSE_FLAG = 2;
+
+      await program.methods
+      .setPaused(new anchor.BN(BUY_PRODUCT_PAUSE_FLAG))
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const pausedProgramMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(pausedProgramMetadata.paused.toNumber()).is.equal(BUY_PRODUCT_PAUSE_FLAG);
+
+      await program.methods
+      .setPaused(new anchor.BN(0))
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.paused.toNumber()).is.equal(0);
+    });
+
+    it("Change fee account as unauthorized signer fails", async () => {
+      const tx = await program.methods
+      .changeFeeAccount()
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: creatorKeypair.publicKey,
+        feeAccount: creatorKeypair.publicKey,
+      })
+      .transaction();
+
+      try {
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+        assert.fail("change_fee_account should have been rejected");
+      } catch(ex) {
+        expect(ex.message).to.not.equal("change_fee_account should have been rejected");
+      }
+
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda);
+      expect(programMetadata.feeAccount).is.eql(feeAccountPubkey);
+    });
+
+    it("Set fee recipients", async () => {
+      await program.methods
+      .setFeeRecipients([
+        {wallet: feeAccountPubkey, basisPoints: 7000},
+        {wallet: secondaryAuthorityPubkey, basisPoints: 3000},
+      ])
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const splitProgramMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(splitProgramMetadata.feeAccount).is.eql(programMetadataPda);
+      expect(splitProgramMetadata.feeRecipients.length).is.equal(2);
+      expect(splitProgramMetadata.feeRecipients[0].wallet).is.eql(feeAccountPubkey);
+      expect(splitProgramMetadata.feeRecipients[1].basisPoints).is.equal(3000);
+
+      //going back to a single fee account keeps the rest of the tests paying feeAccountPubkey
+      await program.methods
+      .changeFeeAccount()
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+        feeAccount: feeAccountPubkey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      //the recipients stay, so fees already collected can still be distributed
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.feeAccount).is.eql(feeAccountPubkey);
+      expect(programMetadata.feeRecipients.length).is.equal(2);
+    });
+
   }); //program tests
 
 if(RUN_STANDARD_TESTS)
@@ -271,6 +665,7 @@ if(RUN_STANDARD_TESTS)
         creator: creatorKeypair.publicKey,
         authority: creatorKeypair.publicKey,
         secondaryAuthority: storeSecondaryAuthorityKeypair.publicKey,    
+        programMetadata: programMetadataPda,
       })
       .transaction();
 
@@ -376,6 +771,7 @@ if(RUN_STANDARD_TESTS)
           secondaryAuthority: secondaryAuthorityPubkey,  
           payTo: payToAccountPubkey,
           //tokenProgram: TOKEN_PROGRAM_ID,
+          acceptedMint: acceptedMintPda,
         })
         .transaction();
     
@@ -414,6 +810,7 @@ if(RUN_STANDARD_TESTS)
       expect(createdProduct.name).is.equal(productName.toLowerCase());
       expect(createdProduct.description).is.equal(productDescription.toLowerCase());  
       expect(createdProduct.data).is.eql(data);
+      expect(createdProduct.paymentMint).is.eql(paymentTokenMintAddress);
 
       const store = await program.account.store.fetch(storePda);
       expect(store.productCount.toNumber()).is.equal(1);
@@ -475,6 +872,68 @@ if(RUN_STANDARD_TESTS)
       
     });
 
+    it("Create Store Fee Tier", async () => {
+      const expirationTimestamp = Math.floor(new Date().getTime() / 1000) + (60 * 60 * 24 * 30); //30 days from now
+      const fee = {
+        flatFee: new anchor.BN(0),
+        basisPoints: 50,
+        minFee: new anchor.BN(1000),
+        maxFee: new anchor.BN(PURCHASE_TRANSACTION_FEE),
+      };
+
+      const tx = await program.methods
+        .createStoreFeeTier(fee, new anchor.BN(expirationTimestamp))
+        .accounts({
+          feeTier: storeFeeTierPda,
+          store: storePda,
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      const feeTier = await program.account.feeTier.fetch(storeFeeTierPda, 'confirmed');
+      expect(feeTier.bump).is.equal(storeFeeTierPdaBump);
+      expect(feeTier.version).is.equal(1);
+      expect(feeTier.entityType).is.equal(1);
+      expect(feeTier.entity).is.eql(storePda);
+      expect(feeTier.authorizedBy).is.eql(provider.publicKey);
+      expect(feeTier.fee.basisPoints).is.equal(50);
+      expect(feeTier.fee.minFee.toNumber()).is.equal(1000);
+      expect(feeTier.fee.maxFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(feeTier.expirationTimestamp.toNumber()).is.equal(expirationTimestamp);
+
+      const store = await program.account.store.fetch(storePda, 'confirmed');
+      expect(store.feeTier).is.eql(storeFeeTierPda);
+    });
+
+    it("Update Store Fee Tier is scheduled", async () => {
+      const feeTierBefore = await program.account.feeTier.fetch(storeFeeTierPda, 'confirmed');
+      const scheduledAt = Math.floor(new Date().getTime() / 1000);
+      const fee = {
+        flatFee: new anchor.BN(0),
+        basisPoints: 100,
+        minFee: new anchor.BN(1000),
+        maxFee: new anchor.BN(PURCHASE_TRANSACTION_FEE),
+      };
+
+      await program.methods
+        .updateFeeTier(fee, new anchor.BN(0))
+        .accounts({
+          feeTier: storeFeeTierPda,
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      //the current fee and expiration stay in effect until the delay passes
+      const feeTier = await program.account.feeTier.fetch(storeFeeTierPda, 'confirmed');
+      expect(feeTier.fee.basisPoints).is.equal(feeTierBefore.fee.basisPoints);
+      expect(feeTier.expirationTimestamp.toNumber()).is.equal(feeTierBefore.expirationTimestamp.toNumber());
+      expect(feeTier.pendingFee.basisPoints).is.equal(100);
+      expect(feeTier.pendingExpirationTimestamp.toNumber()).is.equal(0);
+      expect(feeTier.pendingFeeTimestamp.toNumber()).is.greaterThanOrEqual(scheduledAt + 60 * 60 * 24 - 60);
+    });
+
     it("Create Store Ticket Taker", async () => {
       const [storeTicketTakerPda, storeTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
         [
@@ -507,108 +966,218 @@ if(RUN_STANDARD_TESTS)
       expect(ticketTaker.disabledTimestamp.toNumber()).is.equal(0);
     });
 
-  });//store tests
-
-  describe("[Lone Product Tests]", () => {
-    const loneProductId = generateRandomU32();
-    const updatedProductPrice = 200000;
-    let [loneProductPda, loneProductPdaBump] = PublicKey.findProgramAddressSync(
-      [
-        anchor.utils.bytes.utf8.encode("product"),
-        creatorKeypair.publicKey.toBuffer(),
-        Buffer.from(uIntToBytes(loneProductId,4,"setUint"))
-      ], program.programId);
-
-    it("Create Lone Product - Immediate Redemption", async () => {    
-      //const productMintDecimals = 3;
-      const data = compress({displayName: productName, displayDescription: productDescription});
-      const redemptionType = 1; //immediate
-      const loneProductStatus = 0;
+    it("Create Store Referral", async () => {
+      const referrerPubkey = anchor.web3.Keypair.generate().publicKey;
+      const [storeReferralPda, storeReferralPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("referral"),
+          storePda.toBuffer(),
+          referrerPubkey.toBuffer(),
+        ], program.programId);
 
       const tx = await program.methods
-      .createProduct(loneProductId, loneProductStatus, productPrice, productInventory, redemptionType,
-        new anchor.BN(0), 0, 0,
-        productName.toLowerCase(), productDescription.toLowerCase(), data)
-      .accounts({
-        //mint: loneProductMintPda,
-        product: loneProductPda,
-        creator: creatorKeypair.publicKey,
-        authority: creatorKeypair.publicKey,
-        secondaryAuthority: secondaryAuthorityPubkey,
-        payTo: payToAccountPubkey,
-      })
-      .transaction();
-    
-      const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+        .createStoreReferral(500)
+        .accounts({
+          referral: storeReferralPda,
+          referrer: referrerPubkey,
+          store: storePda,
+          storeAuthority: creatorKeypair.publicKey
+        })
+        .transaction();
 
-      const createdProduct = await program.account.product.fetch(loneProductPda);
-      expect(createdProduct.bump).is.equal(loneProductPdaBump);
-      expect(createdProduct.status).is.equal(loneProductStatus);
-      expect(createdProduct.creator).is.eql(creatorKeypair.publicKey);
-      expect(createdProduct.authority).is.eql(creatorKeypair.publicKey);
-      expect(createdProduct.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
-      expect(createdProduct.id).is.equal(loneProductId); 
-      expect(createdProduct.tag.toNumber()).is.equal(0); 
-      expect(createdProduct.isSnapshot).is.equal(false); 
-      //expect(createdProduct.mint).is.eql(loneProductMintPda);
-      expect(createdProduct.payTo).is.eql(payToAccountPubkey);
-      expect(createdProduct.store).is.eql(PublicKey.default); 
-      expect(createdProduct.price.toNumber()).is.equal(productPrice.toNumber());
-      expect(createdProduct.inventory.toNumber()).is.equal(productInventory.toNumber());
-      expect(createdProduct.redemptionType).is.equal(redemptionType);
-      expect(createdProduct.expirationMinutesAfterPurchase).is.equal(0);
-      expect(createdProduct.expirationMinutesAfterRedemption).is.equal(0);
-      expect(createdProduct.expirationTimestamp.toNumber()).is.equal(0);
-      expect(createdProduct.name).is.equal(productName.toLowerCase());
-      expect(createdProduct.description).is.equal(productDescription.toLowerCase())    
-      expect(createdProduct.data).is.eql(data); 
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+      const referral = await program.account.referral.fetch(storeReferralPda);
+      expect(referral.bump).is.equal(storeReferralPdaBump);
+      expect(referral.version).is.equal(0);
+      expect(referral.entityType).is.equal(1);
+      expect(referral.entity).is.eql(storePda);
+      expect(referral.referrer).is.eql(referrerPubkey);
+      expect(referral.authorizedBy).is.eql(creatorKeypair.publicKey);
+      expect(referral.basisPoints).is.equal(500);
     });
 
-    it("Create and fund buyer ATA for payment token - Immediate Redemption", async() => {
-      const buyerPaymentTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
-        provider.connection,
-        creatorKeypair,
-        paymentTokenMintAddress,
-        creatorKeypair.publicKey,
-        false,
-        'finalized',
-        {commitment:'finalized'},
-        TOKEN_PROGRAM_ID,
-        ASSOCIATED_TOKEN_PROGRAM_ID);
+    it("Close Store Referral", async () => {
+      const referrerPubkey = anchor.web3.Keypair.generate().publicKey;
+      const storeReferralPda = pda("referral", storePda.toBuffer(), referrerPubkey.toBuffer());
 
+      await program.methods
+        .createStoreReferral(500)
+        .accounts({
+          referral: storeReferralPda,
+          referrer: referrerPubkey,
+          store: storePda,
+          storeAuthority: creatorKeypair.publicKey
+        })
+        .signers([creatorKeypair])
+        .rpc();
 
-        //console.log('buyer token account: ', buyerPaymentTokenAccount.address.toBase58());
-        expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
-        expect(buyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
-        expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
+      await program.methods
+        .closeStoreReferral()
+        .accounts({
+          referral: storeReferralPda,
+          store: storePda,
+          storeAuthority: creatorKeypair.publicKey
+        })
+        .signers([creatorKeypair])
+        .rpc();
 
-        //console.log(`funding creator payment account with ${paymentTokensRequired} tokens from mint ${paymentTokenMintAddress}`);
-        const paymentTokenAirdropTx = await tokenFaucetProgram.methods
-          .executeAirdrop(new anchor.BN(paymentTokensRequired))
-          .accounts({
-            signer: creatorKeypair.publicKey,
-            mint: paymentTokenMintAddress,
-            recipient: buyerPaymentTokenAccount.address,
-          })
-          .transaction();
+      const referral = await program.account.referral.fetchNullable(storeReferralPda);
+      expect(referral).is.null;
+    });
 
-        const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, paymentTokenAirdropTx, [creatorKeypair], {commitment: 'finalized'});
-        const updatedBuyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAccount.address, 'finalized', TOKEN_PROGRAM_ID);
-        expect(updatedBuyerPaymentTokenAccount.address).is.eql(buyerPaymentTokenAccount.address);
-        expect(updatedBuyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
-        expect(updatedBuyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
-        expect(updatedBuyerPaymentTokenAccount.amount).is.equal(BigInt(paymentTokensRequired));
+    it("Create Store Coupon", async () => {
+      const code = "WELCOME10";
+      const expirationTimestamp = Math.floor(new Date().getTime() / 1000) + (60 * 60 * 24 * 30);
+      const [storeCouponPda, storeCouponPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("coupon"),
+          storePda.toBuffer(),
+          anchor.utils.bytes.utf8.encode(code),
+        ], program.programId);
+
+      const tx = await program.methods
+        .createStoreCoupon(code, 1, new anchor.BN(1000), new anchor.BN(expirationTimestamp), 100, 1)
+        .accounts({
+          coupon: storeCouponPda,
+          store: storePda,
+          storeAuthority: creatorKeypair.publicKey
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+      const coupon = await program.account.coupon.fetch(storeCouponPda);
+      expect(coupon.bump).is.equal(storeCouponPdaBump);
+      expect(coupon.version).is.equal(0);
+      expect(coupon.entityType).is.equal(1);
+      expect(coupon.entity).is.eql(storePda);
+      expect(coupon.authorizedBy).is.eql(creatorKeypair.publicKey);
+      expect(coupon.code).is.equal(code);
+      expect(coupon.discountType).is.equal(1);
+      expect(coupon.discount.toNumber()).is.equal(1000);
+      expect(coupon.expirationTimestamp.toNumber()).is.equal(expirationTimestamp);
+      expect(coupon.maxUses).is.equal(100);
+      expect(coupon.uses).is.equal(0);
+      expect(coupon.maxUsesPerBuyer).is.equal(1);
+      expect(coupon.enabled).is.equal(true);
     });
 
+    it("Create Store Payout Split", async () => {
+      const payoutSplitId = generateRandomU32();
+      const [payoutSplitPda, payoutSplitPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("payout_split"),
+          creatorKeypair.publicKey.toBuffer(),
+          Buffer.from(uIntToBytes(payoutSplitId,4,"setUint"))
+        ], program.programId);
 
-    it("Buy Lone Product - Immediate Redemption", async () => {
+      const createTx = await program.methods
+        .createPayoutSplit(payoutSplitId, [
+          {wallet: payToAccountPubkey, basisPoints: 6000},
+          {wallet: storeSecondaryAuthorityKeypair.publicKey, basisPoints: 4000},
+        ])
+        .accounts({
+          payoutSplit: payoutSplitPda,
+          creator: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, createTx, [creatorKeypair], {commitment: 'confirmed'});
+
+      const payoutSplit = await program.account.payoutSplit.fetch(payoutSplitPda, 'confirmed');
+      expect(payoutSplit.bump).is.equal(payoutSplitPdaBump);
+      expect(payoutSplit.creator).is.eql(creatorKeypair.publicKey);
+      expect(payoutSplit.recipients.length).is.equal(2);
+      expect(payoutSplit.recipients[1].basisPoints).is.equal(4000);
+
+      const setTx = await program.methods
+        .setStorePayoutSplit()
+        .accounts({
+          store: storePda,
+          payoutSplit: payoutSplitPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, setTx, [creatorKeypair], {commitment: 'confirmed'});
+
+      const store = await program.account.store.fetch(storePda, 'confirmed');
+      expect(store.payoutSplit).is.eql(payoutSplitPda);
+    });
+
+    let storeArbiterKeypair: Keypair;
+
+    it("Set Store Arbiter", async () => {
+      storeArbiterKeypair = await createFundedKeypair();
+
+      //the store can't arbitrate its own tickets
+      await expectAnchorError(program.methods
+        .setStoreArbiter()
+        .accounts({
+          store: storePda,
+          arbiter: storeSecondaryAuthorityKeypair.publicKey,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc(), "InvalidArbiter");
+
+      const tx = await program.methods
+        .setStoreArbiter()
+        .accounts({
+          store: storePda,
+          arbiter: storeArbiterKeypair.publicKey,
+          authority: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair], {commitment: 'confirmed'});
+
+      const store = await program.account.store.fetch(storePda, 'confirmed');
+      expect(store.arbiter).is.eql(storeArbiterKeypair.publicKey);
+    });
+
+    let checkoutPurchaseTicketPda: PublicKey;
+
+    it("Checkout Store Product", async () => {
       const quantity = 1;
-      let buyForPubkey = creatorKeypair.publicKey;
-      const loneProduct = await program.account.product.fetch(loneProductPda);
-      const buyerPaymentTokenAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, creatorKeypair.publicKey, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
-      const payToAtaAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, loneProduct.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
-      
-      const nonce = generateRandomU16();
+      const storeProductBefore = await program.account.product.fetch(storeProductPda);
+
+      //back to active, with no expiration, so it can be bought
+      const updateTx = await program.methods
+        .updateProduct(0, storeProductBefore.price, storeProductBefore.inventory, storeProductBefore.redemptionType,
+          new anchor.BN(0), 0, 0, storeProductBefore.name, storeProductBefore.description, storeProductBefore.data)
+        .accounts({
+          product: storeProductPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, updateTx, [creatorKeypair], {commitment: 'confirmed'});
+
+      const orderTotal = storeProductBefore.price.toNumber() * quantity;
+      const buyerPaymentTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        creatorKeypair,
+        paymentTokenMintAddress,
+        creatorKeypair.publicKey,
+        false,
+        'confirmed',
+        {commitment:'confirmed'},
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID);
+
+      const airdropTx = await tokenFaucetProgram.methods
+        .executeAirdrop(new anchor.BN(orderTotal + PURCHASE_TRANSACTION_FEE))
+        .accounts({
+          signer: creatorKeypair.publicKey,
+          mint: paymentTokenMintAddress,
+          recipient: buyerPaymentTokenAccount.address,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, airdropTx, [creatorKeypair], {commitment: 'confirmed'});
+
       const feeTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
         provider.connection,
         creatorKeypair,
@@ -620,39 +1189,38 @@ if(RUN_STANDARD_TESTS)
         TOKEN_PROGRAM_ID,
         ASSOCIATED_TOKEN_PROGRAM_ID);
 
-      //console.log('buyer payment token address: ', buyerPaymentTokenAddress.toBase58());
-      //console.log('payTo ATA address: ', payToAtaAddress.toBase58());
-      
-
-      const [productSnapshotMetadataPda, productSnapshotMetadataPdaBump] = PublicKey.findProgramAddressSync(
+      const nonce = generateRandomU16();
+      const itemIndex = 0;
+      const [orderPda, orderPdaBump] = PublicKey.findProgramAddressSync(
         [
-          anchor.utils.bytes.utf8.encode("product_snapshot_metadata"),
-          loneProductPda.toBuffer(),
+          anchor.utils.bytes.utf8.encode("order"),
           creatorKeypair.publicKey.toBuffer(),
           Buffer.from(uIntToBytes(nonce,2,"setUint")),
         ], program.programId);
 
-      const [productSnapshotPda, productSnapshotPdaBump] = PublicKey.findProgramAddressSync(
+      const [productSnapshotPda] = PublicKey.findProgramAddressSync(
         [
           anchor.utils.bytes.utf8.encode("product_snapshot"),
-          productSnapshotMetadataPda.toBuffer(),
+          orderPda.toBuffer(),
+          Buffer.from(uIntToBytes(itemIndex,2,"setUint")),
         ], program.programId);
 
-      const [purchaseTicketPda, purchaseTicketPdaBump] = PublicKey.findProgramAddressSync(
+      const [purchaseTicketPda] = PublicKey.findProgramAddressSync(
         [
           anchor.utils.bytes.utf8.encode("purchase_ticket"),
-          productSnapshotMetadataPda.toBuffer(),
+          orderPda.toBuffer(),
           creatorKeypair.publicKey.toBuffer(),
-          Buffer.from(uIntToBytes(nonce,2,"setUint"))
+          Buffer.from(uIntToBytes(itemIndex,2,"setUint")),
         ], program.programId);
-    
+
       const purchaseTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
         paymentTokenMintAddress,
         purchaseTicketPda,
         true,
         TOKEN_PROGRAM_ID,
         ASSOCIATED_TOKEN_PROGRAM_ID);
-      //console.log('purchaseTicketPaymentAddress: ', purchaseTicketPaymentAddress.toBase58());
+
+      const payToAtaAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, storeProductBefore.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
 
       const createPurchaseTicketAtaIx = spl_token.createAssociatedTokenAccountInstruction(
         creatorKeypair.publicKey,
@@ -661,287 +1229,911 @@ if(RUN_STANDARD_TESTS)
         paymentTokenMintAddress,
         TOKEN_PROGRAM_ID,
         ASSOCIATED_TOKEN_PROGRAM_ID);
-        
-      const transferToPurchaseTicketAtaIx = spl_token.createTransferInstruction(
-        buyerPaymentTokenAddress,
-        purchaseTicketPaymentAddress,
-        creatorKeypair.publicKey,
-        paymentTokensRequired,
-        [],
-        TOKEN_PROGRAM_ID,
-      );
-
-      let payToAta: Account;
-      try {
-        payToAta = await spl_token.getAccount(provider.connection, payToAtaAddress, 'confirmed', TOKEN_PROGRAM_ID);
-      } catch(ex) {
-      }
 
-      const buyProductIx = await program.methods
-        .buyProduct(nonce, new anchor.BN(quantity), loneProduct.price,
-          loneProduct.expirationTimestamp, loneProduct.expirationMinutesAfterPurchase, loneProduct.expirationMinutesAfterRedemption)
+      const checkoutIx = await program.methods
+        .checkout(nonce, [{
+          quantity: new anchor.BN(quantity),
+          agreedPrice: storeProductBefore.price,
+          agreedExpirationTimestamp: new anchor.BN(0),
+          agreedExpirationMinutesAfterPurchase: 0,
+          agreedExpirationMinutesAfterRedemption: 0,
+        }], new anchor.BN(PURCHASE_TRANSACTION_FEE))
         .accounts({
-          product: loneProductPda,
-          productSnapshotMetadata: productSnapshotMetadataPda,
-          productSnapshot: productSnapshotPda,
+          order: orderPda,
+          store: storePda,
+          buyerTokenAccount: buyerPaymentTokenAccount.address,
+          paymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
           buyer: creatorKeypair.publicKey,
-          buyFor: secondaryAuthorityPubkey,
-          payTo: loneProduct.payTo,
-          payToTokenAccount: payToAtaAddress,
-          purchaseTicket: purchaseTicketPda,
-          purchaseTicketPayment: purchaseTicketPaymentAddress,
-          purchaseTicketPaymentMint: paymentTokenMintAddress,
+          buyFor: creatorKeypair.publicKey,
           programMetadata: programMetadataPda,
           feeTokenAccount: feeTokenAccount.address,
           feeAccount: feeAccountPubkey,
+          feeTier: storeFeeTierPda,
         })
+        .remainingAccounts([
+          {pubkey: storeProductPda, isWritable: true, isSigner: false},
+          {pubkey: productSnapshotPda, isWritable: true, isSigner: false},
+          {pubkey: purchaseTicketPda, isWritable: true, isSigner: false},
+          {pubkey: purchaseTicketPaymentAddress, isWritable: true, isSigner: false},
+          {pubkey: payToAtaAddress, isWritable: true, isSigner: false},
+          {pubkey: pda("price_tiers", storeProductPda.toBuffer()), isWritable: false, isSigner: false},
+          {pubkey: pda("price_tiers", productSnapshotPda.toBuffer()), isWritable: true, isSigner: false},
+          {pubkey: storeProductBefore.priceFeed, isWritable: false, isSigner: false},
+          {pubkey: storeProductBefore.payoutSplit, isWritable: false, isSigner: false},
+        ])
         .instruction();
 
-      const tx = new anchor.web3.Transaction()
-      .add(createPurchaseTicketAtaIx)
-      .add(transferToPurchaseTicketAtaIx);
+      const tx = new anchor.web3.Transaction().add(createPurchaseTicketAtaIx, checkoutIx);
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair], {commitment: 'confirmed'});
 
-      if(!payToAta) {
-        console.info("payTo ATA doesn't exist. adding instruction to create it");
-        const createPayToAtaIx = spl_token.createAssociatedTokenAccountInstruction(
-          creatorKeypair.publicKey,
-          payToAtaAddress,
-          loneProduct.payTo,
-          paymentTokenMintAddress,
-          TOKEN_PROGRAM_ID,
-          ASSOCIATED_TOKEN_PROGRAM_ID);
-        
-        tx.add(createPayToAtaIx);
-      } else {
-        //console.log('payTo ATA: ', payToAta.address.toBase58());
-      }
+      const order = await program.account.order.fetch(orderPda, 'confirmed');
+      expect(order.bump).is.equal(orderPdaBump);
+      expect(order.buyer).is.eql(creatorKeypair.publicKey);
+      expect(order.store).is.eql(storePda);
+      expect(order.total.toNumber()).is.equal(orderTotal);
+      expect(order.tickets.length).is.equal(1);
+      expect(order.tickets[0]).is.eql(purchaseTicketPda);
 
-      tx.add(buyProductIx);
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.productSnapshotMetadata).is.eql(orderPda);
+      expect(purchaseTicket.productSnapshot).is.eql(productSnapshotPda);
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(quantity);
+      expect(purchaseTicket.price.toNumber()).is.equal(storeProductBefore.price.toNumber());
+      expect(purchaseTicket.fee.toNumber()).is.equal(0);
+      expect(purchaseTicket.arbiter).is.eql(storeArbiterKeypair.publicKey);
 
-      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
-      tx.feePayer = creatorKeypair.publicKey;  
+      const productSnapshot = await program.account.product.fetch(productSnapshotPda, 'confirmed');
+      expect(productSnapshot.isSnapshot).is.equal(true);
 
-      const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair], {commitment: 'finalized'});
-      //console.info('transaction signature: ', response);
-      
-      const productSnapshot = await program.account.product.fetch(productSnapshotPda);
-      expect(productSnapshot.bump).is.equal(loneProduct.bump);
-      expect(productSnapshot.status).is.equal(loneProduct.status);
-      expect(productSnapshot.creator).is.eql(loneProduct.creator);
-      expect(productSnapshot.authority).is.eql(loneProduct.authority);
-      expect(productSnapshot.secondaryAuthority).is.eql(loneProduct.secondaryAuthority);
-      expect(productSnapshot.id).is.equal(loneProduct.id); 
-      expect(productSnapshot.tag.toNumber()).is.equal(loneProduct.tag.toNumber()); 
-      expect(productSnapshot.isSnapshot).is.equal(true); 
-      //expect(productSnapshot.mint).is.eql(loneProduct.mint);
-      //expect(productSnapshot.payTo).is.eql(loneProduct.payTo);
-      expect(productSnapshot.store).is.eql(loneProduct.store); 
-      expect(productSnapshot.price.toNumber()).is.equal(loneProduct.price.toNumber());
-      expect(productSnapshot.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber());
-      expect(productSnapshot.redemptionType).is.equal(loneProduct.redemptionType);
-      expect(productSnapshot.name).is.equal(loneProduct.name);
-      expect(productSnapshot.description).is.equal(loneProduct.description)    
-      expect(productSnapshot.data).is.eql(loneProduct.data);
+      const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicketPaymentAddress, 'confirmed', TOKEN_PROGRAM_ID);
+      expect(purchaseTicketPayment.amount).is.equal(BigInt(orderTotal));
 
-      const loneProductAfterPurchase = await program.account.product.fetch(loneProductPda);
-      expect(loneProductAfterPurchase.isSnapshot).is.equal(false); 
-      expect(loneProductAfterPurchase.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber() - quantity);
+      const storeProductAfter = await program.account.product.fetch(storeProductPda, 'confirmed');
+      expect(storeProductAfter.inventory.toNumber()).is.equal(storeProductBefore.inventory.toNumber() - quantity);
 
-      const productSnapshotMetadata = await program.account.productSnapshotMetadata.fetch(productSnapshotMetadataPda);
-      expect(productSnapshotMetadata.bump).is.equal(productSnapshotMetadataPdaBump);
-      expect(productSnapshotMetadata.product).not.equal(loneProductPda);
-      expect(productSnapshotMetadata.productSnapshot).not.equal(productSnapshotPda);
-      expect(productSnapshotMetadata.nonce).is.equal(nonce);
+      checkoutPurchaseTicketPda = purchaseTicketPda;
+    });
 
-      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-      expect(purchaseTicket.bump).is.equal(purchaseTicketPdaBump);
-      expect(purchaseTicket.product).is.eql(loneProductPda);
-      expect(purchaseTicket.productSnapshotMetadata).is.eql(productSnapshotMetadataPda);
-      expect(purchaseTicket.productSnapshot).is.eql(productSnapshotPda);
-      expect(purchaseTicket.buyer).is.eql(creatorKeypair.publicKey);
-      expect(purchaseTicket.payTo).is.eql(loneProduct.payTo);
-      expect(purchaseTicket.authority).is.eql(secondaryAuthorityPubkey);
-      expect(purchaseTicket.redeemed.toNumber()).is.equal(quantity);
-      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(0);
-      expect(purchaseTicket.nonce).is.equal(nonce);
-      expect(purchaseTicket.payment).is.eql(purchaseTicketPaymentAddress);
-      expect(purchaseTicket.expiration.toNumber()).is.equal(0);
-      expect(purchaseTicket.expirationMinutesAfterRedemption).is.equal(0);
+    //checks out one of the store product, returning the item's accounts
+    const checkoutStoreProductItem = async (item: {agreedPrice?: anchor.BN, agreedExpirationTimestamp: anchor.BN}) => {
+      const storeProduct = await program.account.product.fetch(storeProductPda, 'confirmed');
+      const agreedPrice = item.agreedPrice ?? storeProduct.price;
+      const nonce = generateRandomU16();
+      const orderPda = pda("order", creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const itemIndex = Buffer.from(uIntToBytes(0,2,"setUint"));
+      const purchaseTicketPda = pda("purchase_ticket", orderPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), itemIndex);
+      const productSnapshotPda = pda("product_snapshot", orderPda.toBuffer(), itemIndex);
+      const priceTiersSnapshotPda = pda("price_tiers", productSnapshotPda.toBuffer());
+      await fundPaymentTokens(creatorKeypair.publicKey, agreedPrice.toNumber() + PURCHASE_TRANSACTION_FEE);
+
+      await program.methods
+        .checkout(nonce, [{
+          quantity: new anchor.BN(1),
+          agreedPrice,
+          agreedExpirationTimestamp: item.agreedExpirationTimestamp,
+          agreedExpirationMinutesAfterPurchase: storeProduct.expirationMinutesAfterPurchase,
+          agreedExpirationMinutesAfterRedemption: storeProduct.expirationMinutesAfterRedemption,
+        }], new anchor.BN(PURCHASE_TRANSACTION_FEE))
+        .accounts({
+          order: orderPda,
+          store: storePda,
+          buyerTokenAccount: paymentTokenAddress(creatorKeypair.publicKey),
+          paymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
+          buyer: creatorKeypair.publicKey,
+          buyFor: creatorKeypair.publicKey,
+          programMetadata: programMetadataPda,
+          feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+          feeAccount: feeAccountPubkey,
+          feeTier: storeFeeTierPda,
+        })
+        .remainingAccounts([
+          {pubkey: storeProductPda, isWritable: true, isSigner: false},
+          {pubkey: productSnapshotPda, isWritable: true, isSigner: false},
+          {pubkey: purchaseTicketPda, isWritable: true, isSigner: false},
+          {pubkey: paymentTokenAddress(purchaseTicketPda), isWritable: true, isSigner: false},
+          {pubkey: paymentTokenAddress(storeProduct.payTo), isWritable: true, isSigner: false},
+          {pubkey: pda("price_tiers", storeProductPda.toBuffer()), isWritable: false, isSigner: false},
+          {pubkey: priceTiersSnapshotPda, isWritable: true, isSigner: false},
+          {pubkey: storeProduct.priceFeed, isWritable: false, isSigner: false},
+          {pubkey: storeProduct.payoutSplit, isWritable: false, isSigner: false},
+        ])
+        .preInstructions([
+          spl_token.createAssociatedTokenAccountInstruction(
+            creatorKeypair.publicKey,
+            paymentTokenAddress(purchaseTicketPda),
+            purchaseTicketPda,
+            paymentTokenMintAddress,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID),
+        ])
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
 
-      const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicketPaymentAddress);
-      expect(purchaseTicketPayment.address).is.eql(purchaseTicketPaymentAddress);
-      expect(purchaseTicketPayment.mint).is.eql(paymentTokenMintAddress);
-      expect(purchaseTicketPayment.amount).is.equal(BigInt(0));
+      return {purchaseTicketPda, productSnapshotPda, priceTiersSnapshotPda};
+    }
 
-      const buyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
-      expect(buyerPaymentTokenAccount.address).is.eql(buyerPaymentTokenAddress);
-      expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
-      expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
- 
+    it("Checkout rejects an item whose agreed expiration doesn't match", async () => {
+      const storeProduct = await program.account.product.fetch(storeProductPda, 'confirmed');
+
+      await expectAnchorError(checkoutStoreProductItem({
+        agreedExpirationTimestamp: storeProduct.expirationTimestamp.add(new anchor.BN(1)),
+      }), "AgreedExpirationDoesntMatch");
     });
 
-    it("Update Lone Product", async () => {
-      const updatedExpirationTimestamp = Math.floor(new Date().getTime() / 1000) + (60 * 60 * 24 * 30); //add 30 days to today's date
-      const updatedProductName = productName + "-updated";
-      const updatedProductDescription = productDescription + "-updated";
-      
-      const updatedData = compress({displayName: updatedProductName, displayDescription: updatedProductDescription});
-      const updatedStatus = 0;//active
-      const updatedInventory = 3;
-      const updatedRedemptionType = 2; //ticketed
+    it("Checkout prices a product with price tiers at its tier and snapshots the tiers", async () => {
+      const storeProduct = await program.account.product.fetch(storeProductPda, 'confirmed');
+      const tieredPrice = storeProduct.price.toNumber() - Math.floor(storeProduct.price.toNumber() * 1000 / 10000);
+      const setPriceTiers = (tiers: {minQuantity: anchor.BN, basisPoints: number}[]) => program.methods
+        .setProductPriceTiers(tiers)
+        .accounts({
+          product: storeProductPda,
+          priceTiers: pda("price_tiers", storeProductPda.toBuffer()),
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
 
-      //this should succeed because the owner is correct
-      const txSuccess = await program.methods
-      .updateProduct(updatedStatus, new BN(updatedProductPrice), new BN(updatedInventory), updatedRedemptionType, 
-        new anchor.BN(updatedExpirationTimestamp), 0, 0, updatedProductName.toLowerCase(), updatedProductDescription.toLowerCase(), updatedData)
-      .accounts({
-        product: loneProductPda,
-        authority: creatorKeypair.publicKey,
-      })
-      .transaction();
+      await setPriceTiers([{ minQuantity: new anchor.BN(1), basisPoints: 1000 }]);
 
-      const txSucceeded = await anchor.web3.sendAndConfirmTransaction(provider.connection, txSuccess, [creatorKeypair]);
+      try {
+        await expectAnchorError(checkoutStoreProductItem({
+          agreedPrice: new anchor.BN(tieredPrice - 1),
+          agreedExpirationTimestamp: storeProduct.expirationTimestamp,
+        }), "PriceIsGreaterThanPayment");
+
+        const { purchaseTicketPda, productSnapshotPda, priceTiersSnapshotPda } = await checkoutStoreProductItem({
+          agreedPrice: new anchor.BN(tieredPrice),
+          agreedExpirationTimestamp: storeProduct.expirationTimestamp,
+        });
 
-      const updatedProduct = await program.account.product.fetch(loneProductPda);
-      expect(updatedProduct.bump).is.equal(loneProductPdaBump);
-      expect(updatedProduct.status).is.equal(updatedStatus);
-      expect(updatedProduct.creator).is.eql(creatorKeypair.publicKey);
-      expect(updatedProduct.authority).is.eql(creatorKeypair.publicKey);
-      expect(updatedProduct.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
-      expect(updatedProduct.id).is.equal(loneProductId);
-      expect(updatedProduct.tag.toNumber()).is.equal(0); 
-      //expect(updatedProduct.mint).is.eql(loneProductMintPda);
-      expect(updatedProduct.usableSnapshot).is.eql(PublicKey.default);
-      expect(updatedProduct.payTo).is.eql(payToAccountPubkey);
-      expect(updatedProduct.store).is.eql(PublicKey.default); 
-      expect(updatedProduct.price.toNumber()).is.equal(updatedProductPrice);
-      expect(updatedProduct.inventory.toNumber()).is.equal(updatedInventory);
-      expect(updatedProduct.redemptionType).is.equal(updatedRedemptionType);
-      expect(updatedProduct.expirationMinutesAfterPurchase).is.equal(0);
-      expect(updatedProduct.expirationMinutesAfterRedemption).is.equal(0);
-      expect(updatedProduct.expirationTimestamp.toNumber()).is.equal(updatedExpirationTimestamp);
-      expect(updatedProduct.name).is.equal(updatedProductName.toLowerCase());
-      expect(updatedProduct.description).is.equal(updatedProductDescription.toLowerCase());
-      expect(updatedProduct.data).is.eql(updatedData);
+        const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+        expect(purchaseTicket.price.toNumber()).is.equal(tieredPrice);
+
+        const priceTiersSnapshot = await program.account.priceTiers.fetch(priceTiersSnapshotPda, 'confirmed');
+        expect(priceTiersSnapshot.product).is.eql(productSnapshotPda);
+        expect(priceTiersSnapshot.tiers.length).is.equal(1);
+      }
+      finally {
+        await setPriceTiers([]);
+      }
     });
 
-    
-    describe("[Lone Product - Ticket Tests]", () => {      
-      const purchaseNonce = generateRandomU16();
-      const purchaseQuantity = 3;
-      const purchaseAmountRequired = (updatedProductPrice + PURCHASE_TRANSACTION_FEE) * purchaseQuantity;
-      const [loneProductTicketTakerPda, loneProductTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
+    //the checkout ticket, bought once the store had an arbiter
+    const resolveCheckoutDispute = async (buyerShareBasisPoints: number, arbiter: Keypair) => {
+      const purchaseTicket = await program.account.purchaseTicket.fetch(checkoutPurchaseTicketPda, 'confirmed');
+      const payToTokenAddress = await fundPaymentTokens(purchaseTicket.payTo, 0);
+
+      return program.methods
+        .resolveDispute(buyerShareBasisPoints)
+        .accounts({
+          dispute: pda("dispute", checkoutPurchaseTicketPda.toBuffer()),
+          openedBy: creatorKeypair.publicKey,
+          ticket: checkoutPurchaseTicketPda,
+          ticketPayment: purchaseTicket.payment,
+          paymentMint: paymentTokenMintAddress,
+          buyerTokenAccount: paymentTokenAddress(purchaseTicket.authority),
+          ticketAuthority: purchaseTicket.authority,
+          payToTokenAccount: payToTokenAddress,
+          payTo: purchaseTicket.payTo,
+          payoutSplit: purchaseTicket.payoutSplit,
+          referrerTokenAccount: payToTokenAddress,
+          redemption: anchor.web3.SystemProgram.programId,
+          arbiter: arbiter.publicKey,
+        })
+        .signers([arbiter])
+        .rpc({commitment: 'confirmed'});
+    }
+
+    it("Open dispute", async () => {
+      const purchaseTicketBefore = await program.account.purchaseTicket.fetch(checkoutPurchaseTicketPda, 'confirmed');
+      const [disputePda, disputePdaBump] = PublicKey.findProgramAddressSync(
         [
-          anchor.utils.bytes.utf8.encode("product_taker"),
-          loneProductPda.toBuffer(),
-          ticketTakerKeypair.publicKey.toBuffer(),        
+          anchor.utils.bytes.utf8.encode("dispute"),
+          checkoutPurchaseTicketPda.toBuffer(),
         ], program.programId);
-      const [productSnapshotMetadataPda, productSnapshotMetadataPdaBump] = PublicKey.findProgramAddressSync(
+
+      const openTx = await program.methods
+        .openDispute()
+        .accounts({
+          dispute: disputePda,
+          ticket: checkoutPurchaseTicketPda,
+          store: storePda,
+          redemption: anchor.web3.SystemProgram.programId,
+          openedBy: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, openTx, [creatorKeypair], {commitment: 'confirmed'});
+
+      const dispute = await program.account.dispute.fetch(disputePda, 'confirmed');
+      expect(dispute.bump).is.equal(disputePdaBump);
+      expect(dispute.ticket).is.eql(checkoutPurchaseTicketPda);
+      expect(dispute.redemption).is.eql(anchor.web3.SystemProgram.programId);
+      expect(dispute.arbiter).is.eql(storeArbiterKeypair.publicKey);
+      expect(dispute.quantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber());
+
+      const frozenTicket = await program.account.purchaseTicket.fetch(checkoutPurchaseTicketPda, 'confirmed');
+      expect(frozenTicket.dispute).is.eql(disputePda);
+    });
+
+    it("Disputed ticket can't be cancelled, redeemed or transferred", async () => {
+      const purchaseTicket = await program.account.purchaseTicket.fetch(checkoutPurchaseTicketPda, 'confirmed');
+      const redemptionNonce = generateRandomU32();
+      const transferNonce = generateRandomU16();
+      const destinationTicketPda = pda("purchase_ticket", purchaseTicket.productSnapshotMetadata.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(transferNonce,2,"setUint")));
+
+      await expectAnchorError(program.methods
+        .cancelTicket(new anchor.BN(1))
+        .accounts({
+          product: purchaseTicket.product,
+          ticket: checkoutPurchaseTicketPda,
+          ticketPayment: purchaseTicket.payment,
+          paymentReturn: paymentTokenAddress(creatorKeypair.publicKey),
+          paymentMint: paymentTokenMintAddress,
+          ticketAuthority: creatorKeypair.publicKey,
+          programMetadata: programMetadataPda,
+          acceptedMint: acceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc(), "TicketIsDisputed");
+
+      await expectAnchorError(program.methods
+        .initiateRedemption(redemptionNonce, new anchor.BN(1), 0)
+        .accounts({
+          redemption: pda("redemption", checkoutPurchaseTicketPda.toBuffer(), Buffer.from(uIntToBytes(redemptionNonce,4,"setUint"))),
+          purchaseTicket: checkoutPurchaseTicketPda,
+          purchaseTicketAuthority: creatorKeypair.publicKey,
+          purchaseTicketPayment: purchaseTicket.payment,
+          purchaseTicketPaymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          acceptedMint: acceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc(), "TicketIsDisputed");
+
+      await expectAnchorError(program.methods
+        .transferTicket(transferNonce, new anchor.BN(1))
+        .accounts({
+          destinationTicket: destinationTicketPda,
+          destinationTicketPayment: paymentTokenAddress(destinationTicketPda),
+          destinationTicketAuthority: secondaryAuthorityPubkey,
+          sourceTicket: checkoutPurchaseTicketPda,
+          sourceTicketPayment: purchaseTicket.payment,
+          sourceTicketAuthority: creatorKeypair.publicKey,
+          productSnapshot: purchaseTicket.productSnapshot,
+          paymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          acceptedMint: acceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc(), "TicketIsDisputed");
+    });
+
+    it("Resolve dispute is rejected for anyone but the ticket's arbiter", async () => {
+      await expectAnchorError(resolveCheckoutDispute(5000, creatorKeypair), "IncorrectAuthority");
+    });
+
+    it("Resolve dispute", async () => {
+      const purchaseTicketBefore = await program.account.purchaseTicket.fetch(checkoutPurchaseTicketPda, 'confirmed');
+      const disputedAmount = purchaseTicketBefore.price.toNumber() * purchaseTicketBefore.remainingQuantity.toNumber();
+      const buyerShareBasisPoints = 5000;
+      const buyerShare = Math.floor(disputedAmount * buyerShareBasisPoints / 10000);
+      const disputePda = pda("dispute", checkoutPurchaseTicketPda.toBuffer());
+      const buyerBalanceBefore = await paymentTokenBalance(purchaseTicketBefore.authority);
+      const payToBalanceBefore = await paymentTokenBalance(purchaseTicketBefore.payTo);
+
+      await resolveCheckoutDispute(buyerShareBasisPoints, storeArbiterKeypair);
+
+      const purchaseTicketAfter = await program.account.purchaseTicket.fetch(checkoutPurchaseTicketPda, 'confirmed');
+      expect(purchaseTicketAfter.dispute).is.eql(PublicKey.default);
+      expect(purchaseTicketAfter.remainingQuantity.toNumber()).is.equal(0);
+
+      //no payout split or referrer on the checkout ticket, so the merchant's share all goes to pay_to
+      expect(await paymentTokenBalance(purchaseTicketBefore.authority)).is.equal(buyerBalanceBefore + BigInt(buyerShare));
+      expect(await paymentTokenBalance(purchaseTicketBefore.payTo)).is.equal(payToBalanceBefore + BigInt(disputedAmount - buyerShare));
+
+      const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicketBefore.payment, 'confirmed', TOKEN_PROGRAM_ID);
+      expect(purchaseTicketPayment.amount).is.equal(BigInt(0));
+      expect(await provider.connection.getAccountInfo(disputePda)).is.null;
+    });
+
+    it("Gifted ticket can't be disputed", async () => {
+      const storeProduct = await program.account.product.fetch(storeProductPda, 'confirmed');
+
+      //restocked for one more purchase
+      await program.methods
+        .updateProduct(storeProduct.status, storeProduct.price, storeProduct.inventory.add(new anchor.BN(1)), storeProduct.redemptionType,
+          storeProduct.expirationTimestamp, storeProduct.expirationMinutesAfterPurchase, storeProduct.expirationMinutesAfterRedemption,
+          storeProduct.name, storeProduct.description, storeProduct.data)
+        .accounts({
+          product: storeProductPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      const { purchaseTicketPda, productSnapshotPda } = await buyTestProduct(storeProductPda);
+      const giftPda = pda("gift", purchaseTicketPda.toBuffer());
+
+      await program.methods
+        .createGift(Array.from(createHash('sha256').update("secret").update(Keypair.generate().publicKey.toBuffer()).digest()), 60)
+        .accounts({
+          gift: giftPda,
+          ticket: purchaseTicketPda,
+          productSnapshot: productSnapshotPda,
+          programMetadata: programMetadataPda,
+          giver: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      await expectAnchorError(program.methods
+        .openDispute()
+        .accounts({
+          dispute: pda("dispute", purchaseTicketPda.toBuffer()),
+          ticket: purchaseTicketPda,
+          store: storePda,
+          redemption: anchor.web3.SystemProgram.programId,
+          openedBy: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc(), "TicketIsGifted");
+    });
+
+  });//store tests
+
+  describe("[Lone Product Tests]", () => {
+    const loneProductId = generateRandomU32();
+    const updatedProductPrice = 200000;
+    let [loneProductPda, loneProductPdaBump] = PublicKey.findProgramAddressSync(
+      [
+        anchor.utils.bytes.utf8.encode("product"),
+        creatorKeypair.publicKey.toBuffer(),
+        Buffer.from(uIntToBytes(loneProductId,4,"setUint"))
+      ], program.programId);
+
+    it("Create Lone Product - Immediate Redemption", async () => {    
+      //const productMintDecimals = 3;
+      const data = compress({displayName: productName, displayDescription: productDescription});
+      const redemptionType = 1; //immediate
+      const loneProductStatus = 0;
+
+      const tx = await program.methods
+      .createProduct(loneProductId, loneProductStatus, productPrice, productInventory, redemptionType,
+        new anchor.BN(0), 0, 0,
+        productName.toLowerCase(), productDescription.toLowerCase(), data)
+      .accounts({
+        //mint: loneProductMintPda,
+        product: loneProductPda,
+        creator: creatorKeypair.publicKey,
+        authority: creatorKeypair.publicKey,
+        secondaryAuthority: secondaryAuthorityPubkey,
+        payTo: payToAccountPubkey,
+        acceptedMint: acceptedMintPda,
+      })
+      .transaction();
+    
+      const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+      const createdProduct = await program.account.product.fetch(loneProductPda);
+      expect(createdProduct.bump).is.equal(loneProductPdaBump);
+      expect(createdProduct.status).is.equal(loneProductStatus);
+      expect(createdProduct.creator).is.eql(creatorKeypair.publicKey);
+      expect(createdProduct.authority).is.eql(creatorKeypair.publicKey);
+      expect(createdProduct.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
+      expect(createdProduct.id).is.equal(loneProductId); 
+      expect(createdProduct.tag.toNumber()).is.equal(0); 
+      expect(createdProduct.isSnapshot).is.equal(false); 
+      //expect(createdProduct.mint).is.eql(loneProductMintPda);
+      expect(createdProduct.payTo).is.eql(payToAccountPubkey);
+      expect(createdProduct.store).is.eql(PublicKey.default); 
+      expect(createdProduct.price.toNumber()).is.equal(productPrice.toNumber());
+      expect(createdProduct.inventory.toNumber()).is.equal(productInventory.toNumber());
+      expect(createdProduct.redemptionType).is.equal(redemptionType);
+      expect(createdProduct.expirationMinutesAfterPurchase).is.equal(0);
+      expect(createdProduct.expirationMinutesAfterRedemption).is.equal(0);
+      expect(createdProduct.expirationTimestamp.toNumber()).is.equal(0);
+      expect(createdProduct.name).is.equal(productName.toLowerCase());
+      expect(createdProduct.description).is.equal(productDescription.toLowerCase())    
+      expect(createdProduct.data).is.eql(data); 
+      expect(createdProduct.paymentMint).is.eql(paymentTokenMintAddress);
+      expect(createdProduct.royaltyBasisPoints).is.equal(0);
+    });
+
+    it("Create and fund buyer ATA for payment token - Immediate Redemption", async() => {
+      const buyerPaymentTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        creatorKeypair,
+        paymentTokenMintAddress,
+        creatorKeypair.publicKey,
+        false,
+        'finalized',
+        {commitment:'finalized'},
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID);
+
+
+        //console.log('buyer token account: ', buyerPaymentTokenAccount.address.toBase58());
+        expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
+        expect(buyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
+        expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
+
+        //console.log(`funding creator payment account with ${paymentTokensRequired} tokens from mint ${paymentTokenMintAddress}`);
+        const paymentTokenAirdropTx = await tokenFaucetProgram.methods
+          .executeAirdrop(new anchor.BN(paymentTokensRequired))
+          .accounts({
+            signer: creatorKeypair.publicKey,
+            mint: paymentTokenMintAddress,
+            recipient: buyerPaymentTokenAccount.address,
+          })
+          .transaction();
+
+        const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, paymentTokenAirdropTx, [creatorKeypair], {commitment: 'finalized'});
+        const updatedBuyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAccount.address, 'finalized', TOKEN_PROGRAM_ID);
+        expect(updatedBuyerPaymentTokenAccount.address).is.eql(buyerPaymentTokenAccount.address);
+        expect(updatedBuyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
+        expect(updatedBuyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
+        expect(updatedBuyerPaymentTokenAccount.amount).is.equal(BigInt(paymentTokensRequired));
+    });
+
+
+    it("Buy Lone Product - Immediate Redemption", async () => {
+      const quantity = 1;
+      let buyForPubkey = creatorKeypair.publicKey;
+      const loneProduct = await program.account.product.fetch(loneProductPda);
+      const buyerPaymentTokenAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, creatorKeypair.publicKey, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+      const payToAtaAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, loneProduct.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+      
+      const nonce = generateRandomU16();
+      const feeTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        creatorKeypair,
+        paymentTokenMintAddress,
+        feeAccountPubkey,
+        false,
+        'confirmed',
+        {commitment:'confirmed'},
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID);
+
+      //console.log('buyer payment token address: ', buyerPaymentTokenAddress.toBase58());
+      //console.log('payTo ATA address: ', payToAtaAddress.toBase58());
+      
+
+      const [productSnapshotMetadataPda, productSnapshotMetadataPdaBump] = PublicKey.findProgramAddressSync(
         [
           anchor.utils.bytes.utf8.encode("product_snapshot_metadata"),
           loneProductPda.toBuffer(),
           creatorKeypair.publicKey.toBuffer(),
-          Buffer.from(uIntToBytes(purchaseNonce,2,"setUint")),
+          Buffer.from(uIntToBytes(nonce,2,"setUint")),
+        ], program.programId);
+
+      const [productSnapshotPda, productSnapshotPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("product_snapshot"),
+          productSnapshotMetadataPda.toBuffer(),
+        ], program.programId);
+
+      const [priceTiersPda] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("price_tiers"),
+          loneProductPda.toBuffer(),
+        ], program.programId);
+
+      const [priceTiersSnapshotPda] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("price_tiers"),
+          productSnapshotPda.toBuffer(),
         ], program.programId);
+
       const [purchaseTicketPda, purchaseTicketPdaBump] = PublicKey.findProgramAddressSync(
         [
           anchor.utils.bytes.utf8.encode("purchase_ticket"),
           productSnapshotMetadataPda.toBuffer(),
           creatorKeypair.publicKey.toBuffer(),
-          Buffer.from(uIntToBytes(purchaseNonce,2,"setUint"))
+          Buffer.from(uIntToBytes(nonce,2,"setUint"))
         ], program.programId);
-  
-      it("Create and fund ticketed product buyer", async()=>{
-        const buyerPaymentTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
-          provider.connection,
-          creatorKeypair,
-          paymentTokenMintAddress,
-          creatorKeypair.publicKey,
-          false,
-          'finalized',
-          {commitment:'finalized'},
-          TOKEN_PROGRAM_ID,
-          ASSOCIATED_TOKEN_PROGRAM_ID);  
-  
-          expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
-          expect(buyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
-          expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
-  
-          //console.log(`funding creator payment account with ${amountRequiredToPurchaseLoneProduct} tokens from mint ${paymentTokenMintAddress}`);
-          const paymentTokenAirdropTx = await tokenFaucetProgram.methods
-            .executeAirdrop(new anchor.BN(purchaseAmountRequired))
-            .accounts({
-              signer: creatorKeypair.publicKey,
-              mint: paymentTokenMintAddress,
-              recipient: buyerPaymentTokenAccount.address,
-            })
-            .transaction();
-  
-          const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, paymentTokenAirdropTx, [creatorKeypair], {commitment: 'finalized'});
-          const updatedBuyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAccount.address, 'confirmed', TOKEN_PROGRAM_ID);
-          expect(updatedBuyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
-          expect(updatedBuyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
-          expect(updatedBuyerPaymentTokenAccount.amount).is.equal(BigInt(purchaseAmountRequired));  
-      });
-  
-  
-      it("Buy Lone Product - Ticketed Redemption", async () => {
-        const loneProduct = await program.account.product.fetch(loneProductPda);
-        const buyerPaymentTokenAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, creatorKeypair.publicKey, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
-        const payToAtaAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, loneProduct.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+    
+      const purchaseTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
+        paymentTokenMintAddress,
+        purchaseTicketPda,
+        true,
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID);
+      //console.log('purchaseTicketPaymentAddress: ', purchaseTicketPaymentAddress.toBase58());
+
+      const createPurchaseTicketAtaIx = spl_token.createAssociatedTokenAccountInstruction(
+        creatorKeypair.publicKey,
+        purchaseTicketPaymentAddress,
+        purchaseTicketPda,
+        paymentTokenMintAddress,
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID);
         
-        const feeTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
-          provider.connection,
-          creatorKeypair,
-          paymentTokenMintAddress,
-          feeAccountPubkey,
-          false,
-          'confirmed',
-          {commitment:'confirmed'},
-          TOKEN_PROGRAM_ID,
-          ASSOCIATED_TOKEN_PROGRAM_ID);
-  
-        //console.log('buyer payment token address: ', buyerPaymentTokenAddress.toBase58());
-        //console.log('payTo ATA address: ', payToAtaAddress.toBase58());        
-        const [productSnapshotPda, productSnapshotPdaBump] = PublicKey.findProgramAddressSync(
-          [
-            anchor.utils.bytes.utf8.encode("product_snapshot"),
-            productSnapshotMetadataPda.toBuffer(),
-          ], program.programId);
-  
-        const purchaseTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
+      const transferToPurchaseTicketAtaIx = spl_token.createTransferInstruction(
+        buyerPaymentTokenAddress,
+        purchaseTicketPaymentAddress,
+        creatorKeypair.publicKey,
+        paymentTokensRequired,
+        [],
+        TOKEN_PROGRAM_ID,
+      );
+
+      let payToAta: Account;
+      try {
+        payToAta = await spl_token.getAccount(provider.connection, payToAtaAddress, 'confirmed', TOKEN_PROGRAM_ID);
+      } catch(ex) {
+      }
+
+      const buyProductIx = await program.methods
+        .buyProduct(nonce, new anchor.BN(quantity), loneProduct.price, new anchor.BN(PURCHASE_TRANSACTION_FEE),
+          loneProduct.expirationTimestamp, loneProduct.expirationMinutesAfterPurchase, loneProduct.expirationMinutesAfterRedemption)
+        .accounts({
+          product: loneProductPda,
+          productSnapshotMetadata: productSnapshotMetadataPda,
+          productSnapshot: productSnapshotPda,
+          buyer: creatorKeypair.publicKey,
+          buyFor: secondaryAuthorityPubkey,
+          payTo: loneProduct.payTo,
+          payToTokenAccount: payToAtaAddress,
+          purchaseTicket: purchaseTicketPda,
+          purchaseTicketPayment: purchaseTicketPaymentAddress,
+          purchaseTicketPaymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          feeTokenAccount: feeTokenAccount.address,
+          feeAccount: feeAccountPubkey,
+          feeTier: creatorFeeTierPda,
+          referral: anchor.web3.SystemProgram.programId,
+          referrerTokenAccount: payToAtaAddress,
+          acceptedMint: acceptedMintPda,
+          priceFeed: anchor.web3.SystemProgram.programId,
+          store: anchor.web3.SystemProgram.programId,
+          payoutSplit: anchor.web3.SystemProgram.programId,
+          coupon: anchor.web3.SystemProgram.programId,
+          couponUsage: anchor.web3.SystemProgram.programId,
+          priceTiers: priceTiersPda,
+          priceTiersSnapshot: priceTiersSnapshotPda,
+        })
+        .instruction();
+
+      const tx = new anchor.web3.Transaction()
+      .add(createPurchaseTicketAtaIx)
+      .add(transferToPurchaseTicketAtaIx);
+
+      if(!payToAta) {
+        console.info("payTo ATA doesn't exist. adding instruction to create it");
+        const createPayToAtaIx = spl_token.createAssociatedTokenAccountInstruction(
+          creatorKeypair.publicKey,
+          payToAtaAddress,
+          loneProduct.payTo,
           paymentTokenMintAddress,
-          purchaseTicketPda,
-          true,
           TOKEN_PROGRAM_ID,
           ASSOCIATED_TOKEN_PROGRAM_ID);
+        
+        tx.add(createPayToAtaIx);
+      } else {
+        //console.log('payTo ATA: ', payToAta.address.toBase58());
+      }
+
+      tx.add(buyProductIx);
+
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      tx.feePayer = creatorKeypair.publicKey;  
+
+      const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair], {commitment: 'finalized'});
+      //console.info('transaction signature: ', response);
+      
+      const productSnapshot = await program.account.product.fetch(productSnapshotPda);
+      expect(productSnapshot.bump).is.equal(loneProduct.bump);
+      expect(productSnapshot.status).is.equal(loneProduct.status);
+      expect(productSnapshot.creator).is.eql(loneProduct.creator);
+      expect(productSnapshot.authority).is.eql(loneProduct.authority);
+      expect(productSnapshot.secondaryAuthority).is.eql(loneProduct.secondaryAuthority);
+      expect(productSnapshot.id).is.equal(loneProduct.id); 
+      expect(productSnapshot.tag.toNumber()).is.equal(loneProduct.tag.toNumber()); 
+      expect(productSnapshot.isSnapshot).is.equal(true); 
+      //expect(productSnapshot.mint).is.eql(loneProduct.mint);
+      //expect(productSnapshot.payTo).is.eql(loneProduct.payTo);
+      expect(productSnapshot.store).is.eql(loneProduct.store); 
+      expect(productSnapshot.price.toNumber()).is.equal(loneProduct.price.toNumber());
+      expect(productSnapshot.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber());
+      expect(productSnapshot.redemptionType).is.equal(loneProduct.redemptionType);
+      expect(productSnapshot.name).is.equal(loneProduct.name);
+      expect(productSnapshot.description).is.equal(loneProduct.description)    
+      expect(productSnapshot.data).is.eql(loneProduct.data);
+
+      const loneProductAfterPurchase = await program.account.product.fetch(loneProductPda);
+      expect(loneProductAfterPurchase.isSnapshot).is.equal(false); 
+      expect(loneProductAfterPurchase.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber() - quantity);
+
+      const productSnapshotMetadata = await program.account.productSnapshotMetadata.fetch(productSnapshotMetadataPda);
+      expect(productSnapshotMetadata.bump).is.equal(productSnapshotMetadataPdaBump);
+      expect(productSnapshotMetadata.product).not.equal(loneProductPda);
+      expect(productSnapshotMetadata.productSnapshot).not.equal(productSnapshotPda);
+      expect(productSnapshotMetadata.nonce).is.equal(nonce);
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+      expect(purchaseTicket.bump).is.equal(purchaseTicketPdaBump);
+      expect(purchaseTicket.product).is.eql(loneProductPda);
+      expect(purchaseTicket.productSnapshotMetadata).is.eql(productSnapshotMetadataPda);
+      expect(purchaseTicket.productSnapshot).is.eql(productSnapshotPda);
+      expect(purchaseTicket.buyer).is.eql(creatorKeypair.publicKey);
+      expect(purchaseTicket.payTo).is.eql(loneProduct.payTo);
+      expect(purchaseTicket.authority).is.eql(secondaryAuthorityPubkey);
+      expect(purchaseTicket.redeemed.toNumber()).is.equal(quantity);
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(0);
+      expect(purchaseTicket.nonce).is.equal(nonce);
+      expect(purchaseTicket.payment).is.eql(purchaseTicketPaymentAddress);
+      expect(purchaseTicket.expiration.toNumber()).is.equal(0);
+      expect(purchaseTicket.expirationMinutesAfterRedemption).is.equal(0);
+      expect(purchaseTicket.fee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+
+      const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicketPaymentAddress);
+      expect(purchaseTicketPayment.address).is.eql(purchaseTicketPaymentAddress);
+      expect(purchaseTicketPayment.mint).is.eql(paymentTokenMintAddress);
+      expect(purchaseTicketPayment.amount).is.equal(BigInt(0));
+
+      const buyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
+      expect(buyerPaymentTokenAccount.address).is.eql(buyerPaymentTokenAddress);
+      expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
+      expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
+ 
+    });
+
+    it("Update Lone Product", async () => {
+      const updatedExpirationTimestamp = Math.floor(new Date().getTime() / 1000) + (60 * 60 * 24 * 30); //add 30 days to today's date
+      const updatedProductName = productName + "-updated";
+      const updatedProductDescription = productDescription + "-updated";
+      
+      const updatedData = compress({displayName: updatedProductName, displayDescription: updatedProductDescription});
+      const updatedStatus = 0;//active
+      const updatedInventory = 3;
+      const updatedRedemptionType = 2; //ticketed
+
+      //this should succeed because the owner is correct
+      const txSuccess = await program.methods
+      .updateProduct(updatedStatus, new BN(updatedProductPrice), new BN(updatedInventory), updatedRedemptionType, 
+        new anchor.BN(updatedExpirationTimestamp), 0, 0, updatedProductName.toLowerCase(), updatedProductDescription.toLowerCase(), updatedData)
+      .accounts({
+        product: loneProductPda,
+        authority: creatorKeypair.publicKey,
+      })
+      .transaction();
+
+      const txSucceeded = await anchor.web3.sendAndConfirmTransaction(provider.connection, txSuccess, [creatorKeypair]);
+
+      const updatedProduct = await program.account.product.fetch(loneProductPda);
+      expect(updatedProduct.bump).is.equal(loneProductPdaBump);
+      expect(updatedProduct.status).is.equal(updatedStatus);
+      expect(updatedProduct.creator).is.eql(creatorKeypair.publicKey);
+      expect(updatedProduct.authority).is.eql(creatorKeypair.publicKey);
+      expect(updatedProduct.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
+      expect(updatedProduct.id).is.equal(loneProductId);
+      expect(updatedProduct.tag.toNumber()).is.equal(0); 
+      //expect(updatedProduct.mint).is.eql(loneProductMintPda);
+      expect(updatedProduct.usableSnapshot).is.eql(PublicKey.default);
+      expect(updatedProduct.payTo).is.eql(payToAccountPubkey);
+      expect(updatedProduct.store).is.eql(PublicKey.default); 
+      expect(updatedProduct.price.toNumber()).is.equal(updatedProductPrice);
+      expect(updatedProduct.inventory.toNumber()).is.equal(updatedInventory);
+      expect(updatedProduct.redemptionType).is.equal(updatedRedemptionType);
+      expect(updatedProduct.expirationMinutesAfterPurchase).is.equal(0);
+      expect(updatedProduct.expirationMinutesAfterRedemption).is.equal(0);
+      expect(updatedProduct.expirationTimestamp.toNumber()).is.equal(updatedExpirationTimestamp);
+      expect(updatedProduct.name).is.equal(updatedProductName.toLowerCase());
+      expect(updatedProduct.description).is.equal(updatedProductDescription.toLowerCase());
+      expect(updatedProduct.data).is.eql(updatedData);
+    });
+
+    it("Set Lone Product resale policy", async () => {
+      const resalePolicy = {
+        transfersDisabled: false,
+        maxResaleBasisPoints: 20000, //2x what the ticket was bought for
+        cooldownMinutes: 0,
+      };
+
+      const tx = await program.methods
+        .setProductResalePolicy(resalePolicy)
+        .accounts({
+          product: loneProductPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+      const updatedProduct = await program.account.product.fetch(loneProductPda);
+      expect(updatedProduct.resalePolicy.transfersDisabled).is.equal(resalePolicy.transfersDisabled);
+      expect(updatedProduct.resalePolicy.maxResaleBasisPoints).is.equal(resalePolicy.maxResaleBasisPoints);
+      expect(updatedProduct.resalePolicy.cooldownMinutes).is.equal(resalePolicy.cooldownMinutes);
+    });
+
+    it("Set Lone Product price tiers", async () => {
+      const [priceTiersPda, priceTiersPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("price_tiers"),
+          loneProductPda.toBuffer(),
+        ], program.programId);
+      const tiers = [
+        { minQuantity: new anchor.BN(10), basisPoints: 800 },
+        { minQuantity: new anchor.BN(100), basisPoints: 1500 },
+      ];
+
+      const tx = await program.methods
+        .setProductPriceTiers(tiers)
+        .accounts({
+          product: loneProductPda,
+          priceTiers: priceTiersPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+      const priceTiers = await program.account.priceTiers.fetch(priceTiersPda);
+      expect(priceTiers.bump).is.equal(priceTiersPdaBump);
+      expect(priceTiers.product).is.eql(loneProductPda);
+      expect(priceTiers.tiers.length).is.equal(tiers.length);
+      expect(priceTiers.tiers[0].minQuantity.toNumber()).is.equal(10);
+      expect(priceTiers.tiers[0].basisPoints).is.equal(800);
+      expect(priceTiers.tiers[1].minQuantity.toNumber()).is.equal(100);
+      expect(priceTiers.tiers[1].basisPoints).is.equal(1500);
+    });
+
+    it("Set Lone Product layaway policy", async () => {
+      const layawayPolicy = {
+        paymentWindowMinutes: 60 * 24 * 30,
+        minDepositBasisPoints: 2500,
+        forfeitBasisPoints: 1000,
+      };
+
+      const tx = await program.methods
+        .setProductLayawayPolicy(layawayPolicy)
+        .accounts({
+          product: loneProductPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+      const updatedProduct = await program.account.product.fetch(loneProductPda);
+      expect(updatedProduct.layawayPolicy.paymentWindowMinutes).is.equal(layawayPolicy.paymentWindowMinutes);
+      expect(updatedProduct.layawayPolicy.minDepositBasisPoints).is.equal(layawayPolicy.minDepositBasisPoints);
+      expect(updatedProduct.layawayPolicy.forfeitBasisPoints).is.equal(layawayPolicy.forfeitBasisPoints);
+    });
+
+    
+    describe("[Lone Product - Ticket Tests]", () => {      
+      const purchaseNonce = generateRandomU16();
+      const purchaseQuantity = 3;
+      const purchaseAmountRequired = (updatedProductPrice + PURCHASE_TRANSACTION_FEE) * purchaseQuantity;
+      const [loneProductTicketTakerPda, loneProductTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("product_taker"),
+          loneProductPda.toBuffer(),
+          ticketTakerKeypair.publicKey.toBuffer(),        
+        ], program.programId);
+      const [productSnapshotMetadataPda, productSnapshotMetadataPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("product_snapshot_metadata"),
+          loneProductPda.toBuffer(),
+          creatorKeypair.publicKey.toBuffer(),
+          Buffer.from(uIntToBytes(purchaseNonce,2,"setUint")),
+        ], program.programId);
+      const [purchaseTicketPda, purchaseTicketPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("purchase_ticket"),
+          productSnapshotMetadataPda.toBuffer(),
+          creatorKeypair.publicKey.toBuffer(),
+          Buffer.from(uIntToBytes(purchaseNonce,2,"setUint"))
+        ], program.programId);
   
-        //console.log('purchaseTicketPaymentAddress: ', purchaseTicketPaymentAddress.toBase58());  
-        const createPurchaseTicketAtaIx = spl_token.createAssociatedTokenAccountInstruction(
-          creatorKeypair.publicKey,
-          purchaseTicketPaymentAddress,
-          purchaseTicketPda,
+      it("Create and fund ticketed product buyer", async()=>{
+        const buyerPaymentTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          creatorKeypair,
           paymentTokenMintAddress,
-          TOKEN_PROGRAM_ID,
-          ASSOCIATED_TOKEN_PROGRAM_ID);  
-          
-        const transferToPurchaseTicketAtaIx = spl_token.createTransferInstruction(
-          buyerPaymentTokenAddress,
-          purchaseTicketPaymentAddress,
           creatorKeypair.publicKey,
-          purchaseAmountRequired,
-          [],
+          false,
+          'finalized',
+          {commitment:'finalized'},
           TOKEN_PROGRAM_ID,
-        );
+          ASSOCIATED_TOKEN_PROGRAM_ID);  
   
-        let payToAta: Account;
-        try {
-          payToAta = await spl_token.getAccount(provider.connection, payToAtaAddress, 'confirmed', TOKEN_PROGRAM_ID);
-        } catch(ex) {
+          expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
+          expect(buyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
+          expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
+  
+          //console.log(`funding creator payment account with ${amountRequiredToPurchaseLoneProduct} tokens from mint ${paymentTokenMintAddress}`);
+          const paymentTokenAirdropTx = await tokenFaucetProgram.methods
+            .executeAirdrop(new anchor.BN(purchaseAmountRequired))
+            .accounts({
+              signer: creatorKeypair.publicKey,
+              mint: paymentTokenMintAddress,
+              recipient: buyerPaymentTokenAccount.address,
+            })
+            .transaction();
+  
+          const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, paymentTokenAirdropTx, [creatorKeypair], {commitment: 'finalized'});
+          const updatedBuyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAccount.address, 'confirmed', TOKEN_PROGRAM_ID);
+          expect(updatedBuyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
+          expect(updatedBuyerPaymentTokenAccount.owner).is.eql(creatorKeypair.publicKey);
+          expect(updatedBuyerPaymentTokenAccount.amount).is.equal(BigInt(purchaseAmountRequired));  
+      });
+  
+  
+      it("Buy Lone Product - Ticketed Redemption", async () => {
+        const loneProduct = await program.account.product.fetch(loneProductPda);
+        const buyerPaymentTokenAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, creatorKeypair.publicKey, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+        const payToAtaAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, loneProduct.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+        
+        const feeTokenAccount = await spl_token.getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          creatorKeypair,
+          paymentTokenMintAddress,
+          feeAccountPubkey,
+          false,
+          'confirmed',
+          {commitment:'confirmed'},
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID);
+  
+        //console.log('buyer payment token address: ', buyerPaymentTokenAddress.toBase58());
+        //console.log('payTo ATA address: ', payToAtaAddress.toBase58());        
+        const [productSnapshotPda, productSnapshotPdaBump] = PublicKey.findProgramAddressSync(
+          [
+            anchor.utils.bytes.utf8.encode("product_snapshot"),
+            productSnapshotMetadataPda.toBuffer(),
+          ], program.programId);
+
+        const [priceTiersPda] = PublicKey.findProgramAddressSync(
+          [
+            anchor.utils.bytes.utf8.encode("price_tiers"),
+            loneProductPda.toBuffer(),
+          ], program.programId);
+
+        const [priceTiersSnapshotPda] = PublicKey.findProgramAddressSync(
+          [
+            anchor.utils.bytes.utf8.encode("price_tiers"),
+            productSnapshotPda.toBuffer(),
+          ], program.programId);
+  
+        const purchaseTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
+          paymentTokenMintAddress,
+          purchaseTicketPda,
+          true,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID);
+  
+        //console.log('purchaseTicketPaymentAddress: ', purchaseTicketPaymentAddress.toBase58());  
+        const createPurchaseTicketAtaIx = spl_token.createAssociatedTokenAccountInstruction(
+          creatorKeypair.publicKey,
+          purchaseTicketPaymentAddress,
+          purchaseTicketPda,
+          paymentTokenMintAddress,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID);  
+          
+        const transferToPurchaseTicketAtaIx = spl_token.createTransferInstruction(
+          buyerPaymentTokenAddress,
+          purchaseTicketPaymentAddress,
+          creatorKeypair.publicKey,
+          purchaseAmountRequired,
+          [],
+          TOKEN_PROGRAM_ID,
+        );
+  
+        let payToAta: Account;
+        try {
+          payToAta = await spl_token.getAccount(provider.connection, payToAtaAddress, 'confirmed', TOKEN_PROGRAM_ID);
+        } catch(ex) {
         }
   
         const buyProductIx = await program.methods
-          .buyProduct(purchaseNonce, new anchor.BN(purchaseQuantity), loneProduct.price,
+          .buyProduct(purchaseNonce, new anchor.BN(purchaseQuantity), loneProduct.price, new anchor.BN(PURCHASE_TRANSACTION_FEE),
             loneProduct.expirationTimestamp, loneProduct.expirationMinutesAfterPurchase, loneProduct.expirationMinutesAfterRedemption)
           .accounts({
             product: loneProductPda,
@@ -957,6 +2149,17 @@ if(RUN_STANDARD_TESTS)
             programMetadata: programMetadataPda,
             feeTokenAccount: feeTokenAccount.address,
             feeAccount: feeAccountPubkey,
+            feeTier: creatorFeeTierPda,
+            referral: anchor.web3.SystemProgram.programId,
+            referrerTokenAccount: payToAtaAddress,
+            acceptedMint: acceptedMintPda,
+            priceFeed: anchor.web3.SystemProgram.programId,
+            store: anchor.web3.SystemProgram.programId,
+            payoutSplit: anchor.web3.SystemProgram.programId,
+            coupon: anchor.web3.SystemProgram.programId,
+            couponUsage: anchor.web3.SystemProgram.programId,
+            priceTiers: priceTiersPda,
+            priceTiersSnapshot: priceTiersSnapshotPda,
           })
           .instruction();
   
@@ -987,416 +2190,2647 @@ if(RUN_STANDARD_TESTS)
         const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair], {commitment: 'finalized'});  
         //console.info('transaction signature: ', response);
 
-        const productSnapshot = await program.account.product.fetch(productSnapshotPda);
-        expect(productSnapshot.bump).is.equal(loneProduct.bump);
-        expect(productSnapshot.status).is.equal(loneProduct.status);
-        expect(productSnapshot.creator).is.eql(loneProduct.creator);
-        expect(productSnapshot.authority).is.eql(loneProduct.authority);
-        expect(productSnapshot.secondaryAuthority).is.eql(loneProduct.secondaryAuthority);
-        expect(productSnapshot.id).is.equal(loneProduct.id); 
-        expect(productSnapshot.tag.toNumber()).is.equal(loneProduct.tag.toNumber()); 
-        expect(productSnapshot.isSnapshot).is.equal(true); 
-        //expect(productSnapshot.mint).is.eql(loneProduct.mint);
-        //expect(productSnapshot.payTo).is.eql(loneProduct.payTo);
-        expect(productSnapshot.store).is.eql(loneProduct.store); 
-        expect(productSnapshot.price.toNumber()).is.equal(loneProduct.price.toNumber());
-        expect(productSnapshot.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber());
-        expect(productSnapshot.redemptionType).is.equal(loneProduct.redemptionType);
-        expect(productSnapshot.name).is.equal(loneProduct.name);
-        expect(productSnapshot.description).is.equal(loneProduct.description)    
-        expect(productSnapshot.data).is.eql(loneProduct.data);
-  
-        const loneProductAfterPurchase = await program.account.product.fetch(loneProductPda);
-        expect(loneProductAfterPurchase.isSnapshot).is.equal(false); 
-        expect(loneProductAfterPurchase.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber() - purchaseQuantity);  
-  
-        const productSnapshotMetadata = await program.account.productSnapshotMetadata.fetch(productSnapshotMetadataPda);
-        expect(productSnapshotMetadata.bump).is.equal(productSnapshotMetadataPdaBump);
-        expect(productSnapshotMetadata.product).not.equal(loneProductPda);
-        expect(productSnapshotMetadata.productSnapshot).not.equal(productSnapshotPda);
-        expect(productSnapshotMetadata.nonce).is.equal(purchaseNonce);  
-  
-        const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-        expect(purchaseTicket.bump).is.equal(purchaseTicketPdaBump);
-        expect(purchaseTicket.product).is.eql(loneProductPda);
-        expect(purchaseTicket.productSnapshotMetadata).is.eql(productSnapshotMetadataPda);
-        expect(purchaseTicket.productSnapshot).is.eql(productSnapshotPda);
-        expect(purchaseTicket.buyer).is.eql(creatorKeypair.publicKey);
-        expect(purchaseTicket.payTo).is.eql(loneProduct.payTo);
-        expect(purchaseTicket.authority).is.eql(buyForKeypair.publicKey);
-        expect(purchaseTicket.redeemed.toNumber()).is.equal(0);
-        expect(purchaseTicket.pendingRedemption.toNumber()).is.equal(0);
-        expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(purchaseQuantity);
-        expect(purchaseTicket.nonce).is.equal(purchaseNonce);
-        expect(purchaseTicket.expiration.toNumber()).is.equal(loneProduct.expirationTimestamp.toNumber());
-  
-        const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicketPaymentAddress);
-        expect(purchaseTicketPayment.address).is.eql(purchaseTicketPaymentAddress);
-        expect(purchaseTicketPayment.mint).is.eql(paymentTokenMintAddress);
-        expect(purchaseTicketPayment.amount).is.equal(BigInt(purchaseAmountRequired - PURCHASE_TRANSACTION_FEE));
-  
-        const buyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
-        expect(buyerPaymentTokenAccount.address).is.eql(buyerPaymentTokenAddress);
-        expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
-        expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
-        
-      });
+        const productSnapshot = await program.account.product.fetch(productSnapshotPda);
+        expect(productSnapshot.bump).is.equal(loneProduct.bump);
+        expect(productSnapshot.status).is.equal(loneProduct.status);
+        expect(productSnapshot.creator).is.eql(loneProduct.creator);
+        expect(productSnapshot.authority).is.eql(loneProduct.authority);
+        expect(productSnapshot.secondaryAuthority).is.eql(loneProduct.secondaryAuthority);
+        expect(productSnapshot.id).is.equal(loneProduct.id); 
+        expect(productSnapshot.tag.toNumber()).is.equal(loneProduct.tag.toNumber()); 
+        expect(productSnapshot.isSnapshot).is.equal(true); 
+        //expect(productSnapshot.mint).is.eql(loneProduct.mint);
+        //expect(productSnapshot.payTo).is.eql(loneProduct.payTo);
+        expect(productSnapshot.store).is.eql(loneProduct.store); 
+        expect(productSnapshot.price.toNumber()).is.equal(loneProduct.price.toNumber());
+        expect(productSnapshot.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber());
+        expect(productSnapshot.redemptionType).is.equal(loneProduct.redemptionType);
+        expect(productSnapshot.name).is.equal(loneProduct.name);
+        expect(productSnapshot.description).is.equal(loneProduct.description)    
+        expect(productSnapshot.data).is.eql(loneProduct.data);
+
+        const priceTiersSnapshot = await program.account.priceTiers.fetch(priceTiersSnapshotPda);
+        expect(priceTiersSnapshot.product).is.eql(productSnapshotPda);
+        expect(priceTiersSnapshot.tiers.length).is.equal(2); //quantity is under the first tier, so the price is unchanged
+  
+        const loneProductAfterPurchase = await program.account.product.fetch(loneProductPda);
+        expect(loneProductAfterPurchase.isSnapshot).is.equal(false); 
+        expect(loneProductAfterPurchase.inventory.toNumber()).is.equal(loneProduct.inventory.toNumber() - purchaseQuantity);  
+  
+        const productSnapshotMetadata = await program.account.productSnapshotMetadata.fetch(productSnapshotMetadataPda);
+        expect(productSnapshotMetadata.bump).is.equal(productSnapshotMetadataPdaBump);
+        expect(productSnapshotMetadata.product).not.equal(loneProductPda);
+        expect(productSnapshotMetadata.productSnapshot).not.equal(productSnapshotPda);
+        expect(productSnapshotMetadata.nonce).is.equal(purchaseNonce);  
+  
+        const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        expect(purchaseTicket.bump).is.equal(purchaseTicketPdaBump);
+        expect(purchaseTicket.product).is.eql(loneProductPda);
+        expect(purchaseTicket.productSnapshotMetadata).is.eql(productSnapshotMetadataPda);
+        expect(purchaseTicket.productSnapshot).is.eql(productSnapshotPda);
+        expect(purchaseTicket.buyer).is.eql(creatorKeypair.publicKey);
+        expect(purchaseTicket.payTo).is.eql(loneProduct.payTo);
+        expect(purchaseTicket.authority).is.eql(buyForKeypair.publicKey);
+        expect(purchaseTicket.redeemed.toNumber()).is.equal(0);
+        expect(purchaseTicket.pendingRedemption.toNumber()).is.equal(0);
+        expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(purchaseQuantity);
+        expect(purchaseTicket.nonce).is.equal(purchaseNonce);
+        expect(purchaseTicket.expiration.toNumber()).is.equal(loneProduct.expirationTimestamp.toNumber());
+        expect(purchaseTicket.amountDue.toNumber()).is.equal(0);
+        expect(purchaseTicket.coupon).is.eql(anchor.web3.SystemProgram.programId);
+        expect(purchaseTicket.discount.toNumber()).is.equal(0);
+  
+        const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicketPaymentAddress);
+        expect(purchaseTicketPayment.address).is.eql(purchaseTicketPaymentAddress);
+        expect(purchaseTicketPayment.mint).is.eql(paymentTokenMintAddress);
+        expect(purchaseTicketPayment.amount).is.equal(BigInt(purchaseAmountRequired - PURCHASE_TRANSACTION_FEE));
+  
+        const buyerPaymentTokenAccount = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
+        expect(buyerPaymentTokenAccount.address).is.eql(buyerPaymentTokenAddress);
+        expect(buyerPaymentTokenAccount.mint).is.eql(paymentTokenMintAddress);
+        expect(buyerPaymentTokenAccount.amount).is.equal(BigInt(0));
+        
+      });
+
+      it("Transfer Ticket", async () => {
+        const quantity = 1;
+        const purchaseNonce = generateRandomU16();
+        const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        
+        const [destinationTicketPda, destinationTicketPdaBump] = PublicKey.findProgramAddressSync(
+          [
+            anchor.utils.bytes.utf8.encode("purchase_ticket"),
+            purchaseTicketBefore.productSnapshotMetadata.toBuffer(),
+            purchaseTicketBefore.authority.toBuffer(),
+            Buffer.from(uIntToBytes(purchaseNonce,2,"setUint"))
+          ], program.programId);
+
+        const sourceTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
+          paymentTokenMintAddress,
+          purchaseTicketPda,
+          true,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        const destinationTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
+          paymentTokenMintAddress,
+          destinationTicketPda,
+          true,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID);       
+        
+        const sourceTicketPaymentBefore = await spl_token.getAccount(provider.connection, sourceTicketPaymentAddress);
+
+        const tx = await program.methods
+          .transferTicket(purchaseNonce, new anchor.BN(quantity))
+          .accounts({
+            destinationTicket:destinationTicketPda,
+            destinationTicketPayment: destinationTicketPaymentAddress,
+            destinationTicketAuthority: secondaryAuthorityPubkey,
+            sourceTicket: purchaseTicketPda,
+            sourceTicketPayment: sourceTicketPaymentAddress,
+            sourceTicketAuthority: buyForKeypair.publicKey,
+            productSnapshot: purchaseTicketBefore.productSnapshot,
+            paymentMint: paymentTokenMintAddress,
+            programMetadata: programMetadataPda,
+            acceptedMint: acceptedMintPda,
+          })
+          .transaction();
+
+        //tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+        tx.feePayer = buyForKeypair.publicKey;  
+        const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [buyForKeypair], {commitment: 'finalized'});  
+        //console.info('transaction signature: ', response);
+        
+        const purchaseTicketAfter = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        expect(purchaseTicketAfter.redeemed.toNumber()).is.equal(purchaseTicketBefore.redeemed.toNumber());
+        expect(purchaseTicketAfter.pendingRedemption.toNumber()).is.equal(purchaseTicketBefore.pendingRedemption.toNumber());
+        expect(purchaseTicketAfter.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - quantity);
+
+        const destinationTicket = await program.account.purchaseTicket.fetch(destinationTicketPda);
+        expect(destinationTicket.bump).is.equal(destinationTicketPdaBump);
+        expect(destinationTicket.product).is.eql(purchaseTicketBefore.product);
+        expect(destinationTicket.productSnapshotMetadata).is.eql(purchaseTicketBefore.productSnapshotMetadata);
+        expect(destinationTicket.productSnapshot).is.eql(purchaseTicketBefore.productSnapshot);
+        expect(destinationTicket.buyer).is.eql(purchaseTicketBefore.authority);
+        expect(destinationTicket.payTo).is.eql(purchaseTicketBefore.payTo);
+        expect(destinationTicket.authority).is.eql(secondaryAuthorityPubkey);
+        expect(destinationTicket.redeemed.toNumber()).is.equal(0);
+        expect(destinationTicket.pendingRedemption.toNumber()).is.equal(0);
+        expect(destinationTicket.remainingQuantity.toNumber()).is.equal(quantity);
+        expect(destinationTicket.nonce).is.equal(purchaseNonce);
+        expect(destinationTicket.payment).is.eql(destinationTicketPaymentAddress);
+        expect(destinationTicket.slot.toNumber()).is.greaterThan(purchaseTicketBefore.slot.toNumber());
+        expect(destinationTicket.timestamp.toNumber()).is.greaterThan(purchaseTicketBefore.timestamp.toNumber());
+  
+        const destinationTicketPayment = await spl_token.getAccount(provider.connection, destinationTicketPaymentAddress);
+        expect(destinationTicketPayment.address).is.eql(destinationTicketPaymentAddress);
+        expect(destinationTicketPayment.mint).is.eql(paymentTokenMintAddress);
+        expect(destinationTicketPayment.amount).is.equal(BigInt(purchaseTicketBefore.price.toNumber() * quantity));
+
+        const sourceTicketPaymentAfter = await spl_token.getAccount(provider.connection, sourceTicketPaymentAddress);
+        expect(sourceTicketPaymentAfter.address).is.eql(sourceTicketPaymentAddress);
+        expect(sourceTicketPaymentAfter.mint).is.eql(paymentTokenMintAddress);
+        expect(sourceTicketPaymentAfter.amount).is.equal(sourceTicketPaymentBefore.amount - BigInt(purchaseTicketBefore.price.toNumber() * quantity));
+      });
+
+      it("List and delist ticket", async () => {
+        const quantity = 1;
+        const askingPrice = updatedProductPrice * 2; //at the resale cap
+        const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        const [ticketListingPda, ticketListingPdaBump] = PublicKey.findProgramAddressSync(
+          [
+            anchor.utils.bytes.utf8.encode("ticket_listing"),
+            purchaseTicketPda.toBuffer(),
+          ], program.programId);
+
+        const listTx = await program.methods
+          .listTicket(new anchor.BN(quantity), new anchor.BN(askingPrice))
+          .accounts({
+            listing: ticketListingPda,
+            ticket: purchaseTicketPda,
+            productSnapshot: purchaseTicketBefore.productSnapshot,
+            paymentMint: paymentTokenMintAddress,
+            programMetadata: programMetadataPda,
+            ticketAuthority: buyForKeypair.publicKey,
+          })
+          .transaction();
+
+        listTx.feePayer = buyForKeypair.publicKey;
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, listTx, [buyForKeypair], {commitment: 'finalized'});
+
+        const listing = await program.account.ticketListing.fetch(ticketListingPda);
+        expect(listing.bump).is.equal(ticketListingPdaBump);
+        expect(listing.ticket).is.eql(purchaseTicketPda);
+        expect(listing.seller).is.eql(buyForKeypair.publicKey);
+        expect(listing.paymentMint).is.eql(paymentTokenMintAddress);
+        expect(listing.price.toNumber()).is.equal(askingPrice);
+        expect(listing.quantity.toNumber()).is.equal(quantity);
+
+        const listedTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        expect(listedTicket.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - quantity);
+        expect(listedTicket.listedQuantity.toNumber()).is.equal(quantity);
+
+        const delistTx = await program.methods
+          .delistTicket()
+          .accounts({
+            listing: ticketListingPda,
+            ticket: purchaseTicketPda,
+            seller: buyForKeypair.publicKey,
+          })
+          .transaction();
+
+        delistTx.feePayer = buyForKeypair.publicKey;
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, delistTx, [buyForKeypair], {commitment: 'finalized'});
+
+        const delistedTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        expect(delistedTicket.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber());
+        expect(delistedTicket.listedQuantity.toNumber()).is.equal(0);
+        expect(await provider.connection.getAccountInfo(ticketListingPda)).is.null;
+      });
+
+      it("Gift and claim ticket", async () => {
+        const secret = Buffer.from(Keypair.generate().secretKey.slice(0, 32));
+        const secretHash = Array.from(createHash('sha256').update(secret).update(buyForKeypair.publicKey.toBuffer()).digest());
+        const ticketBeforeGift = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        const [giftPda, giftPdaBump] = PublicKey.findProgramAddressSync(
+          [
+            anchor.utils.bytes.utf8.encode("gift"),
+            purchaseTicketPda.toBuffer(),
+          ], program.programId);
+
+        const giftTx = await program.methods
+          .createGift(secretHash, 60 * 24)
+          .accounts({
+            gift: giftPda,
+            ticket: purchaseTicketPda,
+            productSnapshot: ticketBeforeGift.productSnapshot,
+            programMetadata: programMetadataPda,
+            giver: buyForKeypair.publicKey,
+          })
+          .transaction();
+
+        giftTx.feePayer = buyForKeypair.publicKey;
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, giftTx, [buyForKeypair], {commitment: 'finalized'});
+
+        const gift = await program.account.gift.fetch(giftPda);
+        expect(gift.bump).is.equal(giftPdaBump);
+        expect(gift.ticket).is.eql(purchaseTicketPda);
+        expect(gift.giver).is.eql(buyForKeypair.publicKey);
+        expect(gift.secretHash).is.eql(secretHash);
+
+        const giftedTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        expect(giftedTicket.authority).is.eql(giftPda);
+
+        //claimed back by the giver so the redemption tests still have the ticket
+        const claimTx = await program.methods
+          .claimGift(secret)
+          .accounts({
+            gift: giftPda,
+            ticket: purchaseTicketPda,
+            giver: buyForKeypair.publicKey,
+            recipient: buyForKeypair.publicKey,
+          })
+          .transaction();
+
+        claimTx.feePayer = buyForKeypair.publicKey;
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, claimTx, [buyForKeypair], {commitment: 'finalized'});
+
+        const claimedTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+        expect(claimedTicket.authority).is.eql(buyForKeypair.publicKey);
+        expect(await provider.connection.getAccountInfo(giftPda)).is.null;
+      });
+
+      describe("[Redeem Lone Product Ticket]", () => 
+      {
+        let purchaseTicket;
+        let redemptionNonce, redemptionPda, redemptionPdaBump;
+
+        before(async () => {
+          return new Promise<void>(async (resolve,reject)=>{
+            purchaseTicket = await program.account
+              .purchaseTicket
+              .fetch(purchaseTicketPda)
+              .catch(reject);
+            
+            if(!purchaseTicket)
+              return;
+            do {
+              redemptionNonce = generateRandomU32(); //await provider.connection.getSlot();
+              [redemptionPda, redemptionPdaBump] = PublicKey.findProgramAddressSync(
+                [
+                  anchor.utils.bytes.utf8.encode("redemption"),
+                  purchaseTicketPda.toBuffer(),
+                  Buffer.from(uIntToBytes(redemptionNonce,4,"setUint")),
+                ], program.programId);
+            }while((await program.account.redemption.fetchNullable(redemptionPda)) != null);
+            
+            resolve();
+          });
+        });
+
+        it("Create Lone Product Ticket Taker", async () => {          
+          const txSuccess = await program.methods
+            .createProductTicketTaker()
+            .accounts({
+              ticketTaker: loneProductTicketTakerPda,
+              taker: ticketTakerKeypair.publicKey,
+              product: loneProductPda,
+              productAuthority: creatorKeypair.publicKey
+            })
+            .transaction();
+    
+          const txSucceeded = await anchor.web3.sendAndConfirmTransaction(provider.connection, txSuccess, [creatorKeypair]);
+    
+          const loneProductTicketTaker = await program.account.ticketTaker.fetch(loneProductTicketTakerPda);
+          expect(loneProductTicketTaker.bump).is.equal(loneProductTicketTakerPdaBump);
+          expect(loneProductTicketTaker.version).is.equal(0);
+          expect(loneProductTicketTaker.taker).is.eql(ticketTakerKeypair.publicKey);
+          expect(loneProductTicketTaker.entityType).is.equal(2);
+          expect(loneProductTicketTaker.authorizedBy).is.eql(creatorKeypair.publicKey);
+          expect(loneProductTicketTaker.enabledSlot.toNumber()).is.greaterThan(0);
+          expect(loneProductTicketTaker.enabledTimestamp.toNumber()).is.greaterThan(0);
+          expect(loneProductTicketTaker.disabledSlot.toNumber()).is.equal(0);
+          expect(loneProductTicketTaker.disabledTimestamp.toNumber()).is.equal(0);
+        });
+
+        it("Initiate Redemption", async()=>{
+          const quantity = 1;
+          const expirationMinutes = 5;
+
+          const tx = await program.methods
+            .initiateRedemption(redemptionNonce, new anchor.BN(quantity), expirationMinutes)
+            .accounts({
+              redemption: redemptionPda,
+              purchaseTicket: purchaseTicketPda,
+              purchaseTicketAuthority: purchaseTicket.authority, //buyFor address
+              purchaseTicketPayment: purchaseTicket.payment,
+              purchaseTicketPaymentMint: paymentTokenMintAddress,
+              programMetadata: programMetadataPda,
+              acceptedMint: acceptedMintPda,
+            })
+            .transaction();
+        
+          tx.feePayer = buyForKeypair.publicKey;          
+          //tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;  
+
+          const txSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [buyForKeypair], {commitment: 'finalized'});
+          //console.info('redemption init signature: ', txSignature);
+    
+          const redemptionAccount = await program.account.redemption.fetch(redemptionPda);
+          expect(redemptionAccount.bump).is.equal(redemptionPdaBump);
+          expect(redemptionAccount.version).is.equal(0);
+          expect(redemptionAccount.initSlot.toNumber()).is.greaterThan(0);
+          expect(redemptionAccount.initTimestamp.toNumber()).is.greaterThan(0);
+          expect(redemptionAccount.store).is.eql(purchaseTicket.store);
+          expect(redemptionAccount.product).is.eql(purchaseTicket.product);
+          expect(redemptionAccount.productSnapshotMetadata).is.eql(purchaseTicket.productSnapshotMetadata);
+          expect(redemptionAccount.productSnapshot).is.eql(purchaseTicket.productSnapshot);
+          expect(redemptionAccount.purchaseTicket).is.eql(purchaseTicketPda);
+          expect(redemptionAccount.purchaseTicketSigner).is.eql(purchaseTicket.authority);
+          expect(redemptionAccount.buyer).is.eql(purchaseTicket.buyer);
+          expect(redemptionAccount.payTo).is.eql(purchaseTicket.payTo);
+          //expect(redemptionAccount.quantity.toNumber()).is.equal(purchaseTicket.remainingQuantity.toNumber());
+          expect(redemptionAccount.redeemQuantity.toNumber()).is.equal(quantity);
+          expect(redemptionAccount.price.toNumber()).is.equal(purchaseTicket.price.toNumber());
+          expect(redemptionAccount.ticketTaker).is.eql(PublicKey.default);
+          expect(redemptionAccount.ticketTakerSigner).is.eql(PublicKey.default);
+          expect(redemptionAccount.status).is.equal(0);
+          expect(redemptionAccount.nonce).is.equal(redemptionNonce);
+          expect(redemptionAccount.takeExpiration.toNumber()).is.greaterThan(0);
+
+          const updatedPurchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          expect(updatedPurchaseTicket.redeemed.toNumber()).is.equal(0);
+          expect(updatedPurchaseTicket.pendingRedemption.toNumber()).is.equal(quantity);
+          expect(updatedPurchaseTicket.remainingQuantity.toNumber()).is.equal(purchaseTicket.remainingQuantity - quantity);
+        });
+
+        it("Take Redemption", async ()=>{
+          const redemptionBefore = await program.account.redemption.fetch(redemptionPda);
+          const purchaseTicketPaymentBefore = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
+          const payToTokenAccountAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, purchaseTicket.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+          const payToTokenAccount = await spl_token.getAccount(provider.connection, payToTokenAccountAddress);
+          const [productTicketTakerPda, productTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
+            [
+              anchor.utils.bytes.utf8.encode("product_taker"),
+              purchaseTicket.product.toBuffer(),
+              ticketTakerKeypair.publicKey.toBuffer(),
+            ], program.programId);
+          const [storeTicketTakerPda, storeTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
+            [
+              anchor.utils.bytes.utf8.encode("store_taker"),
+              purchaseTicket.store.toBuffer(),
+              ticketTakerKeypair.publicKey.toBuffer(),
+            ], program.programId);
+
+          let ticketTakerAddress = productTicketTakerPda;
+          let ticketTakerAccount = await program.account.ticketTaker.fetchNullable(productTicketTakerPda);
+          
+          if(!ticketTakerAccount) {
+            ticketTakerAddress = storeTicketTakerPda;
+            ticketTakerAccount = await program.account.ticketTaker.fetchNullable(storeTicketTakerPda);
+          }
+  
+          expect(ticketTakerAccount).to.not.be.null;
+
+          const tx = await program.methods
+          .takeRedemption()
+          .accounts({
+            purchaseTicket: purchaseTicketPda,
+            redemption: redemptionPda,
+            ticketTaker: ticketTakerAddress,
+            ticketTakerSigner: ticketTakerKeypair.publicKey,
+            purchaseTicketPayment: purchaseTicket.payment,
+            purchaseTicketPaymentMint: paymentTokenMintAddress,
+            payToTokenAccount: payToTokenAccountAddress,
+            payTo: purchaseTicket.payTo,
+            programMetadata: programMetadataPda,
+            referrerTokenAccount: payToTokenAccountAddress,
+            acceptedMint: acceptedMintPda,
+            payoutSplit: anchor.web3.SystemProgram.programId,
+          })
+          .transaction();
+      
+          tx.feePayer = ticketTakerKeypair.publicKey;          
+          //tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;  
+
+          const txSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [ticketTakerKeypair], {commitment: 'finalized'});
+          //console.info('ticket redemption signature: ', txSignature);
+
+          const redemptionAfter = await program.account.redemption.fetch(redemptionPda);
+          expect(redemptionAfter.ticketTaker).is.eql(ticketTakerAddress);
+          expect(redemptionAfter.ticketTakerSigner).is.eql(ticketTakerKeypair.publicKey);
+          expect(redemptionAfter.closeSlot.toNumber()).is.greaterThan(redemptionBefore.closeSlot.toNumber());
+          expect(redemptionAfter.closeTimestamp.toNumber()).is.greaterThan(redemptionBefore.closeTimestamp.toNumber());
+          expect(redemptionAfter.status).is.equal(1);
+
+          const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
+          expect(purchaseTicketPayment.address).is.eql(purchaseTicket.payment);
+          expect(purchaseTicketPayment.mint).is.eql(paymentTokenMintAddress);
+          expect(purchaseTicketPayment.amount).is.equal(purchaseTicketPaymentBefore.amount - BigInt(redemptionAfter.price.toNumber() * redemptionAfter.redeemQuantity.toNumber()));
+  
+          const updatedPayToTokenAccount = await spl_token.getAccount(provider.connection, payToTokenAccountAddress);
+          expect(updatedPayToTokenAccount.address).is.eql(payToTokenAccountAddress);
+          expect(updatedPayToTokenAccount.owner).is.eql(purchaseTicket.payTo);
+          expect(updatedPayToTokenAccount.mint).is.eql(paymentTokenMintAddress);
+          expect(updatedPayToTokenAccount.amount).is.equal(payToTokenAccount.amount + BigInt(purchaseTicket.price.toNumber() * redemptionBefore.redeemQuantity.toNumber()));
+
+          const updatedPurchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          expect(updatedPurchaseTicket.redeemed.toNumber()).is.equal(redemptionBefore.redeemQuantity.toNumber());
+          expect(updatedPurchaseTicket.pendingRedemption.toNumber()).is.equal(0);
+        });
+
+        it("Cancel Redemption", async()=>{
+          const cancelRedemptionQuantity = 1;       
+          const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
+          let cancelRedemptionNonce, cancelRedemptionPda, cancelRedemptionPdaBump;
+
+          do {
+            cancelRedemptionNonce = generateRandomU32(); //await provider.connection.getSlot();
+            [cancelRedemptionPda, cancelRedemptionPdaBump] = PublicKey.findProgramAddressSync(
+              [
+                anchor.utils.bytes.utf8.encode("redemption"),
+                purchaseTicketPda.toBuffer(),
+                Buffer.from(uIntToBytes(cancelRedemptionNonce,4,"setUint")),
+              ], program.programId);
+          } while((await program.account.redemption.fetchNullable(cancelRedemptionPda)) != null);
+
+          //console.log(`price=${purchaseTicketBefore.price.toNumber()}, remaining=${purchaseTicketBefore.remainingQuantity.toNumber()}`);
+          //console.log(`payment balance:${purchaseTicketPayment.amount}`);
+
+          const initiateRedemptionTx = await program.methods
+            .initiateRedemption(cancelRedemptionNonce,new anchor.BN(cancelRedemptionQuantity), 0)
+            .accounts({
+              redemption: cancelRedemptionPda,
+              purchaseTicket: purchaseTicketPda,
+              purchaseTicketAuthority: purchaseTicketBefore.authority, //buyFor address
+              purchaseTicketPayment: purchaseTicketBefore.payment,
+              purchaseTicketPaymentMint: paymentTokenMintAddress,
+              programMetadata: programMetadataPda,
+              acceptedMint: acceptedMintPda,
+            })
+            .transaction();
+        
+          initiateRedemptionTx.feePayer = buyForKeypair.publicKey;
+
+          const initiateRedemptionTxSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, initiateRedemptionTx, [buyForKeypair], {commitment: 'finalized'});
+          //console.log('initiateRedemptionTxSignature: ', initiateRedemptionTxSignature);
+          const cancelRedemptionPdaAccount = await program.account.redemption.fetchNullable(cancelRedemptionPda);
+          expect(cancelRedemptionPdaAccount).to.not.be.null;
+          expect(cancelRedemptionPdaAccount.redeemQuantity.toNumber()).is.equal(1);
+
+          const purchaseTicketBeforeCancel = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          expect(purchaseTicketBeforeCancel.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - cancelRedemptionQuantity);
+          expect(purchaseTicketBeforeCancel.pendingRedemption.toNumber()).is.equal(purchaseTicketBefore.pendingRedemption.toNumber() + cancelRedemptionQuantity)
+
+          const cancelRedemptionTx = await program.methods
+            .cancelRedemption()
+            .accounts({
+              redemption: cancelRedemptionPda,
+              purchaseTicket: purchaseTicketPda,
+              purchaseTicketAuthority: buyForKeypair.publicKey, //buyFor address
+            })
+            .transaction();
+        
+          initiateRedemptionTx.feePayer = buyForKeypair.publicKey;
+          const cancelRedemptionTxSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, cancelRedemptionTx, [buyForKeypair], {commitment: 'finalized'});
+          //console.log('cancelRedemptionTxSignature: ', cancelRedemptionTxSignature);
+          const cancelledRedemptionPdaAccount = await program.account.redemption.fetchNullable(cancelRedemptionPda);
+          expect(cancelledRedemptionPdaAccount).to.be.null;
+          
+          const purchaseTicketAfterCancel = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          expect(purchaseTicketAfterCancel.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber());
+          expect(purchaseTicketAfterCancel.pendingRedemption.toNumber()).is.equal(purchaseTicketBefore.pendingRedemption.toNumber());
+        });
+
+        it("Cancel Ticket", async() => {
+          const cancelQuantity = 1;
+          const buyerPaymentTokenAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, creatorKeypair.publicKey, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+          const buyerPaymentTokenBefore = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
+          const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          const purchaseTicketPaymentBefore = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
+          const productBefore = await program.account.product.fetch(purchaseTicket.product);
+
+          const tx = await program.methods
+            .cancelTicket(new anchor.BN(cancelQuantity))
+            .accounts({
+              product: purchaseTicket.product,
+              ticket: purchaseTicketPda,
+              ticketPayment: purchaseTicket.payment,
+              paymentReturn: buyerPaymentTokenAddress,
+              paymentMint: paymentTokenMintAddress,
+              ticketAuthority: buyForKeypair.publicKey,
+              programMetadata: programMetadataPda,
+              acceptedMint: acceptedMintPda,
+            })
+            .transaction();
+        
+          tx.feePayer = buyForKeypair.publicKey;
+          const txSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [buyForKeypair], {commitment: 'finalized'});
+          
+          const purchaseTicketAfter = await program.account.purchaseTicket.fetch(purchaseTicketPda);
+          expect(purchaseTicketAfter.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - cancelQuantity);
+
+          const productAfter = await program.account.product.fetch(purchaseTicket.product);
+          expect(productAfter.inventory.toNumber()).is.equal(productBefore.inventory.toNumber() + cancelQuantity);
+          
+          const purchaseTicketPaymentAfter = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
+          expect(purchaseTicketPaymentAfter.amount).is.equal(purchaseTicketPaymentBefore.amount - BigInt(purchaseTicket.price.toNumber() * cancelQuantity));
+
+          const buyerPaymentTokenAfter = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
+          expect(buyerPaymentTokenAfter.amount).is.equal(buyerPaymentTokenBefore.amount + BigInt(purchaseTicket.price.toNumber() * cancelQuantity));
+        });
+
+      }); //[Redeem Lone Product Ticket]
+  
+    }); //lone product - ticketed redemption tests
+
+    describe("[Lone Product - Subscription Tests]", () => {
+      it("Set Lone Product subscription period", async () => {
+        const subscriptionPeriodMinutes = 60 * 24 * 30;
+
+        const tx = await program.methods
+          .setProductSubscriptionPeriod(subscriptionPeriodMinutes)
+          .accounts({
+            product: loneProductPda,
+            authority: creatorKeypair.publicKey,
+          })
+          .transaction();
+
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+        const updatedProduct = await program.account.product.fetch(loneProductPda);
+        expect(updatedProduct.subscriptionPeriodMinutes).is.equal(subscriptionPeriodMinutes);
+      });
+    });
+
+    describe("[Lone Product - Dutch Auction Tests]", () => {
+      it("Set Lone Product dutch auction", async () => {
+        const dutchAuction = {
+          startPrice: new anchor.BN(updatedProductPrice * 2),
+          floorPrice: new anchor.BN(updatedProductPrice),
+          startTimestamp: new anchor.BN(Math.floor(new Date().getTime() / 1000)),
+          decayPerMinute: new anchor.BN(1000),
+        };
+
+        const tx = await program.methods
+          .setProductDutchAuction(dutchAuction)
+          .accounts({
+            product: loneProductPda,
+            authority: creatorKeypair.publicKey,
+          })
+          .transaction();
+
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [creatorKeypair]);
+
+        const updatedProduct = await program.account.product.fetch(loneProductPda);
+        expect(updatedProduct.dutchAuction.startPrice.toNumber()).is.equal(dutchAuction.startPrice.toNumber());
+        expect(updatedProduct.dutchAuction.floorPrice.toNumber()).is.equal(dutchAuction.floorPrice.toNumber());
+        expect(updatedProduct.dutchAuction.startTimestamp.toNumber()).is.equal(dutchAuction.startTimestamp.toNumber());
+        expect(updatedProduct.dutchAuction.decayPerMinute.toNumber()).is.equal(dutchAuction.decayPerMinute.toNumber());
+      });
+    });
+
+    describe("[Lone Product - English Auction Tests]", () => {
+      const [auctionPda, auctionPdaBump] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("auction"),
+          loneProductPda.toBuffer(),
+        ], program.programId);
+
+      it("Create and cancel Lone Product auction", async () => {
+        //auctions aren't offered on subscription products
+        const clearSubscriptionTx = await program.methods
+          .setProductSubscriptionPeriod(0)
+          .accounts({
+            product: loneProductPda,
+            authority: creatorKeypair.publicKey,
+          })
+          .transaction();
+
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, clearSubscriptionTx, [creatorKeypair]);
+
+        const productBefore = await program.account.product.fetch(loneProductPda);
+        const escrowAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, auctionPda, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+        const minBid = new anchor.BN(updatedProductPrice);
+        const minIncrement = new anchor.BN(1000);
+        const startTimestamp = new anchor.BN(Math.floor(new Date().getTime() / 1000));
+        const endTimestamp = new anchor.BN(startTimestamp.toNumber() + 60 * 60 * 24);
+        const nonce = generateRandomU16();
+        const productSnapshotMetadataPda = pda("product_snapshot_metadata", loneProductPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+        const productSnapshotPda = pda("product_snapshot", productSnapshotMetadataPda.toBuffer());
+
+        const createTx = await program.methods
+          .createAuction(nonce, minBid, minIncrement, startTimestamp, endTimestamp)
+          .accounts({
+            product: loneProductPda,
+            auction: auctionPda,
+            productSnapshotMetadata: productSnapshotMetadataPda,
+            productSnapshot: productSnapshotPda,
+            escrow: escrowAddress,
+            paymentMint: paymentTokenMintAddress,
+            acceptedMint: acceptedMintPda,
+            programMetadata: programMetadataPda,
+            feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+            authority: creatorKeypair.publicKey,
+          })
+          .transaction();
+
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, createTx, [creatorKeypair]);
+
+        const auction = await program.account.auction.fetch(auctionPda);
+        expect(auction.bump).is.equal(auctionPdaBump);
+        expect(auction.product).is.eql(loneProductPda);
+        expect(auction.authority).is.eql(creatorKeypair.publicKey);
+        expect(auction.paymentMint).is.eql(paymentTokenMintAddress);
+        expect(auction.escrow).is.eql(escrowAddress);
+        expect(auction.minBid.toNumber()).is.equal(minBid.toNumber());
+        expect(auction.minIncrement.toNumber()).is.equal(minIncrement.toNumber());
+        expect(auction.endTimestamp.toNumber()).is.equal(endTimestamp.toNumber());
+        expect(auction.highestBidder).is.eql(PublicKey.default);
+        expect(auction.bids).is.equal(0);
+        expect(auction.productSnapshotMetadata).is.eql(productSnapshotMetadataPda);
+        expect(auction.productSnapshot).is.eql(productSnapshotPda);
+        expect(auction.nonce).is.equal(nonce);
+
+        const productSnapshot = await program.account.product.fetch(productSnapshotPda);
+        expect(productSnapshot.isSnapshot).is.equal(true);
+
+        const productDuringAuction = await program.account.product.fetch(loneProductPda);
+        expect(productDuringAuction.inventory.toNumber()).is.equal(productBefore.inventory.toNumber() - 1);
+
+        //tokens sent to the escrow outside of bidding go to the authority, so the escrow still closes
+        const strayTokens = 1234;
+        await fundPaymentTokens(auctionPda, strayTokens);
+        await fundPaymentTokens(creatorKeypair.publicKey, 0);
+        const authorityTokensBefore = await paymentTokenBalance(creatorKeypair.publicKey);
+
+        const cancelTx = await program.methods
+          .cancelAuction()
+          .accounts({
+            auction: auctionPda,
+            escrow: escrowAddress,
+            productSnapshotMetadata: productSnapshotMetadataPda,
+            productSnapshot: productSnapshotPda,
+            product: loneProductPda,
+            authorityTokenAccount: paymentTokenAddress(creatorKeypair.publicKey),
+            authority: creatorKeypair.publicKey,
+          })
+          .transaction();
+
+        await anchor.web3.sendAndConfirmTransaction(provider.connection, cancelTx, [creatorKeypair]);
+
+        expect(await paymentTokenBalance(creatorKeypair.publicKey) - authorityTokensBefore).is.equal(BigInt(strayTokens));
+
+        const productAfter = await program.account.product.fetch(loneProductPda);
+        expect(productAfter.inventory.toNumber()).is.equal(productBefore.inventory.toNumber());
+        expect(await provider.connection.getAccountInfo(auctionPda)).is.null;
+        expect(await provider.connection.getAccountInfo(escrowAddress)).is.null;
+        expect(await provider.connection.getAccountInfo(productSnapshotPda)).is.null;
+      });
+    });
+
+  }); //lone product tests
+
+  describe("[Fee Tests]", () => {
+    const FEE_CHANGE_DELAY_SECONDS = 60 * 60 * 24;
+    let feeProductPda: PublicKey;
+
+    before(async () => {
+      feeProductPda = await createTestProduct(2, 100000, 10);
+    });
+
+    it("Buy with a fee above the agreed fee fails", async () => {
+      await expectAnchorError(buyTestProduct(feeProductPda, {agreedFee: PURCHASE_TRANSACTION_FEE - 1}), "FeeIsGreaterThanAgreedFee");
+    });
+
+    it("Pending fee doesn't apply before FEE_CHANGE_DELAY_SECONDS", async () => {
+      const scheduledAt = Math.floor(new Date().getTime() / 1000);
+
+      await program.methods
+        .changeFee({
+          flatFee: new anchor.BN(PURCHASE_TRANSACTION_FEE * 2),
+          basisPoints: 0,
+          minFee: new anchor.BN(0),
+          maxFee: new anchor.BN(0),
+        })
+        .accounts({
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.fee.flatFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(programMetadata.pendingFee.flatFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE * 2);
+      expect(programMetadata.pendingFeeTimestamp.toNumber()).is.greaterThanOrEqual(scheduledAt + FEE_CHANGE_DELAY_SECONDS - 60);
+
+      //the buyer agreed to the current fee, which is what gets charged until the delay passes
+      const {purchaseTicketPda} = await buyTestProduct(feeProductPda);
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.fee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(purchaseTicket.timestamp.toNumber()).is.lessThan(programMetadata.pendingFeeTimestamp.toNumber());
+
+      //rescheduling the current fee replaces the pending one
+      await program.methods
+        .changeFee({
+          flatFee: new anchor.BN(PURCHASE_TRANSACTION_FEE),
+          basisPoints: 0,
+          minFee: new anchor.BN(0),
+          maxFee: new anchor.BN(0),
+        })
+        .accounts({
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      const restoredProgramMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(restoredProgramMetadata.pendingFee.flatFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+    });
+
+    it("Cancel refunds the price but not the fee", async () => {
+      const {purchaseTicketPda, purchaseTicketPaymentAddress} = await buyTestProduct(feeProductPda);
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      const buyerBalanceBefore = await paymentTokenBalance(creatorKeypair.publicKey);
+      const feeBalanceBefore = await paymentTokenBalance(feeAccountPubkey);
+
+      await program.methods
+        .cancelTicket(new anchor.BN(1))
+        .accounts({
+          product: feeProductPda,
+          ticket: purchaseTicketPda,
+          ticketPayment: purchaseTicketPaymentAddress,
+          paymentReturn: paymentTokenAddress(creatorKeypair.publicKey),
+          paymentMint: paymentTokenMintAddress,
+          ticketAuthority: creatorKeypair.publicKey,
+          programMetadata: programMetadataPda,
+          acceptedMint: acceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      expect(await paymentTokenBalance(creatorKeypair.publicKey)).is.equal(buyerBalanceBefore + BigInt(purchaseTicket.price.toNumber()));
+      expect(await paymentTokenBalance(feeAccountPubkey)).is.equal(feeBalanceBefore);
+
+      const cancelledTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(cancelledTicket.fee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(cancelledTicket.remainingQuantity.toNumber()).is.equal(0);
+    });
+
+    describe("[Fee Schedule Tests]", () => {
+      const price = 1000000;
+      const schedules = [
+        {name: "basis points", fee: {flatFee: 0, basisPoints: 100, minFee: 0, maxFee: 0}, expected: 10000},
+        {name: "hybrid", fee: {flatFee: 5000, basisPoints: 100, minFee: 0, maxFee: 0}, expected: 15000},
+        {name: "min fee clamp", fee: {flatFee: 0, basisPoints: 10, minFee: 5000, maxFee: 0}, expected: 5000},
+        {name: "max fee clamp", fee: {flatFee: 0, basisPoints: 500, minFee: 0, maxFee: 20000}, expected: 20000},
+      ];
+
+      for(const schedule of schedules) {
+        it(`Fee tier charges the ${schedule.name} fee`, async () => {
+          //each schedule gets its own creator, since lone products pick up their creator's fee tier
+          const creator = await createFundedKeypair();
+
+          await program.methods
+            .createCreatorFeeTier({
+              flatFee: new anchor.BN(schedule.fee.flatFee),
+              basisPoints: schedule.fee.basisPoints,
+              minFee: new anchor.BN(schedule.fee.minFee),
+              maxFee: new anchor.BN(schedule.fee.maxFee),
+            }, new anchor.BN(0))
+            .accounts({
+              feeTier: pda("fee_tier", creator.publicKey.toBuffer()),
+              creator: creator.publicKey,
+              programMetadata: programMetadataPda,
+              authority: provider.publicKey,
+            })
+            .rpc({commitment: 'confirmed'});
+
+          const productPda = await createTestProduct(2, price, 1, creator);
+          const feeBalanceBefore = await paymentTokenBalance(feeAccountPubkey);
+          const {purchaseTicketPda} = await buyTestProduct(productPda, {agreedFee: schedule.expected, payment: price + schedule.expected});
+
+          const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+          expect(purchaseTicket.fee.toNumber()).is.equal(schedule.expected);
+          expect(await paymentTokenBalance(feeAccountPubkey)).is.equal(feeBalanceBefore + BigInt(schedule.expected));
+        });
+      }
+    });
+  }); //fee tests
+
+  describe("[Pause Tests]", () => {
+    const BUY_PRODUCT_PAUSE_FLAG = 2;
+
+    const setPaused = (paused: number) => program.methods
+      .setPaused(new anchor.BN(paused))
+      .accounts({
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+    it("Paused purchases fail while cancels go through", async () => {
+      const productPda = await createTestProduct(2, 100000, 10);
+      const {purchaseTicketPda, purchaseTicketPaymentAddress} = await buyTestProduct(productPda);
+
+      await setPaused(BUY_PRODUCT_PAUSE_FLAG);
+
+      try {
+        await expectAnchorError(buyTestProduct(productPda), "InstructionIsPaused");
+
+        const buyerBalanceBefore = await paymentTokenBalance(creatorKeypair.publicKey);
+        await program.methods
+          .cancelTicket(new anchor.BN(1))
+          .accounts({
+            product: productPda,
+            ticket: purchaseTicketPda,
+            ticketPayment: purchaseTicketPaymentAddress,
+            paymentReturn: paymentTokenAddress(creatorKeypair.publicKey),
+            paymentMint: paymentTokenMintAddress,
+            ticketAuthority: creatorKeypair.publicKey,
+            programMetadata: programMetadataPda,
+            acceptedMint: acceptedMintPda,
+          })
+          .signers([creatorKeypair])
+          .rpc({commitment: 'confirmed'});
+
+        const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+        expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(0);
+        expect(await paymentTokenBalance(creatorKeypair.publicKey)).is.equal(buyerBalanceBefore + BigInt(purchaseTicket.price.toNumber()));
+      } finally {
+        await setPaused(0);
+      }
+    });
+  }); //pause tests
+
+  describe("[Fee Distribution Tests]", () => {
+    const majorityRecipient = Keypair.generate().publicKey;
+    const minorityRecipient = Keypair.generate().publicKey;
+
+    before(async () => {
+      await program.methods
+        .setFeeRecipients([
+          {wallet: majorityRecipient, basisPoints: 7000},
+          {wallet: minorityRecipient, basisPoints: 3000},
+        ])
+        .accounts({
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+    });
+
+    after(async () => {
+      await program.methods
+        .changeFeeAccount()
+        .accounts({
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+          feeAccount: feeAccountPubkey,
+        })
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Distribute fees", async () => {
+      const collected = 100001;
+      const feeVaultAddress = await fundPaymentTokens(programMetadataPda, collected);
+      const majorityTokenAddress = await fundPaymentTokens(majorityRecipient, 0);
+      const minorityTokenAddress = await fundPaymentTokens(minorityRecipient, 0);
+      const total = Number(await paymentTokenBalance(programMetadataPda));
+      const majorityShare = Math.floor(total * 7000 / 10000);
+
+      await program.methods
+        .distributeFees()
+        .accounts({
+          programMetadata: programMetadataPda,
+          feeVault: feeVaultAddress,
+        })
+        .remainingAccounts([
+          {pubkey: majorityTokenAddress, isWritable: true, isSigner: false},
+          {pubkey: minorityTokenAddress, isWritable: true, isSigner: false},
+        ])
+        .rpc({commitment: 'confirmed'});
+
+      //the last recipient picks up the rounding dust
+      expect(await paymentTokenBalance(majorityRecipient)).is.equal(BigInt(majorityShare));
+      expect(await paymentTokenBalance(minorityRecipient)).is.equal(BigInt(total - majorityShare));
+      expect(await paymentTokenBalance(programMetadataPda)).is.equal(BigInt(0));
+    });
+
+    it("Distribute fees to the wrong recipients fails", async () => {
+      const feeVaultAddress = await fundPaymentTokens(programMetadataPda, 1000);
+
+      await expectAnchorError(program.methods
+        .distributeFees()
+        .accounts({
+          programMetadata: programMetadataPda,
+          feeVault: feeVaultAddress,
+        })
+        .remainingAccounts([
+          {pubkey: paymentTokenAddress(minorityRecipient), isWritable: true, isSigner: false},
+          {pubkey: paymentTokenAddress(majorityRecipient), isWritable: true, isSigner: false},
+        ])
+        .rpc(), "FeeRecipientMismatch");
+    });
+
+    it("Distribute native fees", async () => {
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: programMetadataPda,
+          lamports: 3000000, //keeps both shares above the recipients' rent exempt minimum
+        })
+      ), [], {commitment: 'confirmed'});
+
+      const programMetadataInfo = await provider.connection.getAccountInfo(programMetadataPda, 'confirmed');
+      const rentExemptMinimum = await provider.connection.getMinimumBalanceForRentExemption(programMetadataInfo.data.length);
+      const total = programMetadataInfo.lamports - rentExemptMinimum;
+      const majorityShare = Math.floor(total * 7000 / 10000);
+
+      await program.methods
+        .distributeNativeFees()
+        .accounts({
+          programMetadata: programMetadataPda,
+        })
+        .remainingAccounts([
+          {pubkey: majorityRecipient, isWritable: true, isSigner: false},
+          {pubkey: minorityRecipient, isWritable: true, isSigner: false},
+        ])
+        .rpc({commitment: 'confirmed'});
+
+      expect(await provider.connection.getBalance(majorityRecipient, 'confirmed')).is.equal(majorityShare);
+      expect(await provider.connection.getBalance(minorityRecipient, 'confirmed')).is.equal(total - majorityShare);
+      expect(await provider.connection.getBalance(programMetadataPda, 'confirmed')).is.equal(rentExemptMinimum);
+    });
+
+    it("Fees collected before changing the fee account are still distributed", async () => {
+      const feeVaultAddress = await fundPaymentTokens(programMetadataPda, 50000);
+      const majorityBefore = await paymentTokenBalance(majorityRecipient);
+      const minorityBefore = await paymentTokenBalance(minorityRecipient);
+      const total = await paymentTokenBalance(programMetadataPda);
+
+      await program.methods
+        .changeFeeAccount()
+        .accounts({
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+          feeAccount: feeAccountPubkey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      await program.methods
+        .distributeFees()
+        .accounts({
+          programMetadata: programMetadataPda,
+          feeVault: feeVaultAddress,
+        })
+        .remainingAccounts([
+          {pubkey: paymentTokenAddress(majorityRecipient), isWritable: true, isSigner: false},
+          {pubkey: paymentTokenAddress(minorityRecipient), isWritable: true, isSigner: false},
+        ])
+        .rpc({commitment: 'confirmed'});
+
+      const distributed = (await paymentTokenBalance(majorityRecipient) - majorityBefore) + (await paymentTokenBalance(minorityRecipient) - minorityBefore);
+      expect(distributed).is.equal(total);
+      expect(await paymentTokenBalance(programMetadataPda)).is.equal(BigInt(0));
+    });
+  }); //fee distribution tests
+
+  describe("[Price Feed Tests]", () => {
+    const priceFeedPda = pda("price_feed", paymentTokenMintAddress.toBuffer());
+    let productPda: PublicKey;
+    let tokenAmount: number;
+
+    const configurePriceFeed = (maxAgeSeconds: number) => program.methods
+      .configurePriceFeed(maxAgeSeconds)
+      .accounts({
+        priceFeed: priceFeedPda,
+        updater: provider.publicKey,
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+    before(async () => {
+      //$2 a token, published just now
+      await program.methods
+        .updatePriceFeed(new anchor.BN(200000000), -8, new anchor.BN(Math.floor(new Date().getTime() / 1000) - 10))
+        .accounts({
+          priceFeed: priceFeedPda,
+          updater: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      //$3, quoted in millionths
+      productPda = await createTestProduct(1, 3000000, 10);
+      await program.methods
+        .setProductPriceFeed()
+        .accounts({
+          product: productPda,
+          priceFeed: priceFeedPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      const mint = await spl_token.getMint(provider.connection, paymentTokenMintAddress, 'confirmed', TOKEN_PROGRAM_ID);
+      tokenAmount = 1.5 * Math.pow(10, mint.decimals);
+    });
+
+    it("Feed priced purchase converts the quote to token units", async () => {
+      const { purchaseTicketPda } = await buyTestProduct(productPda, {
+        agreedPrice: tokenAmount,
+        payment: tokenAmount + PURCHASE_TRANSACTION_FEE,
+        priceFeed: priceFeedPda,
+      });
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.price.toNumber()).is.equal(tokenAmount);
+    });
+
+    it("Agreed price below the converted price is rejected", async () => {
+      await expectAnchorError(buyTestProduct(productPda, {
+        agreedPrice: tokenAmount - 1,
+        payment: tokenAmount + PURCHASE_TRANSACTION_FEE,
+        priceFeed: priceFeedPda,
+      }), "PriceIsGreaterThanPayment");
+    });
+
+    it("Stale price feed is rejected", async () => {
+      await configurePriceFeed(0);
+
+      try {
+        await expectAnchorError(buyTestProduct(productPda, {
+          agreedPrice: tokenAmount,
+          payment: tokenAmount + PURCHASE_TRANSACTION_FEE,
+          priceFeed: priceFeedPda,
+        }), "StalePriceFeed");
+      }
+      finally {
+        await configurePriceFeed(60);
+      }
+    });
+  });
+
+  describe("[Payout Split Tests]", () => {
+    const firstRecipient = anchor.web3.Keypair.generate().publicKey;
+    const secondRecipient = anchor.web3.Keypair.generate().publicKey;
+    const payoutSplitId = generateRandomU32();
+    const payoutSplitPda = pda("payout_split", creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(payoutSplitId,4,"setUint")));
+    let productPda: PublicKey;
+
+    const recipientAccounts = (owners: PublicKey[]) => owners.map(owner => (
+      {pubkey: paymentTokenAddress(owner), isWritable: true, isSigner: false}
+    ));
+
+    before(async () => {
+      await fundPaymentTokens(firstRecipient, 0);
+      await fundPaymentTokens(secondRecipient, 0);
+
+      await program.methods
+        .createPayoutSplit(payoutSplitId, [
+          {wallet: firstRecipient, basisPoints: 7000},
+          {wallet: secondRecipient, basisPoints: 3000},
+        ])
+        .accounts({
+          payoutSplit: payoutSplitPda,
+          creator: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      productPda = await createTestProduct(1, 1000000, 10);
+      await program.methods
+        .setProductPayoutSplit()
+        .accounts({
+          product: productPda,
+          payoutSplit: payoutSplitPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Immediate purchase is split between the recipients", async () => {
+      const firstBefore = await paymentTokenBalance(firstRecipient);
+      const secondBefore = await paymentTokenBalance(secondRecipient);
+
+      const { purchaseTicketPda } = await buyTestProduct(productPda, {
+        payoutSplit: payoutSplitPda,
+        remainingAccounts: recipientAccounts([firstRecipient, secondRecipient]),
+      });
+
+      expect(await paymentTokenBalance(firstRecipient) - firstBefore).is.equal(BigInt(700000));
+      expect(await paymentTokenBalance(secondRecipient) - secondBefore).is.equal(BigInt(300000));
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.payoutSplit).is.eql(payoutSplitPda);
+    });
+
+    it("Recipients out of order are rejected", async () => {
+      await expectAnchorError(buyTestProduct(productPda, {
+        payoutSplit: payoutSplitPda,
+        remainingAccounts: recipientAccounts([secondRecipient, firstRecipient]),
+      }), "PayoutRecipientMismatch");
+    });
+
+    it("Missing recipients are rejected", async () => {
+      await expectAnchorError(buyTestProduct(productPda, {
+        payoutSplit: payoutSplitPda,
+        remainingAccounts: recipientAccounts([firstRecipient]),
+      }), "PayoutRecipientMismatch");
+    });
+
+    it("Subscription payments and renewals are split between the recipients", async () => {
+      const price = 1000000;
+      const subscriber = await createFundedKeypair();
+      await fundPaymentTokens(subscriber.publicKey, (price + PURCHASE_TRANSACTION_FEE) * 2);
+      const subscriptionProductPda = await createTestProduct(2, price, 10);
+      const subscriptionPda = pda("subscription", subscriptionProductPda.toBuffer(), subscriber.publicKey.toBuffer());
+
+      await program.methods
+        .setProductSubscriptionPeriod(1)
+        .accounts({
+          product: subscriptionProductPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      await program.methods
+        .setProductPayoutSplit()
+        .accounts({
+          product: subscriptionProductPda,
+          payoutSplit: payoutSplitPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      const firstBefore = await paymentTokenBalance(firstRecipient);
+      const secondBefore = await paymentTokenBalance(secondRecipient);
+
+      await program.methods
+        .subscribe(new anchor.BN(price), new anchor.BN(PURCHASE_TRANSACTION_FEE), new anchor.BN(price + PURCHASE_TRANSACTION_FEE))
+        .accounts({
+          product: subscriptionProductPda,
+          subscription: subscriptionPda,
+          subscriberTokenAccount: paymentTokenAddress(subscriber.publicKey),
+          paymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
+          payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+          payTo: payToAccountPubkey,
+          payoutSplit: payoutSplitPda,
+          programMetadata: programMetadataPda,
+          feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+          feeAccount: feeAccountPubkey,
+          feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+          priceFeed: anchor.web3.SystemProgram.programId,
+          subscriber: subscriber.publicKey,
+        })
+        .remainingAccounts(recipientAccounts([firstRecipient, secondRecipient]))
+        .signers([subscriber])
+        .rpc({commitment: 'confirmed'});
+
+      expect(await paymentTokenBalance(firstRecipient) - firstBefore).is.equal(BigInt(700000));
+      expect(await paymentTokenBalance(secondRecipient) - secondBefore).is.equal(BigInt(300000));
+
+      const subscription = await program.account.subscription.fetch(subscriptionPda, 'confirmed');
+      expect(subscription.payoutSplit).is.eql(payoutSplitPda);
+      await waitForClockToPass(subscription.expiration.toNumber());
+
+      await program.methods
+        .renewSubscription()
+        .accounts({
+          subscription: subscriptionPda,
+          product: subscriptionProductPda,
+          subscriberTokenAccount: paymentTokenAddress(subscriber.publicKey),
+          paymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
+          payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+          payTo: payToAccountPubkey,
+          payoutSplit: payoutSplitPda,
+          programMetadata: programMetadataPda,
+          feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+          feeAccount: feeAccountPubkey,
+          feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+        })
+        .remainingAccounts(recipientAccounts([firstRecipient, secondRecipient]))
+        .rpc({commitment: 'confirmed'});
+
+      expect(await paymentTokenBalance(firstRecipient) - firstBefore).is.equal(BigInt(1400000));
+      expect(await paymentTokenBalance(secondRecipient) - secondBefore).is.equal(BigInt(600000));
+    });
+  });
+
+  describe("[Resale Tests]", () => {
+    const price = 1000000;
+
+    const setResalePolicy = (productPda: PublicKey, resalePolicy: {transfersDisabled: boolean, maxResaleBasisPoints: number, cooldownMinutes: number}) =>
+      program.methods
+        .setProductResalePolicy(resalePolicy)
+        .accounts({
+          product: productPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+    const listTicket = async (purchaseTicketPda: PublicKey, quantity: number, askingPrice: number) => {
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      const listingPda = pda("ticket_listing", purchaseTicketPda.toBuffer());
+
+      await program.methods
+        .listTicket(new anchor.BN(quantity), new anchor.BN(askingPrice))
+        .accounts({
+          listing: listingPda,
+          ticket: purchaseTicketPda,
+          productSnapshot: purchaseTicket.productSnapshot,
+          paymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          ticketAuthority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      return listingPda;
+    }
+
+    const transferTicket = async (purchaseTicketPda: PublicKey, quantity: number) => {
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      const nonce = generateRandomU16();
+      const destinationTicketPda = pda("purchase_ticket", purchaseTicket.productSnapshotMetadata.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+
+      return program.methods
+        .transferTicket(nonce, new anchor.BN(quantity))
+        .accounts({
+          destinationTicket: destinationTicketPda,
+          destinationTicketPayment: paymentTokenAddress(destinationTicketPda),
+          destinationTicketAuthority: secondaryAuthorityPubkey,
+          sourceTicket: purchaseTicketPda,
+          sourceTicketPayment: purchaseTicket.payment,
+          sourceTicketAuthority: creatorKeypair.publicKey,
+          productSnapshot: purchaseTicket.productSnapshot,
+          paymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          acceptedMint: acceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    }
+
+    it("Transfer is rejected when the snapshot disables transfers", async () => {
+      const productPda = await createTestProduct(2, price, 10);
+      await setResalePolicy(productPda, {transfersDisabled: true, maxResaleBasisPoints: 0, cooldownMinutes: 0});
+      const { purchaseTicketPda } = await buyTestProduct(productPda);
+
+      await expectAnchorError(transferTicket(purchaseTicketPda, 1), "TransfersDisabled");
+      await expectAnchorError(listTicket(purchaseTicketPda, 1, price), "TransfersDisabled");
+    });
+
+    it("Transfer is rejected within the cooldown", async () => {
+      const productPda = await createTestProduct(2, price, 10);
+      await setResalePolicy(productPda, {transfersDisabled: false, maxResaleBasisPoints: 0, cooldownMinutes: 60});
+      const { purchaseTicketPda } = await buyTestProduct(productPda);
+
+      await expectAnchorError(transferTicket(purchaseTicketPda, 1), "ResaleCooldown");
+    });
+
+    it("Listing above the resale cap is rejected", async () => {
+      const productPda = await createTestProduct(2, price, 10);
+      await setResalePolicy(productPda, {transfersDisabled: false, maxResaleBasisPoints: 15000, cooldownMinutes: 0});
+      const { purchaseTicketPda } = await buyTestProduct(productPda);
+
+      await expectAnchorError(listTicket(purchaseTicketPda, 1, 1500001), "ResalePriceTooHigh");
+
+      const listingPda = await listTicket(purchaseTicketPda, 1, 1500000);
+      const listing = await program.account.ticketListing.fetch(listingPda, 'confirmed');
+      expect(listing.price.toNumber()).is.equal(1500000);
+    });
+
+    it("Buy listed ticket pays the seller, the royalty and moves the escrow", async () => {
+      const quantity = 1;
+      const askingPrice = 1200000;
+      const royalty = 60000; //5% of the asking price
+      const productPda = await createTestProduct(2, price, 10);
+
+      await program.methods
+        .setProductRoyalty(500)
+        .accounts({
+          product: productPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      const { productSnapshotMetadataPda, purchaseTicketPda, purchaseTicketPaymentAddress } = await buyTestProduct(productPda, {quantity: 2});
+      const listingPda = await listTicket(purchaseTicketPda, quantity, askingPrice);
+
+      const buyer = await createFundedKeypair();
+      const buyerTokenAddress = await fundPaymentTokens(buyer.publicKey, askingPrice);
+      const sellerTokenAddress = await fundPaymentTokens(creatorKeypair.publicKey, 0);
+      const nonce = generateRandomU16();
+      const destinationTicketPda = pda("purchase_ticket", productSnapshotMetadataPda.toBuffer(), buyer.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const destinationTicketPaymentAddress = paymentTokenAddress(destinationTicketPda);
+
+      const sellerBefore = await paymentTokenBalance(creatorKeypair.publicKey);
+      const payToBefore = await paymentTokenBalance(payToAccountPubkey);
+      const sourceEscrowBefore = await paymentTokenBalance(purchaseTicketPda);
+
+      await program.methods
+        .buyListedTicket(nonce, new anchor.BN(quantity), new anchor.BN(askingPrice))
+        .accounts({
+          listing: listingPda,
+          destinationTicket: destinationTicketPda,
+          destinationTicketPayment: destinationTicketPaymentAddress,
+          sourceTicket: purchaseTicketPda,
+          sourceTicketPayment: purchaseTicketPaymentAddress,
+          productSnapshot: (await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed')).productSnapshot,
+          paymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
+          buyerTokenAccount: buyerTokenAddress,
+          sellerTokenAccount: sellerTokenAddress,
+          seller: creatorKeypair.publicKey,
+          payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+          payTo: payToAccountPubkey,
+          payoutSplit: anchor.web3.SystemProgram.programId,
+          programMetadata: programMetadataPda,
+          buyer: buyer.publicKey,
+        })
+        .signers([buyer])
+        .rpc({commitment: 'confirmed'});
+
+      expect(await paymentTokenBalance(creatorKeypair.publicKey) - sellerBefore).is.equal(BigInt(askingPrice - royalty));
+      expect(await paymentTokenBalance(payToAccountPubkey) - payToBefore).is.equal(BigInt(royalty));
+      expect(await paymentTokenBalance(buyer.publicKey)).is.equal(BigInt(0));
+      expect(sourceEscrowBefore - await paymentTokenBalance(purchaseTicketPda)).is.equal(BigInt(price * quantity));
+      expect(await paymentTokenBalance(destinationTicketPda)).is.equal(BigInt(price * quantity));
+
+      const sourceTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(sourceTicket.remainingQuantity.toNumber()).is.equal(1);
+      expect(sourceTicket.listedQuantity.toNumber()).is.equal(0);
+
+      const destinationTicket = await program.account.purchaseTicket.fetch(destinationTicketPda, 'confirmed');
+      expect(destinationTicket.authority).is.eql(buyer.publicKey);
+      expect(destinationTicket.remainingQuantity.toNumber()).is.equal(quantity);
+      expect(destinationTicket.price.toNumber()).is.equal(price);
+      expect(destinationTicket.fee.toNumber()).is.equal(0);
+      expect(await provider.connection.getAccountInfo(listingPda, 'confirmed')).is.null;
+    });
+  });
+
+  describe("[Layaway Tests]", () => {
+    const price = 1000000;
+    const deposit = 300000;
+    const firstRecipient = anchor.web3.Keypair.generate().publicKey;
+    const secondRecipient = anchor.web3.Keypair.generate().publicKey;
+    const payoutSplitId = generateRandomU32();
+    const payoutSplitPda = pda("payout_split", creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(payoutSplitId,4,"setUint")));
+    let productPda: PublicKey;
+    let purchaseTicketPda: PublicKey;
+
+    const buyLayaway = async (payment: number) => {
+      const product = await program.account.product.fetch(productPda, 'confirmed');
+      const nonce = generateRandomU16();
+      const buyerTokenAddress = await fundPaymentTokens(creatorKeypair.publicKey, payment);
+      const productSnapshotMetadataPda = pda("product_snapshot_metadata", productPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const ticketPda = pda("purchase_ticket", productSnapshotMetadataPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const ticketPaymentAddress = paymentTokenAddress(ticketPda);
+
+      await program.methods
+        .buyProductLayaway(nonce, new anchor.BN(1), product.price, new anchor.BN(PURCHASE_TRANSACTION_FEE),
+          product.expirationTimestamp, product.expirationMinutesAfterPurchase, product.expirationMinutesAfterRedemption)
+        .accounts({
+          product: productPda,
+          productSnapshotMetadata: productSnapshotMetadataPda,
+          productSnapshot: pda("product_snapshot", productSnapshotMetadataPda.toBuffer()),
+          purchaseTicket: ticketPda,
+          purchaseTicketPayment: ticketPaymentAddress,
+          purchaseTicketPaymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
+          buyer: creatorKeypair.publicKey,
+          buyFor: creatorKeypair.publicKey,
+          programMetadata: programMetadataPda,
+          feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+          feeAccount: feeAccountPubkey,
+          feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+          priceFeed: anchor.web3.SystemProgram.programId,
+          store: product.store,
+        })
+        .preInstructions([
+          spl_token.createAssociatedTokenAccountInstruction(
+            creatorKeypair.publicKey,
+            ticketPaymentAddress,
+            ticketPda,
+            paymentTokenMintAddress,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID),
+          spl_token.createTransferInstruction(
+            buyerTokenAddress,
+            ticketPaymentAddress,
+            creatorKeypair.publicKey,
+            payment,
+            [],
+            TOKEN_PROGRAM_ID),
+        ])
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      return ticketPda;
+    }
+
+    const payInstallment = async (amount: number) => {
+      const payerTokenAddress = await fundPaymentTokens(creatorKeypair.publicKey, amount);
+
+      return program.methods
+        .payInstallment(new anchor.BN(amount))
+        .accounts({
+          ticket: purchaseTicketPda,
+          ticketPayment: paymentTokenAddress(purchaseTicketPda),
+          paymentMint: paymentTokenMintAddress,
+          payerTokenAccount: payerTokenAddress,
+          payer: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    }
+
+    before(async () => {
+      await fundPaymentTokens(feeAccountPubkey, 0);
+      await fundPaymentTokens(payToAccountPubkey, 0);
+      await fundPaymentTokens(firstRecipient, 0);
+      await fundPaymentTokens(secondRecipient, 0);
+
+      await program.methods
+        .createPayoutSplit(payoutSplitId, [
+          {wallet: firstRecipient, basisPoints: 7000},
+          {wallet: secondRecipient, basisPoints: 3000},
+        ])
+        .accounts({
+          payoutSplit: payoutSplitPda,
+          creator: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      productPda = await createTestProduct(2, price, 10);
+      await program.methods
+        .setProductPayoutSplit()
+        .accounts({
+          product: productPda,
+          payoutSplit: payoutSplitPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      await program.methods
+        .setProductLayawayPolicy({paymentWindowMinutes: 1, minDepositBasisPoints: 2000, forfeitBasisPoints: 2500})
+        .accounts({
+          product: productPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Layaway purchase escrows the deposit and records the balance", async () => {
+      purchaseTicketPda = await buyLayaway(deposit + PURCHASE_TRANSACTION_FEE);
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.amountDue.toNumber()).is.equal(price - deposit);
+      expect(purchaseTicket.paymentDeadline.toNumber()).is.equal(purchaseTicket.timestamp.toNumber() + 60);
+      expect(purchaseTicket.forfeitBasisPoints).is.equal(2500);
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(1);
+      expect(await paymentTokenBalance(purchaseTicketPda)).is.equal(BigInt(deposit));
+
+      const product = await program.account.product.fetch(productPda, 'confirmed');
+      expect(product.inventory.toNumber()).is.equal(9);
+    });
+
+    it("Deposit over the price is rejected", async () => {
+      await expectAnchorError(buyLayaway(price + 1 + PURCHASE_TRANSACTION_FEE), "DepositIsMoreThanPrice");
+    });
+
+    it("Installment over the amount due is rejected", async () => {
+      await expectAnchorError(payInstallment(price - deposit + 1), "InstallmentIsMoreThanAmountDue");
+
+      await payInstallment(100000);
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.amountDue.toNumber()).is.equal(price - deposit - 100000);
+    });
+
+    it("Redemption is blocked until the ticket is paid in full", async () => {
+      const redemptionNonce = generateRandomU32();
+
+      await expectAnchorError(program.methods
+        .initiateRedemption(redemptionNonce, new anchor.BN(1), 0)
+        .accounts({
+          redemption: pda("redemption", purchaseTicketPda.toBuffer(), Buffer.from(uIntToBytes(redemptionNonce,4,"setUint"))),
+          purchaseTicket: purchaseTicketPda,
+          purchaseTicketAuthority: creatorKeypair.publicKey,
+          purchaseTicketPayment: paymentTokenAddress(purchaseTicketPda),
+          purchaseTicketPaymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          acceptedMint: acceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc(), "TicketIsNotPaidInFull");
+    });
+
+    it("Past the deadline installments are rejected and the forfeit is split", async () => {
+      const paid = deposit + 100000;
+      const forfeited = paid / 4;
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      await waitForClockToPass(purchaseTicket.paymentDeadline.toNumber());
+
+      await expectAnchorError(payInstallment(1), "PaymentDeadlinePassed");
+
+      const firstBefore = await paymentTokenBalance(firstRecipient);
+      const secondBefore = await paymentTokenBalance(secondRecipient);
+      const refundBefore = await paymentTokenBalance(creatorKeypair.publicKey);
+
+      await program.methods
+        .forfeitLayaway()
+        .accounts({
+          product: productPda,
+          ticket: purchaseTicketPda,
+          ticketPayment: paymentTokenAddress(purchaseTicketPda),
+          paymentMint: paymentTokenMintAddress,
+          payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+          payTo: payToAccountPubkey,
+          payoutSplit: payoutSplitPda,
+          refundTokenAccount: paymentTokenAddress(creatorKeypair.publicKey),
+          ticketAuthority: creatorKeypair.publicKey,
+        })
+        .remainingAccounts([firstRecipient, secondRecipient].map(owner => (
+          {pubkey: paymentTokenAddress(owner), isWritable: true, isSigner: false}
+        )))
+        .rpc({commitment: 'confirmed'});
+
+      expect(await paymentTokenBalance(firstRecipient) - firstBefore).is.equal(BigInt(70000));
+      expect(await paymentTokenBalance(secondRecipient) - secondBefore).is.equal(BigInt(30000));
+      expect(await paymentTokenBalance(creatorKeypair.publicKey) - refundBefore).is.equal(BigInt(paid - forfeited));
+      expect(await paymentTokenBalance(purchaseTicketPda)).is.equal(BigInt(0));
+
+      const forfeitedTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(forfeitedTicket.remainingQuantity.toNumber()).is.equal(0);
+      expect(forfeitedTicket.amountDue.toNumber()).is.equal(0);
+
+      const product = await program.account.product.fetch(productPda, 'confirmed');
+      expect(product.inventory.toNumber()).is.equal(10);
+    });
+  });
+
+  describe("[Subscription Tests]", () => {
+    const price = 1000000;
+    const allowance = (price + PURCHASE_TRANSACTION_FEE) * 3;
+    let subscriber: Keypair;
+    let productPda: PublicKey;
+    let subscriptionPda: PublicKey;
+
+    const subscribe = () => program.methods
+      .subscribe(new anchor.BN(price), new anchor.BN(PURCHASE_TRANSACTION_FEE), new anchor.BN(allowance))
+      .accounts({
+        product: productPda,
+        subscription: subscriptionPda,
+        subscriberTokenAccount: paymentTokenAddress(subscriber.publicKey),
+        paymentMint: paymentTokenMintAddress,
+        acceptedMint: acceptedMintPda,
+        payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+        payTo: payToAccountPubkey,
+        payoutSplit: anchor.web3.SystemProgram.programId,
+        programMetadata: programMetadataPda,
+        feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+        feeAccount: feeAccountPubkey,
+        feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+        priceFeed: anchor.web3.SystemProgram.programId,
+        subscriber: subscriber.publicKey,
+      })
+      .signers([subscriber])
+      .rpc({commitment: 'confirmed'});
+
+    const renewSubscription = () => program.methods
+      .renewSubscription()
+      .accounts({
+        subscription: subscriptionPda,
+        product: productPda,
+        subscriberTokenAccount: paymentTokenAddress(subscriber.publicKey),
+        paymentMint: paymentTokenMintAddress,
+        acceptedMint: acceptedMintPda,
+        payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+        payTo: payToAccountPubkey,
+        payoutSplit: anchor.web3.SystemProgram.programId,
+        programMetadata: programMetadataPda,
+        feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+        feeAccount: feeAccountPubkey,
+        feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+      })
+      .rpc({commitment: 'confirmed'});
+
+    before(async () => {
+      subscriber = await createFundedKeypair();
+      await fundPaymentTokens(subscriber.publicKey, allowance * 2);
+      await fundPaymentTokens(payToAccountPubkey, 0);
+      await fundPaymentTokens(feeAccountPubkey, 0);
+
+      productPda = await createTestProduct(2, price, 10);
+      subscriptionPda = pda("subscription", productPda.toBuffer(), subscriber.publicKey.toBuffer());
+
+      await program.methods
+        .setProductSubscriptionPeriod(1)
+        .accounts({
+          product: productPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Subscribe pays the first period and delegates the allowance", async () => {
+      const payToBefore = await paymentTokenBalance(payToAccountPubkey);
+
+      await subscribe();
+
+      expect(await paymentTokenBalance(payToAccountPubkey) - payToBefore).is.equal(BigInt(price));
+
+      const subscription = await program.account.subscription.fetch(subscriptionPda, 'confirmed');
+      expect(subscription.price.toNumber()).is.equal(price);
+      expect(subscription.agreedFee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(subscription.periodMinutes).is.equal(1);
+      expect(subscription.expiration.toNumber()).is.equal(subscription.timestamp.toNumber() + 60);
+      expect(subscription.status).is.equal(1);
+
+      const subscriberTokenAccount = await spl_token.getAccount(provider.connection, paymentTokenAddress(subscriber.publicKey), 'confirmed', TOKEN_PROGRAM_ID);
+      expect(subscriberTokenAccount.delegate).is.eql(subscriptionPda);
+      expect(subscriberTokenAccount.delegatedAmount).is.equal(BigInt(allowance));
+    });
+
+    it("Subscribing again while active is rejected", async () => {
+      await expectAnchorError(subscribe(), "SubscriptionIsActive");
+    });
+
+    it("Renewal before the period elapses is rejected", async () => {
+      await expectAnchorError(renewSubscription(), "SubscriptionPeriodHasntElapsed");
+    });
+
+    it("Renewal after the period draws on the allowance", async () => {
+      const before = await program.account.subscription.fetch(subscriptionPda, 'confirmed');
+      await waitForClockToPass(before.expiration.toNumber());
+      const payToBefore = await paymentTokenBalance(payToAccountPubkey);
+      const feeBefore = await paymentTokenBalance(feeAccountPubkey);
+
+      await renewSubscription();
+
+      expect(await paymentTokenBalance(payToAccountPubkey) - payToBefore).is.equal(BigInt(price));
+      expect(await paymentTokenBalance(feeAccountPubkey) - feeBefore).is.equal(BigInt(PURCHASE_TRANSACTION_FEE));
+
+      const subscription = await program.account.subscription.fetch(subscriptionPda, 'confirmed');
+      expect(subscription.renewals).is.equal(1);
+      expect(subscription.expiration.toNumber()).is.greaterThanOrEqual(before.expiration.toNumber() + 60);
+    });
+
+    it("Cancel stops renewals and revokes the delegate", async () => {
+      await program.methods
+        .cancelSubscription()
+        .accounts({
+          subscription: subscriptionPda,
+          product: productPda,
+          subscriberTokenAccount: paymentTokenAddress(subscriber.publicKey),
+          authority: subscriber.publicKey,
+        })
+        .signers([subscriber])
+        .rpc({commitment: 'confirmed'});
+
+      const subscription = await program.account.subscription.fetch(subscriptionPda, 'confirmed');
+      expect(subscription.status).is.equal(2);
+
+      const subscriberTokenAccount = await spl_token.getAccount(provider.connection, paymentTokenAddress(subscriber.publicKey), 'confirmed', TOKEN_PROGRAM_ID);
+      expect(subscriberTokenAccount.delegate).is.null;
+
+      await waitForClockToPass(subscription.expiration.toNumber());
+      await expectAnchorError(renewSubscription(), "SubscriptionIsCancelled");
+    });
+
+    it("Cancelled subscription can be subscribed to again", async () => {
+      await subscribe();
+
+      const subscription = await program.account.subscription.fetch(subscriptionPda, 'confirmed');
+      expect(subscription.status).is.equal(1);
+      expect(subscription.renewals).is.equal(0);
+      expect(subscription.expiration.toNumber()).is.equal(subscription.timestamp.toNumber() + 60);
+    });
+  });
+
+  describe("[Coupon Tests]", () => {
+    const price = 1000000;
+    const farExpiration = Math.floor(new Date().getTime() / 1000) + (60 * 60 * 24 * 30);
+    let productPda: PublicKey;
+
+    const createCoupon = async (code: string, discountType: number, discount: number, expirationTimestamp: number, maxUses: number, maxUsesPerBuyer: number) => {
+      const couponPda = pda("coupon", productPda.toBuffer(), Buffer.from(code));
+
+      await program.methods
+        .createProductCoupon(code, discountType, new anchor.BN(discount), new anchor.BN(expirationTimestamp), maxUses, maxUsesPerBuyer)
+        .accounts({
+          coupon: couponPda,
+          product: productPda,
+          productAuthority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      return couponPda;
+    }
+
+    const couponUsageAddress = (couponPda: PublicKey) =>
+      pda("coupon_usage", couponPda.toBuffer(), creatorKeypair.publicKey.toBuffer());
+
+    before(async () => {
+      productPda = await createTestProduct(1, price, 20);
+    });
+
+    it("Percentage coupon takes basis points off the price", async () => {
+      const couponPda = await createCoupon("PCT10", 1, 1000, farExpiration, 0, 0);
+      const payToBefore = await paymentTokenBalance(payToAccountPubkey);
+
+      const { purchaseTicketPda } = await buyTestProduct(productPda, {
+        coupon: couponPda,
+        payment: 900000 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.price.toNumber()).is.equal(900000);
+      expect(purchaseTicket.discount.toNumber()).is.equal(100000);
+      expect(await paymentTokenBalance(payToAccountPubkey) - payToBefore).is.equal(BigInt(900000));
+
+      const coupon = await program.account.coupon.fetch(couponPda, 'confirmed');
+      expect(coupon.uses).is.equal(1);
+    });
+
+    it("Fixed coupon is limited per buyer", async () => {
+      const couponPda = await createCoupon("FIXED25", 2, 250000, farExpiration, 10, 1);
+      const couponUsagePda = couponUsageAddress(couponPda);
+
+      const { purchaseTicketPda } = await buyTestProduct(productPda, {
+        coupon: couponPda,
+        couponUsage: couponUsagePda,
+        payment: 750000 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.price.toNumber()).is.equal(750000);
+      expect(purchaseTicket.discount.toNumber()).is.equal(250000);
+
+      const couponUsage = await program.account.couponUsage.fetch(couponUsagePda, 'confirmed');
+      expect(couponUsage.uses).is.equal(1);
+
+      await expectAnchorError(buyTestProduct(productPda, {
+        coupon: couponPda,
+        couponUsage: couponUsagePda,
+        payment: 750000 + PURCHASE_TRANSACTION_FEE,
+      }), "CouponIsUsedUp");
+    });
+
+    it("Coupon usage is created at an address that was sent lamports ahead of the buyer", async () => {
+      const couponPda = await createCoupon("PREFUNDED", 2, 100000, farExpiration, 0, 1);
+      const couponUsagePda = couponUsageAddress(couponPda);
+
+      await sendLamports(couponUsagePda, 1);
+
+      await buyTestProduct(productPda, {
+        coupon: couponPda,
+        couponUsage: couponUsagePda,
+        payment: 900000 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      const couponUsage = await program.account.couponUsage.fetch(couponUsagePda, 'confirmed');
+      expect(couponUsage.uses).is.equal(1);
+    });
+
+    it("Coupon past max uses is rejected", async () => {
+      const couponPda = await createCoupon("ONCE", 2, 100000, farExpiration, 1, 0);
+
+      await buyTestProduct(productPda, {
+        coupon: couponPda,
+        payment: 900000 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      await expectAnchorError(buyTestProduct(productPda, {
+        coupon: couponPda,
+        payment: 900000 + PURCHASE_TRANSACTION_FEE,
+      }), "CouponIsUsedUp");
+    });
+
+    it("Expired coupon is rejected", async () => {
+      const couponPda = await createCoupon("EXPIRED", 1, 1000, 1, 0, 0);
+
+      await expectAnchorError(buyTestProduct(productPda, {
+        coupon: couponPda,
+        payment: 900000 + PURCHASE_TRANSACTION_FEE,
+      }), "CouponIsExpired");
+    });
+  });
+
+  describe("[Price Tier Tests]", () => {
+    const price = 100000;
+    let productPda: PublicKey;
+
+    before(async () => {
+      productPda = await createTestProduct(1, price, 50);
+
+      await program.methods
+        .setProductPriceTiers([
+          { minQuantity: new anchor.BN(5), basisPoints: 1000 },
+          { minQuantity: new anchor.BN(10), basisPoints: 2000 },
+        ])
+        .accounts({
+          product: productPda,
+          priceTiers: pda("price_tiers", productPda.toBuffer()),
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Purchase below the first tier pays the list price", async () => {
+      const { purchaseTicketPda } = await buyTestProduct(productPda, {
+        quantity: 4,
+        payment: price * 4 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.price.toNumber()).is.equal(price);
+    });
+
+    it("Purchase reaching a tier pays the tiered price and snapshots the tiers", async () => {
+      const payToBefore = await paymentTokenBalance(payToAccountPubkey);
+
+      const { purchaseTicketPda, productSnapshotPda, priceTiersSnapshotPda } = await buyTestProduct(productPda, {
+        quantity: 10,
+        agreedPrice: 80000,
+        payment: 800000 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      expect(await paymentTokenBalance(payToAccountPubkey) - payToBefore).is.equal(BigInt(800000));
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.price.toNumber()).is.equal(80000);
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(10);
+
+      const priceTiersSnapshot = await program.account.priceTiers.fetch(priceTiersSnapshotPda, 'confirmed');
+      expect(priceTiersSnapshot.product).is.eql(productSnapshotPda);
+      expect(priceTiersSnapshot.tiers.length).is.equal(2);
+      expect(priceTiersSnapshot.tiers[0].minQuantity.toNumber()).is.equal(5);
+      expect(priceTiersSnapshot.tiers[0].basisPoints).is.equal(1000);
+      expect(priceTiersSnapshot.tiers[1].minQuantity.toNumber()).is.equal(10);
+      expect(priceTiersSnapshot.tiers[1].basisPoints).is.equal(2000);
+
+      const product = await program.account.product.fetch(productPda, 'confirmed');
+      expect(product.inventory.toNumber()).is.equal(50 - 4 - 10);
+    });
+
+    it("Tiers are snapshotted to an address that was sent lamports ahead of the buyer", async () => {
+      const nonce = generateRandomU16();
+      const productSnapshotMetadataPda = pda("product_snapshot_metadata", productPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const productSnapshotPda = pda("product_snapshot", productSnapshotMetadataPda.toBuffer());
+      await sendLamports(pda("price_tiers", productSnapshotPda.toBuffer()), 1);
+
+      const { priceTiersSnapshotPda } = await buyTestProduct(productPda, {
+        nonce,
+        quantity: 5,
+        agreedPrice: 90000,
+        payment: 450000 + PURCHASE_TRANSACTION_FEE,
+      });
+
+      const priceTiersSnapshot = await program.account.priceTiers.fetch(priceTiersSnapshotPda, 'confirmed');
+      expect(priceTiersSnapshot.product).is.eql(productSnapshotPda);
+      expect(priceTiersSnapshot.tiers.length).is.equal(2);
+    });
+  });
+
+  describe("[Dutch Auction Tests]", () => {
+    const startPrice = 1000000;
+    const floorPrice = 400000;
+    const decayPerMinute = 10000;
+    let startTimestamp: number;
+    let productPda: PublicKey;
+
+    before(async () => {
+      productPda = await createTestProduct(1, startPrice, 10);
+
+      //started half an hour ago on the validator's clock, so the price has already come down
+      startTimestamp = await provider.connection.getBlockTime(await provider.connection.getSlot('confirmed')) - 30 * 60;
+
+      await program.methods
+        .setProductDutchAuction({
+          startPrice: new anchor.BN(startPrice),
+          floorPrice: new anchor.BN(floorPrice),
+          startTimestamp: new anchor.BN(startTimestamp),
+          decayPerMinute: new anchor.BN(decayPerMinute),
+        })
+        .accounts({
+          product: productPda,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Purchase pays the decayed price", async () => {
+      const payToBefore = await paymentTokenBalance(payToAccountPubkey);
+
+      const { purchaseTicketPda } = await buyTestProduct(productPda, {
+        agreedPrice: startPrice,
+        payment: startPrice + PURCHASE_TRANSACTION_FEE,
+      });
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      const elapsedMinutes = Math.floor((purchaseTicket.timestamp.toNumber() - startTimestamp) / 60);
+      const decayedPrice = Math.max(startPrice - elapsedMinutes * decayPerMinute, floorPrice);
+      expect(decayedPrice).is.lessThan(startPrice);
+      expect(purchaseTicket.price.toNumber()).is.equal(decayedPrice);
+      expect(await paymentTokenBalance(payToAccountPubkey) - payToBefore).is.equal(BigInt(decayedPrice));
+    });
+
+    it("Agreed price below the decayed price is rejected", async () => {
+      await expectAnchorError(buyTestProduct(productPda, {
+        agreedPrice: floorPrice - 1,
+        payment: startPrice + PURCHASE_TRANSACTION_FEE,
+      }), "PriceIsGreaterThanPayment");
+    });
+  });
+
+  describe("[English Auction Tests]", () => {
+    const minBid = 500000;
+    const minIncrement = 50000;
+    const nonce = generateRandomU16();
+    let productPda: PublicKey;
+    let auctionPda: PublicKey;
+    let escrowAddress: PublicKey;
+    let productSnapshotMetadataPda: PublicKey;
+    let firstBidder: Keypair;
+    let secondBidder: Keypair;
+
+    const placeBid = (bidder: Keypair, amount: number, previousBidder: PublicKey) => program.methods
+      .placeBid(new anchor.BN(amount))
+      .accounts({
+        auction: auctionPda,
+        escrow: escrowAddress,
+        bidderTokenAccount: paymentTokenAddress(bidder.publicKey),
+        previousBidderTokenAccount: paymentTokenAddress(previousBidder),
+        programMetadata: programMetadataPda,
+        bidder: bidder.publicKey,
+      })
+      .signers([bidder])
+      .rpc({commitment: 'confirmed'});
+
+    before(async () => {
+      firstBidder = await createFundedKeypair();
+      secondBidder = await createFundedKeypair();
+      await fundPaymentTokens(firstBidder.publicKey, 1000000);
+      await fundPaymentTokens(secondBidder.publicKey, 1000000);
+      await fundPaymentTokens(feeAccountPubkey, 0);
+
+      productPda = await createTestProduct(2, minBid, 10);
+      auctionPda = pda("auction", productPda.toBuffer());
+      escrowAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, auctionPda, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+      productSnapshotMetadataPda = pda("product_snapshot_metadata", productPda.toBuffer(), creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+
+      //short enough on the validator's clock to settle within the test
+      const startTimestamp = await provider.connection.getBlockTime(await provider.connection.getSlot('confirmed'));
+
+      await program.methods
+        .createAuction(nonce, new anchor.BN(minBid), new anchor.BN(minIncrement), new anchor.BN(startTimestamp), new anchor.BN(startTimestamp + 30))
+        .accounts({
+          product: productPda,
+          auction: auctionPda,
+          productSnapshotMetadata: productSnapshotMetadataPda,
+          productSnapshot: pda("product_snapshot", productSnapshotMetadataPda.toBuffer()),
+          escrow: escrowAddress,
+          paymentMint: paymentTokenMintAddress,
+          acceptedMint: acceptedMintPda,
+          programMetadata: programMetadataPda,
+          feeTier: pda("fee_tier", creatorKeypair.publicKey.toBuffer()),
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Bids below the minimum increment are rejected", async () => {
+      await expectAnchorError(placeBid(firstBidder, minBid - 1, firstBidder.publicKey), "BidTooLow");
+      await placeBid(firstBidder, minBid, firstBidder.publicKey);
+
+      await expectAnchorError(placeBid(secondBidder, minBid + minIncrement - 1, firstBidder.publicKey), "BidTooLow");
+    });
+
+    it("Outbid bidder is refunded", async () => {
+      const firstBidderBefore = await paymentTokenBalance(firstBidder.publicKey);
+
+      await placeBid(secondBidder, minBid + minIncrement, firstBidder.publicKey);
+
+      expect(await paymentTokenBalance(firstBidder.publicKey) - firstBidderBefore).is.equal(BigInt(minBid));
+      expect(await paymentTokenBalance(auctionPda)).is.equal(BigInt(minBid + minIncrement));
+
+      const auction = await program.account.auction.fetch(auctionPda, 'confirmed');
+      expect(auction.highestBidder).is.eql(secondBidder.publicKey);
+      expect(auction.highestBid.toNumber()).is.equal(minBid + minIncrement);
+      expect(auction.bids).is.equal(2);
+    });
+
+    it("Settlement takes the fee and issues the winner a ticket", async () => {
+      const winningBid = minBid + minIncrement;
+      const auction = await program.account.auction.fetch(auctionPda, 'confirmed');
+      await waitForClockToPass(auction.endTimestamp.toNumber());
+
+      //tokens sent to the escrow outside of bidding don't inflate the ticket's price. they go to pay_to
+      const strayTokens = 4321;
+      await fundPaymentTokens(auctionPda, strayTokens);
+      const feeBefore = await paymentTokenBalance(feeAccountPubkey);
+      const purchaseTicketPda = pda("purchase_ticket", productSnapshotMetadataPda.toBuffer(), secondBidder.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const purchaseTicketPaymentAddress = paymentTokenAddress(purchaseTicketPda);
+
+      await program.methods
+        .settleAuction()
+        .accounts({
+          auction: auctionPda,
+          escrow: escrowAddress,
+          authority: creatorKeypair.publicKey,
+          productSnapshotMetadata: productSnapshotMetadataPda,
+          productSnapshot: auction.productSnapshot,
+          store: anchor.web3.SystemProgram.programId,
+          purchaseTicket: purchaseTicketPda,
+          purchaseTicketPayment: purchaseTicketPaymentAddress,
+          paymentMint: paymentTokenMintAddress,
+          programMetadata: programMetadataPda,
+          feeTokenAccount: paymentTokenAddress(feeAccountPubkey),
+          feeAccount: feeAccountPubkey,
+          payToTokenAccount: paymentTokenAddress(payToAccountPubkey),
+          payer: secondBidder.publicKey,
+        })
+        .signers([secondBidder])
+        .rpc({commitment: 'confirmed'});
+
+      //pay_to and the fee account are the same wallet in these tests
+      expect(await paymentTokenBalance(feeAccountPubkey) - feeBefore).is.equal(BigInt(PURCHASE_TRANSACTION_FEE + strayTokens));
+      expect(await paymentTokenBalance(purchaseTicketPda)).is.equal(BigInt(winningBid - PURCHASE_TRANSACTION_FEE));
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.authority).is.eql(secondBidder.publicKey);
+      expect(purchaseTicket.productSnapshot).is.eql(auction.productSnapshot);
+      expect(purchaseTicket.price.toNumber()).is.equal(winningBid - PURCHASE_TRANSACTION_FEE);
+      expect(purchaseTicket.fee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(1);
+
+      expect(await provider.connection.getAccountInfo(escrowAddress)).is.null;
+      expect(await provider.connection.getAccountInfo(auctionPda)).is.null;
+    });
+  });
+
+  describe("[Gift Tests]", () => {
+    const price = 1000000;
+    const secret = Buffer.from(Keypair.generate().secretKey.slice(0, 32));
+    const recipient = Keypair.generate();
+    let productPda: PublicKey;
+    let purchaseTicketPda: PublicKey;
+    let giftPda: PublicKey;
+
+    const claimGift = (claimSecret: Buffer, claimant: Keypair) => program.methods
+      .claimGift(claimSecret)
+      .accounts({
+        gift: giftPda,
+        ticket: purchaseTicketPda,
+        giver: creatorKeypair.publicKey,
+        recipient: claimant.publicKey,
+      })
+      .signers([claimant])
+      .rpc({commitment: 'confirmed'});
+
+    const reclaimGift = () => program.methods
+      .reclaimGift()
+      .accounts({
+        gift: giftPda,
+        product: productPda,
+        ticket: purchaseTicketPda,
+        ticketPayment: paymentTokenAddress(purchaseTicketPda),
+        paymentReturn: paymentTokenAddress(creatorKeypair.publicKey),
+        paymentMint: paymentTokenMintAddress,
+        programMetadata: programMetadataPda,
+        giver: creatorKeypair.publicKey,
+      })
+      .signers([creatorKeypair])
+      .rpc({commitment: 'confirmed'});
+
+    before(async () => {
+      productPda = await createTestProduct(2, price, 10);
+      ({ purchaseTicketPda } = await buyTestProduct(productPda));
+      giftPda = pda("gift", purchaseTicketPda.toBuffer());
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+
+      await program.methods
+        .createGift(Array.from(createHash('sha256').update(secret).update(recipient.publicKey.toBuffer()).digest()), 1)
+        .accounts({
+          gift: giftPda,
+          ticket: purchaseTicketPda,
+          productSnapshot: purchaseTicket.productSnapshot,
+          programMetadata: programMetadataPda,
+          giver: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+    });
+
+    it("Claim with the wrong secret is rejected", async () => {
+      await expectAnchorError(claimGift(Buffer.from(Keypair.generate().secretKey.slice(0, 32)), recipient), "InvalidGiftSecret");
+    });
+
+    it("Claim by anyone but the recipient is rejected", async () => {
+      await expectAnchorError(claimGift(secret, Keypair.generate()), "InvalidGiftSecret");
+    });
+
+    it("Reclaim before the claim deadline is rejected", async () => {
+      await expectAnchorError(reclaimGift(), "GiftClaimDeadlineNotPassed");
+    });
+
+    it("Reclaim after the claim deadline refunds the giver", async () => {
+      const gift = await program.account.gift.fetch(giftPda, 'confirmed');
+      await waitForClockToPass(gift.claimDeadline.toNumber());
+      const refundBefore = await paymentTokenBalance(creatorKeypair.publicKey);
+      const productBefore = await program.account.product.fetch(productPda, 'confirmed');
 
-      it("Transfer Ticket", async () => {
-        const quantity = 1;
-        const purchaseNonce = generateRandomU16();
-        const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-        
-        const [destinationTicketPda, destinationTicketPdaBump] = PublicKey.findProgramAddressSync(
-          [
-            anchor.utils.bytes.utf8.encode("purchase_ticket"),
-            purchaseTicketBefore.productSnapshotMetadata.toBuffer(),
-            purchaseTicketBefore.authority.toBuffer(),
-            Buffer.from(uIntToBytes(purchaseNonce,2,"setUint"))
-          ], program.programId);
+      await reclaimGift();
 
-        const sourceTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
-          paymentTokenMintAddress,
-          purchaseTicketPda,
-          true,
-          TOKEN_PROGRAM_ID,
-          ASSOCIATED_TOKEN_PROGRAM_ID);
+      expect(await paymentTokenBalance(creatorKeypair.publicKey) - refundBefore).is.equal(BigInt(price));
 
-        const destinationTicketPaymentAddress = await spl_token.getAssociatedTokenAddress(
-          paymentTokenMintAddress,
-          destinationTicketPda,
-          true,
-          TOKEN_PROGRAM_ID,
-          ASSOCIATED_TOKEN_PROGRAM_ID);       
-        
-        const sourceTicketPaymentBefore = await spl_token.getAccount(provider.connection, sourceTicketPaymentAddress);
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.authority).is.eql(creatorKeypair.publicKey);
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(0);
 
-        const tx = await program.methods
-          .transferTicket(purchaseNonce, new anchor.BN(quantity))
-          .accounts({
-            destinationTicket:destinationTicketPda,
-            destinationTicketPayment: destinationTicketPaymentAddress,
-            destinationTicketAuthority: secondaryAuthorityPubkey,
-            sourceTicket: purchaseTicketPda,
-            sourceTicketPayment: sourceTicketPaymentAddress,
-            sourceTicketAuthority: buyForKeypair.publicKey,
-            paymentMint: paymentTokenMintAddress,
-          })
-          .transaction();
+      const product = await program.account.product.fetch(productPda, 'confirmed');
+      expect(product.inventory.toNumber()).is.equal(productBefore.inventory.toNumber() + 1);
+      expect(await provider.connection.getAccountInfo(giftPda)).is.null;
+    });
+  });
 
-        //tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
-        tx.feePayer = buyForKeypair.publicKey;  
-        const response = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [buyForKeypair], {commitment: 'finalized'});  
-        //console.info('transaction signature: ', response);
-        
-        const purchaseTicketAfter = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-        expect(purchaseTicketAfter.redeemed.toNumber()).is.equal(purchaseTicketBefore.redeemed.toNumber());
-        expect(purchaseTicketAfter.pendingRedemption.toNumber()).is.equal(purchaseTicketBefore.pendingRedemption.toNumber());
-        expect(purchaseTicketAfter.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - quantity);
+  describe("[Migration Tests]", () => {
+    //loaded by the test validator from tests/fixtures, see Anchor.toml
+    const programMetadataV0 = new PublicKey("9LkVVB1ZiYiEHyVNHGVi9c7Xam8HvsN2LufebBCES2QM");
+    const purchaseTicketV0 = new PublicKey("DtBVaA7TWBvGTGoDm3gtfgU2qyyWu9XZfwMGMW1GHaAr");
+    const legacyPaymentMint = new PublicKey("F6g9cmPtNAec9GYBF4s9vtX6hCE9eUxnFcv3bL8WsNuj");
 
-        const destinationTicket = await program.account.purchaseTicket.fetch(destinationTicketPda);
-        expect(destinationTicket.bump).is.equal(destinationTicketPdaBump);
-        expect(destinationTicket.product).is.eql(purchaseTicketBefore.product);
-        expect(destinationTicket.productSnapshotMetadata).is.eql(purchaseTicketBefore.productSnapshotMetadata);
-        expect(destinationTicket.productSnapshot).is.eql(purchaseTicketBefore.productSnapshot);
-        expect(destinationTicket.buyer).is.eql(purchaseTicketBefore.authority);
-        expect(destinationTicket.payTo).is.eql(purchaseTicketBefore.payTo);
-        expect(destinationTicket.authority).is.eql(secondaryAuthorityPubkey);
-        expect(destinationTicket.redeemed.toNumber()).is.equal(0);
-        expect(destinationTicket.pendingRedemption.toNumber()).is.equal(0);
-        expect(destinationTicket.remainingQuantity.toNumber()).is.equal(quantity);
-        expect(destinationTicket.nonce).is.equal(purchaseNonce);
-        expect(destinationTicket.payment).is.eql(destinationTicketPaymentAddress);
-        expect(destinationTicket.slot.toNumber()).is.greaterThan(purchaseTicketBefore.slot.toNumber());
-        expect(destinationTicket.timestamp.toNumber()).is.greaterThan(purchaseTicketBefore.timestamp.toNumber());
-  
-        const destinationTicketPayment = await spl_token.getAccount(provider.connection, destinationTicketPaymentAddress);
-        expect(destinationTicketPayment.address).is.eql(destinationTicketPaymentAddress);
-        expect(destinationTicketPayment.mint).is.eql(paymentTokenMintAddress);
-        expect(destinationTicketPayment.amount).is.equal(BigInt(purchaseTicketBefore.price.toNumber() * quantity));
+    it("Migrate version 0 program metadata", async () => {
+      const legacyAcceptedMintPda = pda("accepted_mint", legacyPaymentMint.toBuffer());
 
-        const sourceTicketPaymentAfter = await spl_token.getAccount(provider.connection, sourceTicketPaymentAddress);
-        expect(sourceTicketPaymentAfter.address).is.eql(sourceTicketPaymentAddress);
-        expect(sourceTicketPaymentAfter.mint).is.eql(paymentTokenMintAddress);
-        expect(sourceTicketPaymentAfter.amount).is.equal(sourceTicketPaymentBefore.amount - BigInt(purchaseTicketBefore.price.toNumber() * quantity));
-      });
+      await program.methods
+        .migrateProgramMetadata()
+        .accounts({
+          account: programMetadataV0,
+          legacyAcceptedMint: legacyAcceptedMintPda,
+          payer: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
 
-      describe("[Redeem Lone Product Ticket]", () => 
-      {
-        let purchaseTicket;
-        let redemptionNonce, redemptionPda, redemptionPdaBump;
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataV0, 'confirmed');
+      expect(programMetadata.version).is.equal(2);
+      expect(programMetadata.initialized).is.equal(true);
+      expect(programMetadata.fee.flatFee.toNumber()).is.equal(10000);
+      expect(programMetadata.fee.basisPoints).is.equal(0);
+      expect(programMetadata.multisig).is.eql(PublicKey.default);
+      expect(programMetadata.pendingFeeTimestamp.toNumber()).is.equal(0);
+      expect(programMetadata.paused.toNumber()).is.equal(0);
+      expect(programMetadata.feeRecipients.length).is.equal(0);
+
+      const legacyAcceptedMint = await program.account.acceptedMint.fetch(legacyAcceptedMintPda, 'confirmed');
+      expect(legacyAcceptedMint.mint).is.eql(legacyPaymentMint);
+      expect(legacyAcceptedMint.enabled).is.equal(true);
+      expect(legacyAcceptedMint.feeOverride).is.equal(false);
+
+      await expectAnchorError(program.methods
+        .migrateProgramMetadata()
+        .accounts({
+          account: programMetadataV0,
+          legacyAcceptedMint: legacyAcceptedMintPda,
+          payer: provider.publicKey,
+        })
+        .rpc(), "AccountIsCurrentVersion");
+    });
 
-        before(async () => {
-          return new Promise<void>(async (resolve,reject)=>{
-            purchaseTicket = await program.account
-              .purchaseTicket
-              .fetch(purchaseTicketPda)
-              .catch(reject);
-            
-            if(!purchaseTicket)
-              return;
-            do {
-              redemptionNonce = generateRandomU32(); //await provider.connection.getSlot();
-              [redemptionPda, redemptionPdaBump] = PublicKey.findProgramAddressSync(
-                [
-                  anchor.utils.bytes.utf8.encode("redemption"),
-                  purchaseTicketPda.toBuffer(),
-                  Buffer.from(uIntToBytes(redemptionNonce,4,"setUint")),
-                ], program.programId);
-            }while((await program.account.redemption.fetchNullable(redemptionPda)) != null);
-            
-            resolve();
-          });
-        });
+    it("Migrate version 0 purchase ticket", async () => {
+      await program.methods
+        .migrateTicket()
+        .accounts({
+          account: purchaseTicketV0,
+          payer: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketV0, 'confirmed');
+      expect(purchaseTicket.version).is.equal(8);
+      expect(purchaseTicket.authority).is.eql(new PublicKey("4ztuTVmiUzvk12Nxgyo3Ky1vs5Ey1xWUg3HyYjUsCLYF"));
+      expect(purchaseTicket.payment).is.eql(new PublicKey("AEkk4CP2HPdMxqo4Arjzdc3zSuybADB7XTP1eV4x7y84"));
+      expect(purchaseTicket.remainingQuantity.toNumber()).is.equal(2);
+      expect(purchaseTicket.redeemed.toNumber()).is.equal(1);
+      expect(purchaseTicket.nonce).is.equal(7);
+      expect(purchaseTicket.price.toNumber()).is.equal(1000000);
+      expect(purchaseTicket.fee.toNumber()).is.equal(0);
+      expect(purchaseTicket.paymentMint).is.eql(legacyPaymentMint);
+      expect(purchaseTicket.payoutSplit).is.eql(PublicKey.default);
+      expect(purchaseTicket.amountDue.toNumber()).is.equal(0);
+      expect(purchaseTicket.dispute).is.eql(PublicKey.default);
+      expect(purchaseTicket.arbiter).is.eql(PublicKey.default);
+
+      const accountInfo = await provider.connection.getAccountInfo(purchaseTicketV0, 'confirmed');
+      expect(accountInfo.data.length).is.equal(program.account.purchaseTicket.size);
+    });
+  }); //migration tests
 
-        it("Create Lone Product Ticket Taker", async () => {          
-          const txSuccess = await program.methods
-            .createProductTicketTaker()
-            .accounts({
-              ticketTaker: loneProductTicketTakerPda,
-              taker: ticketTakerKeypair.publicKey,
-              product: loneProductPda,
-              productAuthority: creatorKeypair.publicKey
-            })
-            .transaction();
-    
-          const txSucceeded = await anchor.web3.sendAndConfirmTransaction(provider.connection, txSuccess, [creatorKeypair]);
-    
-          const loneProductTicketTaker = await program.account.ticketTaker.fetch(loneProductTicketTakerPda);
-          expect(loneProductTicketTaker.bump).is.equal(loneProductTicketTakerPdaBump);
-          expect(loneProductTicketTaker.version).is.equal(0);
-          expect(loneProductTicketTaker.taker).is.eql(ticketTakerKeypair.publicKey);
-          expect(loneProductTicketTaker.entityType).is.equal(2);
-          expect(loneProductTicketTaker.authorizedBy).is.eql(creatorKeypair.publicKey);
-          expect(loneProductTicketTaker.enabledSlot.toNumber()).is.greaterThan(0);
-          expect(loneProductTicketTaker.enabledTimestamp.toNumber()).is.greaterThan(0);
-          expect(loneProductTicketTaker.disabledSlot.toNumber()).is.equal(0);
-          expect(loneProductTicketTaker.disabledTimestamp.toNumber()).is.equal(0);
-        });
+  describe("[Native Payment Tests]", () => {
+    const nativeProductId = generateRandomU32();
+    const nativeProductPrice = new BN(5000000); //lamports
+    let [nativeProductPda, nativeProductPdaBump] = PublicKey.findProgramAddressSync(
+      [
+        anchor.utils.bytes.utf8.encode("product"),
+        creatorKeypair.publicKey.toBuffer(),
+        Buffer.from(uIntToBytes(nativeProductId,4,"setUint"))
+      ], program.programId);
+    let [nativeAcceptedMintPda, nativeAcceptedMintPdaBump] = PublicKey.findProgramAddressSync(
+      [
+        anchor.utils.bytes.utf8.encode("accepted_mint"),
+        anchor.web3.SystemProgram.programId.toBuffer(),
+      ], program.programId);
+
+    it("Add native accepted mint", async () => {
+      await program.methods
+      .addNativeAcceptedMint()
+      .accounts({
+        acceptedMint: nativeAcceptedMintPda,
+        programMetadata: programMetadataPda,
+        authority: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
+
+      const acceptedMint = await program.account.acceptedMint.fetch(nativeAcceptedMintPda, 'confirmed');
+      expect(acceptedMint.mint).is.eql(anchor.web3.SystemProgram.programId);
+      expect(acceptedMint.enabled).is.equal(true);
+    });
+
+    it("Create and Buy Native Product - Immediate Redemption", async () => {
+      const data = compress({displayName: productName, displayDescription: productDescription});
+      const redemptionType = 1; //immediate
+
+      const createTx = await program.methods
+      .createProduct(nativeProductId, 0, nativeProductPrice, productInventory, redemptionType,
+        new anchor.BN(0), 0, 0,
+        productName.toLowerCase(), productDescription.toLowerCase(), data)
+      .accounts({
+        product: nativeProductPda,
+        creator: creatorKeypair.publicKey,
+        authority: creatorKeypair.publicKey,
+        secondaryAuthority: secondaryAuthorityPubkey,
+        payTo: payToAccountPubkey,
+        acceptedMint: nativeAcceptedMintPda,
+      })
+      .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, createTx, [creatorKeypair], {commitment: 'confirmed'});
+
+      const nativeProduct = await program.account.product.fetch(nativeProductPda, 'confirmed');
+      expect(nativeProduct.paymentMint).is.eql(anchor.web3.SystemProgram.programId);
+
+      const nonce = generateRandomU16();
+      const [productSnapshotMetadataPda] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("product_snapshot_metadata"),
+          nativeProductPda.toBuffer(),
+          creatorKeypair.publicKey.toBuffer(),
+          Buffer.from(uIntToBytes(nonce,2,"setUint")),
+        ], program.programId);
+      const [productSnapshotPda] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("product_snapshot"),
+          productSnapshotMetadataPda.toBuffer(),
+        ], program.programId);
+      const [purchaseTicketPda] = PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("purchase_ticket"),
+          productSnapshotMetadataPda.toBuffer(),
+          creatorKeypair.publicKey.toBuffer(),
+          Buffer.from(uIntToBytes(nonce,2,"setUint")),
+        ], program.programId);
+
+      const payToBalanceBefore = await provider.connection.getBalance(payToAccountPubkey, 'confirmed');
+
+      const buyTx = await program.methods
+      .buyProductNative(nonce, new anchor.BN(1), nativeProduct.price, new anchor.BN(PURCHASE_TRANSACTION_FEE),
+        nativeProduct.expirationTimestamp, nativeProduct.expirationMinutesAfterPurchase, nativeProduct.expirationMinutesAfterRedemption)
+      .accounts({
+        product: nativeProductPda,
+        productSnapshotMetadata: productSnapshotMetadataPda,
+        productSnapshot: productSnapshotPda,
+        purchaseTicket: purchaseTicketPda,
+        acceptedMint: nativeAcceptedMintPda,
+        payTo: nativeProduct.payTo,
+        buyer: creatorKeypair.publicKey,
+        buyFor: creatorKeypair.publicKey,
+        programMetadata: programMetadataPda,
+        feeAccount: feeAccountPubkey,
+        feeTier: creatorFeeTierPda,
+        referral: anchor.web3.SystemProgram.programId,
+        referrer: nativeProduct.payTo,
+        priceFeed: anchor.web3.SystemProgram.programId,
+        store: anchor.web3.SystemProgram.programId,
+        payoutSplit: anchor.web3.SystemProgram.programId,
+      })
+      .transaction();
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, buyTx, [creatorKeypair], {commitment: 'confirmed'});
+
+      //payTo and the fee account are the same wallet in these tests
+      const payToBalance = await provider.connection.getBalance(payToAccountPubkey, 'confirmed');
+      expect(payToBalance).is.equal(payToBalanceBefore + nativeProductPrice.toNumber() + PURCHASE_TRANSACTION_FEE);
+
+      const purchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(purchaseTicket.paymentMint).is.eql(anchor.web3.SystemProgram.programId);
+      expect(purchaseTicket.payment).is.eql(purchaseTicketPda);
+      expect(purchaseTicket.redeemed.toNumber()).is.equal(1);
+      expect(purchaseTicket.fee.toNumber()).is.equal(PURCHASE_TRANSACTION_FEE);
+    });
+
+    it("Native ticket is disputed and the arbiter's ruling splits its escrowed lamports", async () => {
+      const disputeStoreId = generateRandomU16();
+      const disputeStorePda = pda("store", creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(disputeStoreId,2,"setUint")));
+      const disputeProductId = generateRandomU32();
+      const disputeProductPda = pda("product", creatorKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(disputeProductId,4,"setUint")));
+      const arbiterKeypair = await createFundedKeypair();
+      const buyerKeypair = await createFundedKeypair();
+      const storeData = compress({displayName: storeName, displayDescription: storeDescription});
+      const productData = compress({displayName: productName, displayDescription: productDescription});
+
+      await program.methods
+        .createStore(disputeStoreId, 1, storeName.toLowerCase(), storeDescription.toLowerCase(), storeData)
+        .accounts({
+          store: disputeStorePda,
+          creator: creatorKeypair.publicKey,
+          authority: creatorKeypair.publicKey,
+          secondaryAuthority: storeSecondaryAuthorityKeypair.publicKey,
+          programMetadata: programMetadataPda,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      await program.methods
+        .setStoreArbiter()
+        .accounts({
+          store: disputeStorePda,
+          arbiter: arbiterKeypair.publicKey,
+          authority: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      await program.methods
+        .createStoreProduct(disputeProductId, 0, nativeProductPrice, productInventory, 2,
+          new anchor.BN(0), 0, 0, productName.toLowerCase(), productDescription.toLowerCase(), productData)
+        .accounts({
+          product: disputeProductPda,
+          store: disputeStorePda,
+          creator: creatorKeypair.publicKey,
+          authority: creatorKeypair.publicKey,
+          secondaryAuthority: secondaryAuthorityPubkey,
+          payTo: payToAccountPubkey,
+          acceptedMint: nativeAcceptedMintPda,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
 
-        it("Initiate Redemption", async()=>{
-          const quantity = 1;
-          const expirationMinutes = 5;
+      const nonce = generateRandomU16();
+      const productSnapshotMetadataPda = pda("product_snapshot_metadata", disputeProductPda.toBuffer(), buyerKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
+      const purchaseTicketPda = pda("purchase_ticket", productSnapshotMetadataPda.toBuffer(), buyerKeypair.publicKey.toBuffer(), Buffer.from(uIntToBytes(nonce,2,"setUint")));
 
-          const tx = await program.methods
-            .initiateRedemption(redemptionNonce, new anchor.BN(quantity), expirationMinutes)
-            .accounts({
-              redemption: redemptionPda,
-              purchaseTicket: purchaseTicketPda,
-              purchaseTicketAuthority: purchaseTicket.authority, //buyFor address
-              purchaseTicketPayment: purchaseTicket.payment,
-              purchaseTicketPaymentMint: paymentTokenMintAddress,
-            })
-            .transaction();
-        
-          tx.feePayer = buyForKeypair.publicKey;          
-          //tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;  
+      await program.methods
+        .buyProductNative(nonce, new anchor.BN(1), nativeProductPrice, new anchor.BN(PURCHASE_TRANSACTION_FEE),
+          new anchor.BN(0), 0, 0)
+        .accounts({
+          product: disputeProductPda,
+          productSnapshotMetadata: productSnapshotMetadataPda,
+          productSnapshot: pda("product_snapshot", productSnapshotMetadataPda.toBuffer()),
+          purchaseTicket: purchaseTicketPda,
+          acceptedMint: nativeAcceptedMintPda,
+          payTo: payToAccountPubkey,
+          buyer: buyerKeypair.publicKey,
+          buyFor: buyerKeypair.publicKey,
+          programMetadata: programMetadataPda,
+          feeAccount: feeAccountPubkey,
+          feeTier: pda("fee_tier", disputeStorePda.toBuffer()),
+          referral: anchor.web3.SystemProgram.programId,
+          referrer: payToAccountPubkey,
+          priceFeed: anchor.web3.SystemProgram.programId,
+          store: disputeStorePda,
+          payoutSplit: anchor.web3.SystemProgram.programId,
+        })
+        .signers([buyerKeypair])
+        .rpc({commitment: 'confirmed'});
 
-          const txSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [buyForKeypair], {commitment: 'finalized'});
-          //console.info('redemption init signature: ', txSignature);
-    
-          const redemptionAccount = await program.account.redemption.fetch(redemptionPda);
-          expect(redemptionAccount.bump).is.equal(redemptionPdaBump);
-          expect(redemptionAccount.version).is.equal(0);
-          expect(redemptionAccount.initSlot.toNumber()).is.greaterThan(0);
-          expect(redemptionAccount.initTimestamp.toNumber()).is.greaterThan(0);
-          expect(redemptionAccount.store).is.eql(purchaseTicket.store);
-          expect(redemptionAccount.product).is.eql(purchaseTicket.product);
-          expect(redemptionAccount.productSnapshotMetadata).is.eql(purchaseTicket.productSnapshotMetadata);
-          expect(redemptionAccount.productSnapshot).is.eql(purchaseTicket.productSnapshot);
-          expect(redemptionAccount.purchaseTicket).is.eql(purchaseTicketPda);
-          expect(redemptionAccount.purchaseTicketSigner).is.eql(purchaseTicket.authority);
-          expect(redemptionAccount.buyer).is.eql(purchaseTicket.buyer);
-          expect(redemptionAccount.payTo).is.eql(purchaseTicket.payTo);
-          //expect(redemptionAccount.quantity.toNumber()).is.equal(purchaseTicket.remainingQuantity.toNumber());
-          expect(redemptionAccount.redeemQuantity.toNumber()).is.equal(quantity);
-          expect(redemptionAccount.price.toNumber()).is.equal(purchaseTicket.price.toNumber());
-          expect(redemptionAccount.ticketTaker).is.eql(PublicKey.default);
-          expect(redemptionAccount.ticketTakerSigner).is.eql(PublicKey.default);
-          expect(redemptionAccount.status).is.equal(0);
-          expect(redemptionAccount.nonce).is.equal(redemptionNonce);
-          expect(redemptionAccount.takeExpiration.toNumber()).is.greaterThan(0);
+      const disputePda = pda("dispute", purchaseTicketPda.toBuffer());
+      await program.methods
+        .openDispute()
+        .accounts({
+          dispute: disputePda,
+          ticket: purchaseTicketPda,
+          store: disputeStorePda,
+          redemption: anchor.web3.SystemProgram.programId,
+          openedBy: creatorKeypair.publicKey,
+        })
+        .signers([creatorKeypair])
+        .rpc({commitment: 'confirmed'});
 
-          const updatedPurchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          expect(updatedPurchaseTicket.redeemed.toNumber()).is.equal(0);
-          expect(updatedPurchaseTicket.pendingRedemption.toNumber()).is.equal(quantity);
-          expect(updatedPurchaseTicket.remainingQuantity.toNumber()).is.equal(purchaseTicket.remainingQuantity - quantity);
-        });
+      const buyerBalanceBefore = await provider.connection.getBalance(buyerKeypair.publicKey, 'confirmed');
+      const payToBalanceBefore = await provider.connection.getBalance(payToAccountPubkey, 'confirmed');
+      const ticketBalanceBefore = await provider.connection.getBalance(purchaseTicketPda, 'confirmed');
 
-        it("Take Redemption", async ()=>{
-          const redemptionBefore = await program.account.redemption.fetch(redemptionPda);
-          const purchaseTicketPaymentBefore = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
-          const payToTokenAccountAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, purchaseTicket.payTo, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
-          const payToTokenAccount = await spl_token.getAccount(provider.connection, payToTokenAccountAddress);
-          const [productTicketTakerPda, productTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
-            [
-              anchor.utils.bytes.utf8.encode("product_taker"),
-              purchaseTicket.product.toBuffer(),
-              ticketTakerKeypair.publicKey.toBuffer(),
-            ], program.programId);
-          const [storeTicketTakerPda, storeTicketTakerPdaBump] = PublicKey.findProgramAddressSync(
-            [
-              anchor.utils.bytes.utf8.encode("store_taker"),
-              purchaseTicket.store.toBuffer(),
-              ticketTakerKeypair.publicKey.toBuffer(),
-            ], program.programId);
+      const buyerShareBasisPoints = 4000;
+      await program.methods
+        .resolveDisputeNative(buyerShareBasisPoints)
+        .accounts({
+          dispute: disputePda,
+          openedBy: creatorKeypair.publicKey,
+          ticket: purchaseTicketPda,
+          ticketAuthority: buyerKeypair.publicKey,
+          payTo: payToAccountPubkey,
+          payoutSplit: anchor.web3.SystemProgram.programId,
+          referrer: payToAccountPubkey,
+          redemption: anchor.web3.SystemProgram.programId,
+          arbiter: arbiterKeypair.publicKey,
+        })
+        .signers([arbiterKeypair])
+        .rpc({commitment: 'confirmed'});
+
+      const buyerShare = nativeProductPrice.toNumber() * buyerShareBasisPoints / 10000;
+      const merchantShare = nativeProductPrice.toNumber() - buyerShare;
+      expect(await provider.connection.getBalance(buyerKeypair.publicKey, 'confirmed')).is.equal(buyerBalanceBefore + buyerShare);
+      expect(await provider.connection.getBalance(payToAccountPubkey, 'confirmed')).is.equal(payToBalanceBefore + merchantShare);
+      expect(await provider.connection.getBalance(purchaseTicketPda, 'confirmed')).is.equal(ticketBalanceBefore - nativeProductPrice.toNumber());
+
+      const ticket = await program.account.purchaseTicket.fetch(purchaseTicketPda, 'confirmed');
+      expect(ticket.remainingQuantity.toNumber()).is.equal(0);
+      expect(ticket.dispute).is.eql(anchor.web3.SystemProgram.programId);
+      expect(await provider.connection.getAccountInfo(disputePda, 'confirmed')).is.null;
+    });
+  }); //native payment tests
+
+  //handing administration to a multisig is one way, so these run last
+  describe("[Admin Multisig Tests]", () => {
+    const CHANGE_AUTHORITY = 3;
+    const CHANGE_SIGNERS = 4;
+    const SET_PAUSED = 5;
+    const multisigSignerKeypairs = [Keypair.generate(), Keypair.generate()];
+    const multisigSigners = [provider.publicKey, ...multisigSignerKeypairs.map(k => k.publicKey)];
+    const noFee = {flatFee: new anchor.BN(0), basisPoints: 0, minFee: new anchor.BN(0), maxFee: new anchor.BN(0)};
+    const [adminMultisigPda, adminMultisigPdaBump] = PublicKey.findProgramAddressSync(
+      [
+        anchor.utils.bytes.utf8.encode("admin_multisig"),
+      ], program.programId);
 
-          let ticketTakerAddress = productTicketTakerPda;
-          let ticketTakerAccount = await program.account.ticketTaker.fetchNullable(productTicketTakerPda);
-          
-          if(!ticketTakerAccount) {
-            ticketTakerAddress = storeTicketTakerPda;
-            ticketTakerAccount = await program.account.ticketTaker.fetchNullable(storeTicketTakerPda);
-          }
-  
-          expect(ticketTakerAccount).to.not.be.null;
+    const adminProposalPda = (id: number) => PublicKey.findProgramAddressSync(
+      [
+        anchor.utils.bytes.utf8.encode("admin_proposal"),
+        adminMultisigPda.toBuffer(),
+        new anchor.BN(id).toArrayLike(Buffer, 'be', 8),
+      ], program.programId)[0];
 
-          const tx = await program.methods
-          .takeRedemption()
-          .accounts({
-            purchaseTicket: purchaseTicketPda,
-            redemption: redemptionPda,
-            ticketTaker: ticketTakerAddress,
-            ticketTakerSigner: ticketTakerKeypair.publicKey,
-            purchaseTicketPayment: purchaseTicket.payment,
-            purchaseTicketPaymentMint: paymentTokenMintAddress,
-            payToTokenAccount: payToTokenAccountAddress,
-            payTo: purchaseTicket.payTo,
-          })
-          .transaction();
-      
-          tx.feePayer = ticketTakerKeypair.publicKey;          
-          //tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;  
+    const propose = async (action: number, authority: PublicKey, secondaryAuthority: PublicKey, threshold: number, signers: PublicKey[], paused: number) => {
+      const adminMultisig = await program.account.adminMultisig.fetch(adminMultisigPda, 'confirmed');
+      const proposalPda = adminProposalPda(adminMultisig.proposalCount.toNumber());
 
-          const txSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [ticketTakerKeypair], {commitment: 'finalized'});
-          //console.info('ticket redemption signature: ', txSignature);
+      await program.methods
+        .createAdminProposal(action, noFee, PublicKey.default, authority, secondaryAuthority, threshold, signers, new anchor.BN(paused), [])
+        .accounts({
+          proposal: proposalPda,
+          adminMultisig: adminMultisigPda,
+          proposer: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
 
-          const redemptionAfter = await program.account.redemption.fetch(redemptionPda);
-          expect(redemptionAfter.ticketTaker).is.eql(ticketTakerAddress);
-          expect(redemptionAfter.ticketTakerSigner).is.eql(ticketTakerKeypair.publicKey);
-          expect(redemptionAfter.closeSlot.toNumber()).is.greaterThan(redemptionBefore.closeSlot.toNumber());
-          expect(redemptionAfter.closeTimestamp.toNumber()).is.greaterThan(redemptionBefore.closeTimestamp.toNumber());
-          expect(redemptionAfter.status).is.equal(1);
+      return proposalPda;
+    }
 
-          const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
-          expect(purchaseTicketPayment.address).is.eql(purchaseTicket.payment);
-          expect(purchaseTicketPayment.mint).is.eql(paymentTokenMintAddress);
-          expect(purchaseTicketPayment.amount).is.equal(purchaseTicketPaymentBefore.amount - BigInt(redemptionAfter.price.toNumber() * redemptionAfter.redeemQuantity.toNumber()));
-  
-          const updatedPayToTokenAccount = await spl_token.getAccount(provider.connection, payToTokenAccountAddress);
-          expect(updatedPayToTokenAccount.address).is.eql(payToTokenAccountAddress);
-          expect(updatedPayToTokenAccount.owner).is.eql(purchaseTicket.payTo);
-          expect(updatedPayToTokenAccount.mint).is.eql(paymentTokenMintAddress);
-          expect(updatedPayToTokenAccount.amount).is.equal(payToTokenAccount.amount + BigInt(purchaseTicket.price.toNumber() * redemptionBefore.redeemQuantity.toNumber()));
+    const approve = (proposalPda: PublicKey, signer: Keypair) => program.methods
+      .approveAdminProposal()
+      .accounts({
+        proposal: proposalPda,
+        adminMultisig: adminMultisigPda,
+        signer: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc({commitment: 'confirmed'});
 
-          const updatedPurchaseTicket = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          expect(updatedPurchaseTicket.redeemed.toNumber()).is.equal(redemptionBefore.redeemQuantity.toNumber());
-          expect(updatedPurchaseTicket.pendingRedemption.toNumber()).is.equal(0);
-        });
+    const execute = (proposalPda: PublicKey) => program.methods
+      .executeAdminProposal()
+      .accounts({
+        proposal: proposalPda,
+        adminMultisig: adminMultisigPda,
+        programMetadata: programMetadataPda,
+        executor: provider.publicKey,
+      })
+      .rpc({commitment: 'confirmed'});
 
-        it("Cancel Redemption", async()=>{
-          const cancelRedemptionQuantity = 1;       
-          const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          const purchaseTicketPayment = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
-          let cancelRedemptionNonce, cancelRedemptionPda, cancelRedemptionPdaBump;
+    it("Create admin multisig", async () => {
+      await program.methods
+        .createAdminMultisig(2, multisigSigners)
+        .accounts({
+          adminMultisig: adminMultisigPda,
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
+
+      const adminMultisig = await program.account.adminMultisig.fetch(adminMultisigPda, 'confirmed');
+      expect(adminMultisig.bump).is.equal(adminMultisigPdaBump);
+      expect(adminMultisig.threshold).is.equal(2);
+      expect(adminMultisig.signersVersion).is.equal(0);
+      expect(adminMultisig.signers).is.eql(multisigSigners);
+
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.multisig).is.eql(adminMultisigPda);
+      expect(programMetadata.authority).is.eql(PublicKey.default);
+      expect(programMetadata.secondaryAuthority).is.eql(PublicKey.default);
+
+      //program settings no longer take a single signer
+      await expectAnchorError(program.methods
+        .setPaused(new anchor.BN(0))
+        .accounts({
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc(), "ConstraintRaw");
+    });
 
-          do {
-            cancelRedemptionNonce = generateRandomU32(); //await provider.connection.getSlot();
-            [cancelRedemptionPda, cancelRedemptionPdaBump] = PublicKey.findProgramAddressSync(
-              [
-                anchor.utils.bytes.utf8.encode("redemption"),
-                purchaseTicketPda.toBuffer(),
-                Buffer.from(uIntToBytes(cancelRedemptionNonce,4,"setUint")),
-              ], program.programId);
-          } while((await program.account.redemption.fetchNullable(cancelRedemptionPda)) != null);
+    it("Propose, approve and execute authority change", async () => {
+      const proposalPda = await propose(CHANGE_AUTHORITY, provider.publicKey, secondaryAuthorityPubkey, 0, [], 0);
 
-          //console.log(`price=${purchaseTicketBefore.price.toNumber()}, remaining=${purchaseTicketBefore.remainingQuantity.toNumber()}`);
-          //console.log(`payment balance:${purchaseTicketPayment.amount}`);
+      const proposal = await program.account.adminProposal.fetch(proposalPda, 'confirmed');
+      expect(proposal.action).is.equal(CHANGE_AUTHORITY);
+      expect(proposal.proposer).is.eql(provider.publicKey);
+      expect(proposal.approvals).is.equal(1);
+      expect(proposal.executed).is.equal(false);
 
-          const initiateRedemptionTx = await program.methods
-            .initiateRedemption(cancelRedemptionNonce,new anchor.BN(cancelRedemptionQuantity), 0)
-            .accounts({
-              redemption: cancelRedemptionPda,
-              purchaseTicket: purchaseTicketPda,
-              purchaseTicketAuthority: purchaseTicketBefore.authority, //buyFor address
-              purchaseTicketPayment: purchaseTicketBefore.payment,
-              purchaseTicketPaymentMint: paymentTokenMintAddress,
-            })
-            .transaction();
-        
-          initiateRedemptionTx.feePayer = buyForKeypair.publicKey;
+      await expectAnchorError(execute(proposalPda), "NotEnoughApprovals");
 
-          const initiateRedemptionTxSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, initiateRedemptionTx, [buyForKeypair], {commitment: 'finalized'});
-          //console.log('initiateRedemptionTxSignature: ', initiateRedemptionTxSignature);
-          const cancelRedemptionPdaAccount = await program.account.redemption.fetchNullable(cancelRedemptionPda);
-          expect(cancelRedemptionPdaAccount).to.not.be.null;
-          expect(cancelRedemptionPdaAccount.redeemQuantity.toNumber()).is.equal(1);
+      //the proposer's approval is recorded when the proposal is created
+      await expectAnchorError(program.methods
+        .approveAdminProposal()
+        .accounts({
+          proposal: proposalPda,
+          adminMultisig: adminMultisigPda,
+          signer: provider.publicKey,
+        })
+        .rpc(), "AlreadyApproved");
 
-          const purchaseTicketBeforeCancel = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          expect(purchaseTicketBeforeCancel.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - cancelRedemptionQuantity);
-          expect(purchaseTicketBeforeCancel.pendingRedemption.toNumber()).is.equal(purchaseTicketBefore.pendingRedemption.toNumber() + cancelRedemptionQuantity)
+      await approve(proposalPda, multisigSignerKeypairs[0]);
+      await execute(proposalPda);
 
-          const cancelRedemptionTx = await program.methods
-            .cancelRedemption()
-            .accounts({
-              redemption: cancelRedemptionPda,
-              purchaseTicket: purchaseTicketPda,
-              purchaseTicketAuthority: buyForKeypair.publicKey, //buyFor address
-            })
-            .transaction();
-        
-          initiateRedemptionTx.feePayer = buyForKeypair.publicKey;
-          const cancelRedemptionTxSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, cancelRedemptionTx, [buyForKeypair], {commitment: 'finalized'});
-          //console.log('cancelRedemptionTxSignature: ', cancelRedemptionTxSignature);
-          const cancelledRedemptionPdaAccount = await program.account.redemption.fetchNullable(cancelRedemptionPda);
-          expect(cancelledRedemptionPdaAccount).to.be.null;
-          
-          const purchaseTicketAfterCancel = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          expect(purchaseTicketAfterCancel.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber());
-          expect(purchaseTicketAfterCancel.pendingRedemption.toNumber()).is.equal(purchaseTicketBefore.pendingRedemption.toNumber());
-        });
+      const executedProposal = await program.account.adminProposal.fetch(proposalPda, 'confirmed');
+      expect(executedProposal.approvals).is.equal(3);
+      expect(executedProposal.executed).is.equal(true);
 
-        it("Cancel Ticket", async() => {
-          const cancelQuantity = 1;
-          const buyerPaymentTokenAddress = await spl_token.getAssociatedTokenAddress(paymentTokenMintAddress, creatorKeypair.publicKey, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
-          const buyerPaymentTokenBefore = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
-          const purchaseTicketBefore = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          const purchaseTicketPaymentBefore = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
-          const productBefore = await program.account.product.fetch(purchaseTicket.product);
+      const programMetadata = await program.account.programMetadata.fetch(programMetadataPda, 'confirmed');
+      expect(programMetadata.authority).is.eql(provider.publicKey);
+      expect(programMetadata.secondaryAuthority).is.eql(secondaryAuthorityPubkey);
 
-          const tx = await program.methods
-            .cancelTicket(new anchor.BN(cancelQuantity))
-            .accounts({
-              product: purchaseTicket.product,
-              ticket: purchaseTicketPda,
-              ticketPayment: purchaseTicket.payment,
-              paymentReturn: buyerPaymentTokenAddress,
-              paymentMint: paymentTokenMintAddress,
-              ticketAuthority: buyForKeypair.publicKey,
-            })
-            .transaction();
-        
-          tx.feePayer = buyForKeypair.publicKey;
-          const txSignature = await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [buyForKeypair], {commitment: 'finalized'});
-          
-          const purchaseTicketAfter = await program.account.purchaseTicket.fetch(purchaseTicketPda);
-          expect(purchaseTicketAfter.remainingQuantity.toNumber()).is.equal(purchaseTicketBefore.remainingQuantity.toNumber() - cancelQuantity);
+      await expectAnchorError(execute(proposalPda), "AlreadyProcessed");
 
-          const productAfter = await program.account.product.fetch(purchaseTicket.product);
-          expect(productAfter.inventory.toNumber()).is.equal(productBefore.inventory.toNumber() + cancelQuantity);
-          
-          const purchaseTicketPaymentAfter = await spl_token.getAccount(provider.connection, purchaseTicket.payment);
-          expect(purchaseTicketPaymentAfter.amount).is.equal(purchaseTicketPaymentBefore.amount - BigInt(purchaseTicket.price.toNumber() * cancelQuantity));
+      //the authority the multisig installed runs the day to day instructions
+      await program.methods
+        .setAcceptedMintEnabled(true)
+        .accounts({
+          acceptedMint: acceptedMintPda,
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc({commitment: 'confirmed'});
 
-          const buyerPaymentTokenAfter = await spl_token.getAccount(provider.connection, buyerPaymentTokenAddress);
-          expect(buyerPaymentTokenAfter.amount).is.equal(buyerPaymentTokenBefore.amount + BigInt(purchaseTicket.price.toNumber() * cancelQuantity));
-        });
+      const acceptedMint = await program.account.acceptedMint.fetch(acceptedMintPda, 'confirmed');
+      expect(acceptedMint.enabled).is.equal(true);
 
-      }); //[Redeem Lone Product Ticket]
-  
-    }); //lone product - ticketed redemption tests
+      //but can't change what buyers pay
+      await expectAnchorError(program.methods
+        .changeAcceptedMintFee(true, noFee)
+        .accounts({
+          acceptedMint: acceptedMintPda,
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc(), "ConstraintRaw");
 
-  }); //lone product tests
+      await expectAnchorError(program.methods
+        .createCreatorFeeTier(noFee, new anchor.BN(0))
+        .accounts({
+          feeTier: pda("fee_tier", multisigSignerKeypairs[0].publicKey.toBuffer()),
+          creator: multisigSignerKeypairs[0].publicKey,
+          programMetadata: programMetadataPda,
+          authority: provider.publicKey,
+        })
+        .rpc(), "ConstraintRaw");
+    });
+
+    it("Proposals are stale after a signer change", async () => {
+      const pauseProposalPda = await propose(SET_PAUSED, PublicKey.default, PublicKey.default, 0, [], 0);
+      const signersProposalPda = await propose(CHANGE_SIGNERS, PublicKey.default, PublicKey.default, 2, multisigSigners.slice(0, 2), 0);
+
+      await approve(signersProposalPda, multisigSignerKeypairs[1]);
+      await execute(signersProposalPda);
+
+      const adminMultisig = await program.account.adminMultisig.fetch(adminMultisigPda, 'confirmed');
+      expect(adminMultisig.signersVersion).is.equal(1);
+      expect(adminMultisig.signers).is.eql(multisigSigners.slice(0, 2));
+
+      await expectAnchorError(approve(pauseProposalPda, multisigSignerKeypairs[0]), "StaleAdminProposal");
+      await expectAnchorError(execute(pauseProposalPda), "StaleAdminProposal");
+    });
+  }); //admin multisig tests
 
 }//RUN_STANDARD_TESTS
 
@@ -1436,6 +4870,7 @@ if(LOAD_MOCK_DATA)
             creator: creatorKeypair.publicKey,
             authority: creatorKeypair.publicKey,
             secondaryAuthority: secondaryAuthorityPubkey,
+            programMetadata: programMetadataPda,
         })
         .transaction();
 
@@ -1520,6 +4955,7 @@ if(LOAD_MOCK_DATA)
                   authority: creatorKeypair.publicKey,
                   secondaryAuthority: secondaryAuthorityPubkey,
                   payTo: payToAccountPubkey,
+                  acceptedMint: acceptedMintPda,
                 })
                 .transaction();
         } 
@@ -1543,6 +4979,7 @@ if(LOAD_MOCK_DATA)
                   authority: creatorKeypair.publicKey,
                   secondaryAuthority: secondaryAuthorityPubkey,
                   payTo: payToAccountPubkey,
+                  acceptedMint: acceptedMintPda,
                 })
                 .transaction();
         }